@@ -1,123 +1,236 @@
 use std::collections::{HashMap, HashSet};
 
 use eframe::{App, egui, Frame};
-use eframe::egui::{Button, ScrollArea, Ui};
+use eframe::egui::{Button, RichText, ScrollArea, Ui};
+use enum_iterator::all;
 use ordinal::Ordinal;
-use rand::rngs::ThreadRng;
+use rand::rngs::StdRng;
 
-use crate::data::Data;
-use crate::game::{Game, GameLogEvent, Scoreboard};
-use crate::league::{end_of_season, League, RECORD_STATS};
-use crate::player::{collect_all_active, generate_players, PlayerId, PlayerMap};
+use crate::engine::{Engine, GameConfig};
+use crate::game::{Game, Scoreboard, BOXSCORE_BATTING, BOXSCORE_PITCHING};
+use crate::league::{career_records, expand, League, LeagueEvent, CAREER_STATS, PROMOTION_COUNT, RECORD_STATS, SINGLE_GAME_STATS};
+use crate::player::{AgeProfile, Arsenal, ErrorType, Expect, Handedness, PlayerId, PlayerMap, Position};
 use crate::stat::{HistoricalStats, Stat, Stats};
-use crate::team::{Team, TeamId, TeamMap};
+use crate::team::{LeaguePosition, Team, TeamId, TeamMap, MAX_ROSTER_SIZE};
 
 #[derive(Copy, Clone, PartialEq)]
+#[cfg_attr(feature = "persistence", derive(serde::Deserialize, serde::Serialize))]
 enum Mode {
     Schedule(usize, Option<usize>),
     BoxScore(usize, usize),
     GameLog(usize, usize),
     Standings(usize),
     Team(usize, TeamId),
+    DepthChart(usize, TeamId),
+    TeamScoring(usize, TeamId),
     Player(usize, PlayerId, Option<TeamId>),
-    BatLeaders(usize, Stat, bool),
-    PitLeaders(usize, Stat, bool),
+    BatLeaders(usize, Stat, bool, Option<Position>, bool),
+    PitLeaders(usize, Stat, bool, Option<Position>, bool),
     LeagueRecords(usize),
+    LeagueStats(usize),
+    LeagueHistory(usize),
+    RunExpectancy(usize),
+    SeasonSummary(u32),
+    // (away, home)
+    Exhibition(TeamId, TeamId),
+    Compare(PlayerId, PlayerId),
+    CompareTeams(TeamId, TeamId),
+    News,
+    Dashboard,
 }
 
-/// We derive Deserialize/Serialize, so we can persist app state on shutdown.
-#[cfg_attr(feature = "persistence", derive(serde::Deserialize, serde::Serialize))]
-pub struct Imp019App {
-    rng: ThreadRng,
-    data: Data,
+impl Mode {
+    fn league(&self) -> usize {
+        match self {
+            Mode::Schedule(league, _) |
+            Mode::BoxScore(league, _) |
+            Mode::GameLog(league, _) |
+            Mode::Standings(league) |
+            Mode::Team(league, _) |
+            Mode::DepthChart(league, _) |
+            Mode::TeamScoring(league, _) |
+            Mode::Player(league, _, _) |
+            Mode::BatLeaders(league, _, _, _, _) |
+            Mode::PitLeaders(league, _, _, _, _) |
+            Mode::LeagueRecords(league) |
+            Mode::LeagueStats(league) |
+            Mode::LeagueHistory(league) |
+            Mode::RunExpectancy(league) => *league,
+            Mode::SeasonSummary(_) | Mode::Exhibition(_, _) | Mode::Compare(_, _) | Mode::CompareTeams(_, _) | Mode::News | Mode::Dashboard => 0,
+        }
+    }
+
+    // a restored or carried-over mode can point at things that are gone
+    fn is_valid(&self, leagues: &[League], teams: &TeamMap, players: &PlayerMap, favorite: Option<TeamId>) -> bool {
+        if *self != Mode::News && self.league() >= leagues.len() {
+            return false;
+        }
+        match self {
+            Mode::BoxScore(league, game) | Mode::GameLog(league, game) => *game < leagues[*league].schedule.games.len(),
+            Mode::Team(_, team_id) | Mode::DepthChart(_, team_id) | Mode::TeamScoring(_, team_id) => teams.contains_key(team_id),
+            Mode::Player(_, player_id, team_id) => players.contains_key(player_id) && team_id.is_none_or(|o| teams.contains_key(&o)),
+            Mode::Dashboard => favorite.is_some_and(|o| teams.contains_key(&o)),
+            Mode::Exhibition(away, home) => teams.contains_key(away) && teams.contains_key(home),
+            Mode::Compare(left, right) => players.contains_key(left) && players.contains_key(right),
+            Mode::CompareTeams(left, right) => teams.contains_key(left) && teams.contains_key(right),
+            _ => true,
+        }
+    }
+}
+
+// everything simming a day can change, kept so the last day can be undone
+#[derive(Clone)]
+struct Snapshot {
     player_map: PlayerMap,
     team_map: TeamMap,
     leagues: Vec<League>,
     year: u32,
+    events: Vec<LeagueEvent>,
+    rng: StdRng,
+}
+
+/// We derive Deserialize/Serialize, so we can persist app state on shutdown.
+#[cfg_attr(feature = "persistence", derive(serde::Deserialize, serde::Serialize))]
+pub struct Imp019App {
+    engine: Engine,
     disp_mode: Mode,
     sim_all: bool,
+    new_game: Option<(GameConfig, Option<String>)>,
+    favorite: Option<TeamId>,
+    undo: Option<Snapshot>,
 }
 
 impl Default for Imp019App {
     fn default() -> Self {
         Imp019App {
-            rng: rand::thread_rng(),
-            data: Data::new(),
-            player_map: HashMap::new(),
-            team_map: HashMap::new(),
-            leagues: Vec::new(),
-            year: 2030,
+            engine: Engine::default(),
             disp_mode: Mode::Schedule(0, None),
             sim_all: false,
+            new_game: None,
+            favorite: None,
+            undo: None,
         }
     }
 }
 
 impl Imp019App {
-    pub fn new(_cc: &eframe::CreationContext<'_>) -> Self {
-        let mut rng = rand::thread_rng();
-        let data = Data::new();
-        let year = 2049;
+    const DEFAULT_KEEP_LOG_DAYS: usize = 7;
+    const MAX_KEEP_LOG_DAYS: usize = 162;
 
-        let mut players = HashMap::new();
-        generate_players(&mut players, 3600, year, &data, &mut rng);
+    pub fn new(_cc: &eframe::CreationContext<'_>) -> Self {
+        Self::new_with_config(&GameConfig::default())
+    }
 
-        let mut available = collect_all_active(&players);
+    pub(crate) fn new_with_config(config: &GameConfig) -> Self {
+        Imp019App {
+            engine: Engine::with_config(config),
+            disp_mode: Mode::Schedule(0, None),
+            sim_all: false,
+            new_game: None,
+            favorite: None,
+            undo: None,
+        }
+    }
 
-        let locs = data.get_locs(&mut HashSet::new(), &mut rng, 60);
-        let nicks = data.get_nicks(&mut HashSet::new(), &mut rng, 60);
+    fn snapshot(&self) -> Snapshot {
+        Snapshot {
+            player_map: self.engine.player_map.clone(),
+            team_map: self.engine.team_map.clone(),
+            leagues: self.engine.leagues.clone(),
+            year: self.engine.year,
+            events: self.engine.events.clone(),
+            rng: self.engine.rng.clone(),
+        }
+    }
 
-        let mut teams = HashMap::new();
-        teams.reserve(60);
-        for team_id in 0..60 {
-            let loc = locs[team_id].clone();
-            let nick = nicks[team_id].clone();
-            let mut team = Team::new(loc, nick, year);
+    fn undo_day(&mut self) {
+        if let Some(snapshot) = self.undo.take() {
+            self.engine.player_map = snapshot.player_map;
+            self.engine.team_map = snapshot.team_map;
+            self.engine.leagues = snapshot.leagues;
+            self.engine.year = snapshot.year;
+            self.engine.events = snapshot.events;
+            self.engine.rng = snapshot.rng;
+            self.engine.career_records = career_records(&self.engine.player_map);
+        }
+    }
 
-            team.populate(&mut available, &players);
+    // the state a game started from, only held for the last simmed day's games
+    fn replay_start(&self, league_idx: usize, game_idx: usize) -> Option<&Snapshot> {
+        let league = &self.engine.leagues[league_idx];
+        let game = &league.schedule.games[game_idx];
+        self.undo.as_ref()
+            .filter(|o| o.year == self.engine.year && (o.leagues[league_idx].cur_idx..league.cur_idx).contains(&game_idx))
+            .filter(|_| game.has_playbyplay())
+    }
 
-            let team_id = (team_id + 1) as TeamId;
-            teams.insert(team_id, team);
+    fn sim_day(&mut self) {
+        self.undo = Some(self.snapshot());
+        if self.engine.sim_day() {
+            self.disp_mode = Mode::SeasonSummary(self.engine.year - 1);
         }
+    }
 
-        let mut remaining_teams = teams.keys().copied().collect();
-
-        let leagues = vec![
-            League::new(1, 20, &mut remaining_teams, &mut rng),
-            League::new(2, 20, &mut remaining_teams, &mut rng),
-            League::new(3, 20, &mut remaining_teams, &mut rng),
-        ];
+    // mirrors the menu and side panel buttons, ignored while typing into a text field
+    fn handle_shortcuts(&mut self, ctx: &egui::Context) {
+        if ctx.wants_keyboard_input() {
+            return;
+        }
 
-        Imp019App {
-            rng,
-            data,
-            player_map: players,
-            team_map: teams,
-            leagues,
-            year,
-            disp_mode: Mode::Schedule(0, None),
-            sim_all: false,
+        let league = self.disp_mode.league();
+        let pressed = |key| ctx.input(|o| o.key_pressed(key));
+        if pressed(egui::Key::Space) {
+            self.sim_day();
+        }
+        if pressed(egui::Key::A) {
+            self.sim_all = true;
+        }
+        if pressed(egui::Key::U) {
+            self.undo_day();
+        }
+        if pressed(egui::Key::C) {
+            self.disp_mode = Mode::Schedule(league, None);
+        }
+        if pressed(egui::Key::S) {
+            self.disp_mode = Mode::Standings(league);
+        }
+        if pressed(egui::Key::B) {
+            self.disp_mode = Mode::BatLeaders(league, Stat::Bhr, true, None, true);
+        }
+        if pressed(egui::Key::P) {
+            self.disp_mode = Mode::PitLeaders(league, Stat::Pw, true, None, true);
+        }
+        if pressed(egui::Key::R) {
+            self.disp_mode = Mode::LeagueRecords(league);
+        }
+        if pressed(egui::Key::L) {
+            self.disp_mode = Mode::LeagueStats(league);
+        }
+        if pressed(egui::Key::N) {
+            self.disp_mode = Mode::News;
+        }
+        if pressed(egui::Key::D) && self.favorite.is_some() {
+            self.disp_mode = Mode::Dashboard;
         }
-    }
 
-    pub fn update(&mut self) -> bool {
-        let mut result = false;
-        for league in &mut self.leagues {
-            result = league.sim(&mut self.team_map, &mut self.player_map, self.year, &mut self.rng) || result;
+        // number keys jump to a league's schedule
+        for (idx, key) in [egui::Key::Num1, egui::Key::Num2, egui::Key::Num3].iter().enumerate() {
+            if idx < self.engine.leagues.len() && pressed(*key) {
+                self.disp_mode = Mode::Schedule(idx, None);
+            }
         }
-        result
     }
 }
 
-fn as_league(value: Option<u32>) -> String {
-    if let Some(pos) = value {
-        format!("{} in League {}", Ordinal(pos % 100), pos / 100)
-    } else {
-        "---".to_string()
-    }
+fn as_league(value: Option<LeaguePosition>) -> String {
+    value.map_or("---".to_string(), |o| o.to_string())
+}
+
+fn highlight_color(ui: &Ui) -> egui::Color32 {
+    ui.visuals().selection.bg_fill.gamma_multiply(0.4)
 }
 
-fn display_game(ui: &mut Ui, game: &Game, teams: &TeamMap) -> bool {
+fn display_game(ui: &mut Ui, game: &Game, teams: &TeamMap, highlight: bool) -> bool {
     let home_team = teams.get(&game.home.id).unwrap();
     let away_team = teams.get(&game.away.id).unwrap();
 
@@ -125,7 +238,11 @@ fn display_game(ui: &mut Ui, game: &Game, teams: &TeamMap) -> bool {
 
     let mut clicked = false;
 
-    ui.group(|ui| {
+    let mut frame = egui::Frame::group(ui.style());
+    if highlight {
+        frame = frame.fill(highlight_color(ui));
+    }
+    frame.show(ui, |ui| {
         ui.vertical(|ui| {
             if complete {
                 ui.horizontal(|ui| {
@@ -165,17 +282,8 @@ fn display_game(ui: &mut Ui, game: &Game, teams: &TeamMap) -> bool {
 fn display_bo(ui: &mut Ui, scoreboard: &Scoreboard, team: &Team, players: &PlayerMap, stat_map: &HashMap<PlayerId, Vec<Stat>>) {
     ui.label(format!("{} {} Batters", team.abbr(), team.nickname()));
 
-    const HEADERS: [Stat; 6] = [
-        Stat::Bab,
-        Stat::Br,
-        Stat::Bh,
-        Stat::Brbi,
-        Stat::Bbb,
-        Stat::Bso,
-    ];
-
-    for header in HEADERS.iter() {
-        ui.monospace(header.to_string());
+    for header in BOXSCORE_BATTING.iter() {
+        ui.monospace(header.to_string()).on_hover_text(header.explanation());
     }
     ui.monospace(Stat::Bavg.to_string());
     ui.monospace("OPS");
@@ -189,7 +297,7 @@ fn display_bo(ui: &mut Ui, scoreboard: &Scoreboard, team: &Team, players: &Playe
 
         ui.label(format!("{}. {} {}", idx + 1, batter.fname(), def.pos));
 
-        for header in HEADERS.iter() {
+        for header in BOXSCORE_BATTING.iter() {
             ui.monospace(header.value(stats.get_stat(*header)).to_string());
         }
         ui.monospace(Stat::Bavg.value(full_stats.get_stat(Stat::Bavg)).to_string());
@@ -199,95 +307,50 @@ fn display_bo(ui: &mut Ui, scoreboard: &Scoreboard, team: &Team, players: &Playe
     }
 }
 
-fn display_pitching(ui: &mut Ui, scoreboard: &Scoreboard, team: &Team, players: &PlayerMap, stat_map: &HashMap<PlayerId, Vec<Stat>>) {
+fn display_pitching(ui: &mut Ui, scoreboard: &Scoreboard, team: &Team, players: &PlayerMap) {
     ui.label(format!("{} {} Pitchers", team.abbr(), team.nickname()));
 
-    const HEADERS: [Stat; 7] = [
-        Stat::Po,
-        Stat::Ph,
-        Stat::Pr,
-        Stat::Per,
-        Stat::Pbb,
-        Stat::Pso,
-        Stat::Phr,
-    ];
-
-    for header in HEADERS.iter() {
-        ui.monospace(header.to_string());
+    for header in BOXSCORE_PITCHING.iter() {
+        ui.monospace(header.to_string()).on_hover_text(header.explanation());
     }
+    ui.monospace("P");
+    ui.monospace("BF");
     ui.monospace(Stat::Pera.to_string());
     ui.end_row();
 
     for rec in scoreboard.pitcher_record.iter() {
         let pitcher = players.get(&rec.pitcher).unwrap();
-
-        let stats = Stats::compile_stats(stat_map.get(&rec.pitcher).unwrap_or(&Vec::new()));
         let full_stats = pitcher.get_stats();
 
         ui.label(pitcher.fname());
-        for header in HEADERS.iter() {
-            ui.monospace(header.value(stats.get_stat(*header)).to_string());
+        for (header, value) in BOXSCORE_PITCHING.iter().zip(rec.boxscore_values()) {
+            ui.monospace(header.value(value as u32));
         }
 
+        ui.monospace(rec.pitches.to_string());
+        ui.monospace(rec.bf.to_string());
         ui.monospace(Stat::Pera.value(full_stats.p_era));
         ui.end_row();
     }
 }
 
-fn for_each_event<T>(game: &Game, mut action: T) where T: FnMut(usize, bool, &GameLogEvent, bool) {
-    let mut inning = 1;
-    let mut tophalf = true;
-    let mut outs = 0;
-
-    let mut error = false;
-    for event in game.playbyplay.iter() {
-        action(inning, tophalf, event, error);
-
-        if event.event == Stat::Fe {
-            error = true;
-        }
-
-        if event.event == Stat::Bgidp {
-            outs += 1;  // add the second out below
-        }
-
-        if matches!( event.event, Stat::Bo | Stat::Bso | Stat::Bgidp | Stat::Bcs) {
-            if !error {
-                outs += 1;
-            }
-            error = false;
-            if outs == 3 {
-                if !tophalf {
-                    inning += 1;
-                }
-                tophalf = !tophalf;
-                outs = 0;
-            }
-        }
-    }
-}
-
-fn display_team_stats(ui: &mut Ui, is_batter: bool, headers: &[Stat], team_players: &[PlayerId], players: &PlayerMap) -> Option<PlayerId> {
+fn display_team_stats(ui: &mut Ui, is_batter: bool, headers: &[Stat], team: &Team, players: &PlayerMap) -> Option<PlayerId> {
     ui.label("Name");
     ui.label("Pos");
 
 
     for header in headers {
-        ui.label(header.to_string());
+        ui.label(header.to_string()).on_hover_text(header.explanation());
     }
     ui.end_row();
 
 
     let mut ret = None;
-    for player_id in team_players {
-        let player = players.get(player_id).unwrap();
-        if player.pos.is_pitcher() == is_batter {
-            continue;
-        }
+    for (player_id, player) in team.iter_players(players).filter(|o| o.1.pos.is_pitcher() != is_batter) {
         let stats = player.get_stats();
 
-        if ui.add(Button::new(&player.fullname()).frame(false)).clicked() {
-            ret = Some(*player_id);
+        if ui.add(Button::new(player.fullname()).frame(false)).clicked() {
+            ret = Some(player_id);
         }
         ui.label(player.pos.to_string());
 
@@ -297,6 +360,14 @@ fn display_team_stats(ui: &mut Ui, is_batter: bool, headers: &[Stat], team_playe
         ui.end_row();
     }
 
+    let total = team.total_stats(players, is_batter);
+    ui.label(RichText::new("TOTAL").strong());
+    ui.label("");
+    for header in headers {
+        ui.label(RichText::new(header.value(total.get_stat(*header))).strong());
+    }
+    ui.end_row();
+
     ret
 }
 
@@ -310,12 +381,149 @@ fn display_historical_stat_row(ui: &mut Ui, headers: &[Stat], stats: &Stats, yea
     ui.end_row();
 }
 
+fn display_trend(ui: &mut Ui, stat: Stat, points: &[(u32, u32)]) {
+    let (response, painter) = ui.allocate_painter(egui::vec2(480.0, 160.0), egui::Sense::hover());
+    let rect = response.rect.shrink(24.0);
+    painter.rect_stroke(rect, 0.0, ui.visuals().widgets.noninteractive.bg_stroke);
+
+    let max = points.iter().map(|o| o.1).max().unwrap_or(0).max(1);
+    let step = rect.width() / points.len().max(2).saturating_sub(1) as f32;
+    let to_pos = |idx: usize, value: u32| egui::pos2(rect.left() + step * idx as f32, rect.bottom() - rect.height() * value as f32 / max as f32);
+
+    let color = ui.visuals().selection.bg_fill;
+    let line = points.iter().enumerate().map(|(idx, o)| to_pos(idx, o.1)).collect::<Vec<_>>();
+    painter.add(egui::Shape::line(line.clone(), egui::Stroke::new(2.0, color)));
+
+    let text_color = ui.visuals().text_color();
+    let font = egui::FontId::proportional(11.0);
+    for (pos, (year, value)) in line.iter().zip(points) {
+        painter.circle_filled(*pos, 3.0, color);
+        painter.text(*pos - egui::vec2(0.0, 4.0), egui::Align2::CENTER_BOTTOM, stat.value(*value), font.clone(), text_color);
+        painter.text(egui::pos2(pos.x, rect.bottom() + 4.0), egui::Align2::CENTER_TOP, year.to_string(), font.clone(), text_color);
+    }
+}
+
+const SUMMARY_SECTIONS: [&str; 6] = ["Champions", "Records", "Promotion and Relegation", "Franchises", "Milestones", "Retirements"];
+
+// the end of season routines already report everything, the summary just groups it by kind
+fn summary_section(event: &LeagueEvent) -> usize {
+    match event {
+        LeagueEvent::Champion(..) | LeagueEvent::Tiebreaker(..) => 0,
+        LeagueEvent::Record(..) => 1,
+        LeagueEvent::Promoted(..) | LeagueEvent::Relegated(..) => 2,
+        LeagueEvent::Relocated(..) | LeagueEvent::Expansion(..) => 3,
+        LeagueEvent::Milestone(..) => 4,
+        LeagueEvent::Retired(..) => 5,
+    }
+}
+
+fn display_event(ui: &mut Ui, event: &LeagueEvent, teams: &TeamMap, players: &PlayerMap) -> Option<Mode> {
+    let mut mode = None;
+    ui.horizontal(|ui| {
+        let (league, player_id, team_id, text) = match event {
+            LeagueEvent::Milestone(milestone) => {
+                ui.label(format!("{} Day {}:", milestone.year, milestone.day));
+                (milestone.league, Some(milestone.player_id), None, format!("reaches {} career {}", milestone.total, milestone.stat))
+            }
+            LeagueEvent::Record(league, stat, record) => {
+                ui.label(format!("{}:", record.year));
+                (*league, Some(record.player_id), Some(record.team_id), format!("sets the League {} {} record at {}", league, stat, stat.value(record.record)))
+            }
+            LeagueEvent::Champion(league, team_id, year) => {
+                ui.label(format!("{}:", year));
+                (*league, None, Some(*team_id), format!("win League {}", league))
+            }
+            LeagueEvent::Promoted(league, team_id, from, year) => {
+                ui.label(format!("{}:", year));
+                (*league, None, Some(*team_id), format!("promoted from League {} to League {}", from, league))
+            }
+            LeagueEvent::Relegated(league, team_id, from, year) => {
+                ui.label(format!("{}:", year));
+                (*league, None, Some(*team_id), format!("relegated from League {} to League {}", from, league))
+            }
+            LeagueEvent::Expansion(league, team_id, year) => {
+                ui.label(format!("{}:", year));
+                (*league, None, Some(*team_id), format!("join League {} as an expansion team", league))
+            }
+            LeagueEvent::Tiebreaker(league, winner, loser, year) => {
+                ui.label(format!("{}:", year));
+                (*league, None, Some(*winner), format!("beat the {} in a tiebreaker", teams.get(loser).unwrap().name()))
+            }
+            LeagueEvent::Relocated(league, team_id, old_name, year) => {
+                ui.label(format!("{}: The {} relocate and become the", year, old_name));
+                (*league, None, Some(*team_id), String::new())
+            }
+            LeagueEvent::Retired(league, team_id, player_id, year) => {
+                ui.label(format!("{}:", year));
+                let seasons = players.get(player_id).unwrap().historical.len();
+                (*league, Some(*player_id), Some(*team_id), format!("retires after {} seasons", seasons))
+            }
+        };
+
+        // league ids are one more than their index
+        let league_idx = league as usize - 1;
+        if let Some(player_id) = player_id {
+            let player = players.get(&player_id).unwrap();
+            if ui.add(Button::new(player.fullname()).frame(false)).clicked() {
+                mode = Some(Mode::Player(league_idx, player_id, team_id));
+            }
+        } else if let Some(team_id) = team_id {
+            let team = teams.get(&team_id).unwrap();
+            if ui.add(Button::new(team.name()).frame(false)).clicked() {
+                mode = Some(Mode::Team(league_idx, team_id));
+            }
+        }
+        ui.label(text);
+    });
+
+    mode
+}
+
+fn display_fielding(ui: &mut Ui, team: &Team, players: &PlayerMap) {
+    ui.label("Pos");
+    for header in &FIELDING_HEADERS {
+        ui.label(header.to_string()).on_hover_text(header.explanation());
+    }
+    ui.end_row();
+
+    for pos in all::<Position>().filter(|o| *o != Position::DesignatedHitter) {
+        let mut at_pos = team.iter_players(players).filter(|o| o.1.pos == pos).peekable();
+        if at_pos.peek().is_none() {
+            continue;
+        }
+
+        let mut total = Stats::default();
+        for (_, player) in at_pos {
+            total.compile(&player.get_stats());
+        }
+
+        ui.label(pos.to_string());
+        for header in &FIELDING_HEADERS {
+            ui.label(header.value(total.get_stat(*header)));
+        }
+        ui.end_row();
+    }
+}
+
+// lines from seasons without a stored league baseline are left as they were
+fn neutralize_history(historical: &[HistoricalStats], leagues: &[League]) -> Vec<HistoricalStats> {
+    historical.iter().map(|o| {
+        let baseline = leagues.get(o.league as usize - 1).and_then(|league| league.history.iter().find(|season| season.year == o.year));
+        HistoricalStats {
+            year: o.year,
+            league: o.league,
+            team: o.team,
+            stats: baseline.map_or_else(|| o.stats.clone(), |season| o.stats.neutralized(&season.total)),
+        }
+    }).collect()
+}
+
 fn display_historical_stats(ui: &mut Ui, headers: &[Stat], historical: &[HistoricalStats], teams: &TeamMap) -> Stats {
     ui.label("Year");
     ui.label("League");
     ui.label("Team");
     for header in headers {
-        ui.label(header.to_string());
+        ui.label(header.to_string()).on_hover_text(header.explanation());
     }
     ui.end_row();
 
@@ -331,10 +539,85 @@ fn display_historical_stats(ui: &mut Ui, headers: &[Stat], historical: &[Histori
     total
 }
 
+const MAX_SEARCH_RESULTS: usize = 10;
+
+// lower is better for the stats sorted in reverse
+fn is_better(stat: Stat, value: u32, other: u32) -> bool {
+    if stat.is_reverse_sort() { value < other } else { value > other }
+}
+
+// a name filter over every player, returning whoever was picked from the matches
+fn player_search(ui: &mut Ui, id_source: &str, players: &PlayerMap) -> Option<PlayerId> {
+    let id = ui.make_persistent_id(id_source);
+    let mut text = ui.data_mut(|o| o.get_temp_mut_or_default::<String>(id).clone());
+    ui.add(egui::TextEdit::singleline(&mut text).hint_text("Search players"));
+
+    let mut picked = None;
+    let needle = text.trim().to_lowercase();
+    if needle.len() >= 2 {
+        for (player_id, player) in players.iter().filter(|o| o.1.fullname().to_lowercase().contains(&needle)).take(MAX_SEARCH_RESULTS) {
+            if ui.selectable_label(false, format!("{} ({}, born {})", player.fullname(), player.pos, player.born)).clicked() {
+                picked = Some(*player_id);
+            }
+        }
+    }
+    if picked.is_some() {
+        text.clear();
+    }
+    ui.data_mut(|o| o.insert_temp(id, text));
+    picked
+}
+
+// one row of a side by side comparison, highlighting whichever side leads
+fn display_compare_row(ui: &mut Ui, label: &str, text: [String; 2], better: [bool; 2]) {
+    ui.label(label);
+    for (text, better) in text.iter().zip(&better) {
+        let text = RichText::new(text.as_str());
+        ui.label(if *better { text.strong().background_color(highlight_color(ui)) } else { text });
+    }
+    ui.end_row();
+}
+
 fn display_leaders(ui: &mut Ui, is_batter: bool, headers: &[Stat], league: &League, teams: &TeamMap, players: &PlayerMap, mut mode: Mode) -> Mode {
-    let (disp_league, result, reverse) = match mode {
-        Mode::BatLeaders(disp_league, result, reverse) => (disp_league, result, reverse),
-        Mode::PitLeaders(disp_league, result, reverse) => (disp_league, result, reverse),
+    let (filter, qualified) = match mode {
+        Mode::BatLeaders(_, _, _, filter, qualified) => (filter, qualified),
+        Mode::PitLeaders(_, _, _, filter, qualified) => (filter, qualified),
+        _ => panic!(),
+    };
+
+    ui.horizontal_wrapped(|ui| {
+        let positions = all::<Position>().filter(|o| o.is_pitcher() != is_batter).map(Some);
+        for pos in std::iter::once(None).chain(positions) {
+            let label = pos.map_or("All".to_string(), |o| o.to_string());
+            if ui.selectable_label(pos == filter, label).clicked() {
+                mode = match mode {
+                    Mode::BatLeaders(disp_league, result, reverse, _, qualified) => Mode::BatLeaders(disp_league, result, reverse, pos, qualified),
+                    Mode::PitLeaders(disp_league, result, reverse, _, qualified) => Mode::PitLeaders(disp_league, result, reverse, pos, qualified),
+                    _ => panic!(),
+                }
+            }
+        }
+        ui.separator();
+        if ui.selectable_label(qualified, "Qualified").clicked() {
+            mode = match mode {
+                Mode::BatLeaders(disp_league, result, reverse, filter, _) => Mode::BatLeaders(disp_league, result, reverse, filter, !qualified),
+                Mode::PitLeaders(disp_league, result, reverse, filter, _) => Mode::PitLeaders(disp_league, result, reverse, filter, !qualified),
+                _ => panic!(),
+            }
+        }
+    });
+
+    egui::Grid::new(if is_batter { "bleaders" } else { "pleaders" }).striped(true).show(ui, |ui| {
+        mode = display_leaders_grid(ui, is_batter, headers, league, teams, players, mode);
+    });
+
+    mode
+}
+
+fn display_leaders_grid(ui: &mut Ui, is_batter: bool, headers: &[Stat], league: &League, teams: &TeamMap, players: &PlayerMap, mut mode: Mode) -> Mode {
+    let (disp_league, result, reverse, filter, qualified) = match mode {
+        Mode::BatLeaders(disp_league, result, reverse, filter, qualified) => (disp_league, result, reverse, filter, qualified),
+        Mode::PitLeaders(disp_league, result, reverse, filter, qualified) => (disp_league, result, reverse, filter, qualified),
         _ => panic!(),
     };
 
@@ -344,11 +627,11 @@ fn display_leaders(ui: &mut Ui, is_batter: bool, headers: &[Stat], league: &Leag
     ui.label("Pos");
 
     for header in headers {
-        if ui.button(header.to_string()).clicked() {
+        if ui.button(header.to_string()).on_hover_text(header.explanation()).clicked() {
             let flip = if *header == result { !reverse } else { !header.is_reverse_sort() };
             mode = match mode {
-                Mode::BatLeaders(disp_league, _, _) => Mode::BatLeaders(disp_league, *header, flip),
-                Mode::PitLeaders(disp_league, _, _) => Mode::PitLeaders(disp_league, *header, flip),
+                Mode::BatLeaders(disp_league, _, _, filter, qualified) => Mode::BatLeaders(disp_league, *header, flip, filter, qualified),
+                Mode::PitLeaders(disp_league, _, _, filter, qualified) => Mode::PitLeaders(disp_league, *header, flip, filter, qualified),
                 _ => panic!(),
             }
         }
@@ -356,39 +639,27 @@ fn display_leaders(ui: &mut Ui, is_batter: bool, headers: &[Stat], league: &Leag
 
     ui.end_row();
 
-    let mut all_players = Vec::new();
-
-    for team_id in &league.teams {
-        let team = &teams.get(team_id).unwrap();
-        let games = team.results.games();
+    let league_total = league.total_stats(teams, players);
+    let team_of = league.teams.iter().flat_map(|o| teams.get(o).unwrap().players.iter().map(move |p| (*p, *o))).collect::<HashMap<_, _>>();
 
-        for player_id in &team.players {
-            let player = players.get(player_id).unwrap();
-            if player.pos.is_pitcher() != is_batter {
-                let stats = player.get_stats();
-                if result.is_qualified(&stats, games) {
-                    all_players.push((team.abbr(), player, stats, player_id));
-                }
-            }
-        }
+    let mut leaders = league.leaders(result, is_batter, qualified, teams, players);
+    if reverse == result.is_reverse_sort() {
+        leaders.reverse();
     }
 
-    all_players.sort_by_key(|o| o.2.get_stat(result));
-    if reverse {
-        all_players.reverse()
-    };
-
-    for (rank, ap) in all_players.iter().enumerate() {
-        let player = ap.1;
+    for (rank, (player_id, _)) in leaders.iter().filter(|o| filter.is_none_or(|pos| players.get(&o.0).unwrap().pos == pos)).enumerate() {
+        let player = players.get(player_id).unwrap();
+        let team = teams.get(&team_of[player_id]).unwrap();
 
         ui.label(format!("{}", rank + 1));
         if ui.add(Button::new(player.fullname()).frame(false)).clicked() {
-            mode = Mode::Player(disp_league, *ap.3, None);
+            mode = Mode::Player(disp_league, *player_id, None);
         }
-        ui.label(ap.0);
-        ui.label(ap.1.pos.to_string());
+        ui.label(team.abbr());
+        ui.label(player.pos.to_string());
 
-        let stats = &ap.2;
+        let mut stats = player.get_stats();
+        stats.calc_war(&league_total);
 
         for header in headers {
             ui.label(header.value(stats.get_stat(*header)));
@@ -398,7 +669,27 @@ fn display_leaders(ui: &mut Ui, is_batter: bool, headers: &[Stat], league: &Leag
     mode
 }
 
-const BATTING_HEADERS: [Stat; 20] = [
+fn display_league_stats(ui: &mut Ui, headers: &[Stat], league: &League, total: &Stats) {
+    ui.label("");
+    for header in headers {
+        ui.label(header.to_string()).on_hover_text(header.explanation());
+    }
+    ui.end_row();
+
+    ui.label("Total");
+    for header in headers {
+        ui.label(header.value(total.get_stat(*header)));
+    }
+    ui.end_row();
+
+    ui.label("Per Team");
+    for header in headers {
+        ui.label(header.value(league.team_average(*header, total)));
+    }
+    ui.end_row();
+}
+
+const BATTING_HEADERS: [Stat; 22] = [
     Stat::G,
     Stat::Gs,
     Stat::Bpa,
@@ -414,22 +705,33 @@ const BATTING_HEADERS: [Stat; 20] = [
     Stat::Bgidp,
     Stat::Bsb,
     Stat::Bcs,
+    Stat::Boob,
     Stat::Br,
     Stat::Brbi,
     Stat::Bavg,
     Stat::Bobp,
     Stat::Bslg,
+    Stat::Bwoba,
 ];
 
-const PITCHING_HEADERS: [Stat; 25] = [
+const FIELDING_HEADERS: [Stat; 4] = [
+    Stat::Fpo,
+    Stat::Fa,
+    Stat::Fe,
+    Stat::Ffpct,
+];
+
+const PITCHING_HEADERS: [Stat; 30] = [
     Stat::G,
     Stat::Pw,
     Stat::Pl,
     Stat::Psv,
     Stat::Pbs,
+    Stat::Psvpct,
     Stat::Phld,
     Stat::Pcg,
     Stat::Psho,
+    Stat::Pqs,
     Stat::Po,
     Stat::Pbf,
     Stat::Ph,
@@ -442,8 +744,11 @@ const PITCHING_HEADERS: [Stat; 25] = [
     Stat::Pso,
     Stat::Pr,
     Stat::Per,
+    Stat::Pir,
+    Stat::Pirs,
     Stat::Pera,
     Stat::Pwhip,
+    Stat::Prs9,
     Stat::Pavg,
     Stat::Pobp,
     Stat::Pslg,
@@ -460,38 +765,148 @@ impl App for Imp019App {
         // Tip: a good default choice is to just keep the `CentralPanel`.
         // For inspiration and more examples, go to https://emilk.github.io/egui
 
+        if !self.disp_mode.is_valid(&self.engine.leagues, &self.engine.team_map, &self.engine.player_map, self.favorite) {
+            self.disp_mode = Mode::Schedule(0, None);
+        }
+
+        self.handle_shortcuts(ctx);
+
         egui::TopBottomPanel::top("top_panel").show(ctx, |ui| {
             // The top panel is often a good place for a menu bar:
             egui::menu::bar(ui, |ui| {
                 egui::menu::menu_button(ui, "File", |ui| {
+                    if ui.button("New Game...").clicked() {
+                        self.new_game = Some((GameConfig::default(), None));
+                        ui.close_menu();
+                    }
                     if ui.button("Quit").clicked() {
                         ctx.send_viewport_cmd(egui::ViewportCommand::Close);
                     }
                 });
                 ui.separator();
                 if ui.button("Sim").clicked() {
-                    let result = self.update();
-                    if !result {
-                        end_of_season(&mut self.leagues, &mut self.team_map, &mut self.player_map, 4, self.year, &self.data, &mut self.rng);
-                        self.year += 1;
-                    }
+                    self.sim_day();
                 };
                 if ui.button("Sim All").clicked() {
                     self.sim_all = true;
                 }
+                if ui.add_enabled(self.undo.is_some(), Button::new("Undo Day")).clicked() {
+                    self.undo_day();
+                }
+                ui.checkbox(&mut self.engine.fast_sim, "Fast").on_hover_text("Skip keeping play-by-play, box scores of new games won't be available");
+                let mut trim = self.engine.keep_log_days.is_some();
+                if ui.checkbox(&mut trim, "Trim logs").on_hover_text("Only keep play-by-play for the most recent days to save memory").changed() {
+                    self.engine.keep_log_days = if trim { Some(Self::DEFAULT_KEEP_LOG_DAYS) } else { None };
+                }
+                if let Some(days) = &mut self.engine.keep_log_days {
+                    ui.add(egui::DragValue::new(days).clamp_range(1..=Self::MAX_KEEP_LOG_DAYS).suffix(" days"));
+                }
+                ui.separator();
+                ui.label(format!("Seed: {}", self.engine.seed())).on_hover_text("Start a new game with this seed to get the same world");
+                if ui.button("Copy").clicked() {
+                    ui.output_mut(|o| o.copied_text = self.engine.seed().to_string());
+                }
+                ui.separator();
+                let offseason = self.engine.leagues.iter().all(|o| o.cur_idx == 0);
+                if ui.add_enabled(offseason, Button::new("Expand")).clicked() {
+                    let events = expand(&mut self.engine.leagues, &mut self.engine.team_map, &mut self.engine.player_map, 2, self.engine.year, &self.engine.data, &mut self.engine.rng);
+                    self.engine.events.extend(events);
+                    self.undo = None;
+                }
             });
         });
 
         if self.sim_all {
-            self.sim_all = self.update();
+            self.undo = None;
+            self.sim_all = self.engine.sim_games();
             ctx.request_repaint();
         }
 
+        let mut start = None;
+        let data = &self.engine.data;
+        if let Some((config, error)) = &mut self.new_game {
+            let mut open = true;
+            egui::Window::new("New Game").open(&mut open).show(ctx, |ui| {
+                egui::Grid::new("new_game").show(ui, |ui| {
+                    ui.label("Leagues");
+                    ui.add(egui::DragValue::new(&mut config.leagues).clamp_range(1..=10));
+                    ui.end_row();
+                    ui.label("Teams per league");
+                    ui.add(egui::DragValue::new(&mut config.teams_per_league).clamp_range(2..=40));
+                    ui.end_row();
+                    ui.label("Players per team");
+                    ui.add(egui::DragValue::new(&mut config.players_per_team).clamp_range(1..=200));
+                    ui.end_row();
+                    ui.label("Roster size");
+                    ui.add(egui::DragValue::new(&mut config.roster_size).clamp_range(Team::min_roster_size()..=MAX_ROSTER_SIZE));
+                    ui.end_row();
+                    ui.label("Playoff teams");
+                    ui.add(egui::DragValue::new(&mut config.playoffs.teams).clamp_range(1..=32));
+                    ui.end_row();
+                    ui.label("Series length");
+                    ui.add(egui::DragValue::new(&mut config.playoffs.series_len).clamp_range(1..=9));
+                    ui.end_row();
+                    ui.label("Run environment");
+                    ui.add(egui::DragValue::new(&mut config.run_env).speed(0.01).clamp_range(GameConfig::MIN_RUN_ENV..=GameConfig::MAX_RUN_ENV));
+                    ui.end_row();
+                    ui.label("Mercy rule");
+                    let mut mercy = config.mercy_rule.is_some();
+                    let (mut lead, mut inning) = config.mercy_rule.unwrap_or(GameConfig::DEFAULT_MERCY_RULE);
+                    ui.horizontal(|ui| {
+                        ui.checkbox(&mut mercy, "");
+                        ui.add_enabled(mercy, egui::DragValue::new(&mut lead).clamp_range(1..=30).suffix(" runs"));
+                        ui.add_enabled(mercy, egui::DragValue::new(&mut inning).clamp_range(1..=9).prefix("after "));
+                    });
+                    config.mercy_rule = if mercy { Some((lead, inning)) } else { None };
+                    ui.end_row();
+                    ui.label("Mean age");
+                    let mut mean = config.ages.mean();
+                    if ui.add(egui::DragValue::new(&mut mean).speed(0.1).clamp_range(AgeProfile::MIN_MEAN..=AgeProfile::MAX_MEAN)).changed() {
+                        config.ages = AgeProfile::with_mean(mean);
+                    }
+                    ui.end_row();
+                    ui.label("Designated hitter");
+                    ui.checkbox(&mut config.dh, "");
+                    ui.end_row();
+                    // u64 seeds don't survive a round trip through a DragValue's f64, so it's edited as text
+                    ui.label("Seed");
+                    let id = ui.make_persistent_id("seed_text");
+                    let mut text = ui.data_mut(|o| o.get_temp_mut_or_insert_with(id, || config.seed.map_or_else(String::new, |o| o.to_string())).clone());
+                    ui.horizontal(|ui| {
+                        ui.add(egui::TextEdit::singleline(&mut text).hint_text("random").desired_width(180.0));
+                        config.seed = text.trim().parse().ok();
+                        if config.seed.is_none() && !text.trim().is_empty() {
+                            ui.label("Not a number, a random seed will be used");
+                        }
+                    });
+                    ui.data_mut(|o| o.insert_temp(id, text));
+                    ui.end_row();
+                });
+                if let Some(error) = error {
+                    ui.label(error.as_str());
+                }
+                if ui.button("Start").clicked() {
+                    match config.validate(data) {
+                        Ok(()) => start = Some(*config),
+                        Err(err) => *error = Some(err),
+                    }
+                }
+            });
+            if !open {
+                self.new_game = None;
+            }
+        }
+        if let Some(config) = start {
+            *self = Self::new_with_config(&config);
+        }
+
         egui::SidePanel::left("side_panel").show(ctx, |ui| {
             ui.heading("Leagues");
-            for league_idx in 0..self.leagues.len() {
+            for league_idx in 0..self.engine.leagues.len() {
                 ui.horizontal(|ui| {
                     ui.label(format!("League {}", league_idx + 1));
+                    let (badge, hover) = if self.engine.leagues[league_idx].dh { ("DH", "Designated hitter") } else { ("P-bat", "Pitchers bat, and count on the batting leaderboards") };
+                    ui.small(badge).on_hover_text(hover);
                     if ui.button("Sche").clicked() {
                         self.disp_mode = Mode::Schedule(league_idx, None);
                     }
@@ -499,23 +914,49 @@ impl App for Imp019App {
                         self.disp_mode = Mode::Standings(league_idx);
                     }
                     if ui.button("Bat").clicked() {
-                        self.disp_mode = Mode::BatLeaders(league_idx, Stat::Bhr, true);
+                        self.disp_mode = Mode::BatLeaders(league_idx, Stat::Bhr, true, None, true);
                     }
                     if ui.button("Pit").clicked() {
-                        self.disp_mode = Mode::PitLeaders(league_idx, Stat::Pw, true);
+                        self.disp_mode = Mode::PitLeaders(league_idx, Stat::Pw, true, None, true);
                     }
                     if ui.button("Rec").clicked() {
                         self.disp_mode = Mode::LeagueRecords(league_idx);
                     }
+                    if ui.button("Lg").clicked() {
+                        self.disp_mode = Mode::LeagueStats(league_idx);
+                    }
+                    if ui.button("Hist").clicked() {
+                        self.disp_mode = Mode::LeagueHistory(league_idx);
+                    }
+                    if ui.button("RE").clicked() {
+                        self.disp_mode = Mode::RunExpectancy(league_idx);
+                    }
                 });
             }
             ui.separator();
+            ui.horizontal(|ui| {
+                if ui.button("News").clicked() {
+                    self.disp_mode = Mode::News;
+                }
+                if ui.add_enabled(self.engine.events.iter().any(|o| matches!(o, LeagueEvent::Champion(..))), Button::new("Summary")).clicked() {
+                    self.disp_mode = Mode::SeasonSummary(self.engine.year - 1);
+                }
+                if ui.add_enabled(self.favorite.is_some(), Button::new("Dashboard")).clicked() {
+                    self.disp_mode = Mode::Dashboard;
+                }
+                if ui.button("Exhibition").clicked() {
+                    let mut team_ids = self.engine.team_map.keys().copied();
+                    if let (Some(away), Some(home)) = (team_ids.next(), team_ids.next()) {
+                        self.disp_mode = Mode::Exhibition(away, home);
+                    }
+                }
+            });
         });
 
         egui::CentralPanel::default().show(ctx, |ui| {
             self.disp_mode = match &self.disp_mode {
                 Mode::Schedule(disp_league, cur_day) => {
-                    let league = &self.leagues[*disp_league];
+                    let league = &self.engine.leagues[*disp_league];
                     let total_games = league.schedule.games.len();
 
                     let teams = league.teams.len();
@@ -536,6 +977,17 @@ impl App for Imp019App {
                         if ui.add_enabled(end <= total_games, Button::new("Next >")).clicked() {
                             mode = Mode::Schedule(*disp_league, Some(cur + 1));
                         }
+                        if let Some(favorite) = self.favorite {
+                            let abbr = self.engine.team_map.get(&favorite).unwrap().abbr();
+                            let next = self.engine.leagues.iter().enumerate()
+                                .find(|(_, o)| o.teams.contains(&favorite))
+                                .and_then(|(idx, o)| Some((idx, o.next_game_day(favorite)?)));
+                            if ui.add_enabled(next.is_some(), Button::new(format!("Next {} Game", abbr))).clicked() {
+                                if let Some((league_idx, day)) = next {
+                                    mode = Mode::Schedule(league_idx, Some(day));
+                                }
+                            }
+                        }
                     });
                     ui.end_row();
 
@@ -546,10 +998,11 @@ impl App for Imp019App {
                             if end <= total_games {
                                 for idx in start..end {
                                     let game = &league.schedule.games[idx];
-                                    if display_game(ui, game, &self.team_map) {
+                                    let highlight = self.favorite.is_some_and(|o| game.home.id == o || game.away.id == o);
+                                    if display_game(ui, game, &self.engine.team_map, highlight) {
                                         mode = Mode::BoxScore(*disp_league, idx)
                                     }
-                                    if ((idx - start + 1) % 5) == 0 {
+                                    if (idx - start + 1).is_multiple_of(5) {
                                         ui.end_row();
                                     }
                                 }
@@ -562,15 +1015,12 @@ impl App for Imp019App {
                     mode
                 }
                 Mode::BoxScore(disp_league, game_idx) => {
-                    let league = &self.leagues[*disp_league];
+                    let league = &self.engine.leagues[*disp_league];
                     let mut mode = Mode::BoxScore(*disp_league, *game_idx);
                     let game = &league.schedule.games[*game_idx];
 
-                    let awayteam = self.team_map.get(&game.away.id).unwrap();
-                    let hometeam = self.team_map.get(&game.home.id).unwrap();
-
-                    let mut awayruns = Vec::new();
-                    let mut homeruns = Vec::new();
+                    let awayteam = self.engine.team_map.get(&game.away.id).unwrap();
+                    let hometeam = self.engine.team_map.get(&game.home.id).unwrap();
 
                     ui.horizontal(|ui| {
                         if ui.button("Back").clicked() {
@@ -581,39 +1031,28 @@ impl App for Imp019App {
                         if ui.button("Game Log").clicked() {
                             mode = Mode::GameLog(*disp_league, *game_idx);
                         }
-                    });
-
-
-                    let mut winner = None;
-                    let mut loser = None;
-                    let mut save = None;
-
-                    let mut stat_map = HashMap::new();
-
-                    for_each_event(game, |inning, tophalf, event, _| {
-                        let player_stats = stat_map.entry(event.player).or_insert_with(Vec::new);
-                        player_stats.push(event.event);
-
-                        match event.event {
-                            Stat::Pw => winner = Some(event.player),
-                            Stat::Pl => loser = Some(event.player),
-                            Stat::Psv => save = Some(event.player),
-                            _ => {}
-                        };
-
-                        let skip = matches!(event.event, Stat::Pw|Stat::Pcg|Stat::Psho|Stat::Pl|Stat::Psv|Stat::Pbs|Stat::Phld|Stat::Po|Stat::Pso);
+                        if ui.add_enabled(game.has_playbyplay(), Button::new("Copy")).clicked() {
+                            let text = game.to_boxscore_text(&self.engine.team_map, &self.engine.player_map);
+                            ui.output_mut(|o| o.copied_text = text);
+                        }
 
-                        if !skip {
-                            let runs = if tophalf { &mut awayruns } else { &mut homeruns };
-                            if runs.len() < inning {
-                                runs.push(0);
-                            }
-                            if event.event == Stat::Br {
-                                runs[inning - 1] += 1;
+                        // older games can't be replayed, so they get no button
+                        let id = ui.make_persistent_id("replay");
+                        if let Some(before) = self.replay_start(*disp_league, *game_idx) {
+                            if ui.button("Replay").clicked() {
+                                let replay = game.replay(&before.team_map, &before.player_map, self.engine.year, league.run_env);
+                                ui.data_mut(|o| o.insert_temp(id, (*disp_league, *game_idx, replay.playbyplay == game.playbyplay)));
                             }
                         }
+                        if let Some((_, _, matches)) = ui.data(|o| o.get_temp::<(usize, usize, bool)>(id)).filter(|o| (o.0, o.1) == (*disp_league, *game_idx)) {
+                            ui.label(if matches { "Replay matches" } else { "Replay differs" });
+                        }
                     });
 
+
+                    let boxscore = game.boxscore();
+                    let (awayruns, homeruns) = (&boxscore.away_runs, &boxscore.home_runs);
+
                     egui::Grid::new("Innings").show(ui, |ui| {
                         ui.monospace("   ");
                         for inning in 1..=awayruns.len().max(homeruns.len()) {
@@ -622,14 +1061,16 @@ impl App for Imp019App {
                         ui.monospace("  R");
                         ui.monospace("  H");
                         ui.monospace("  E");
+                        ui.monospace("LOB");
                         ui.end_row();
                         ui.monospace(awayteam.abbr());
                         for awayrun in awayruns.iter() {
                             ui.monospace(format!("{}", awayrun));
                         }
-                        ui.monospace(format!("{:3}", game.away.r));
-                        ui.monospace(format!("{:3}", game.away.h));
-                        ui.monospace(format!("{:3}", game.away.e));
+                        for total in boxscore.rhe(false) {
+                            ui.monospace(format!("{:3}", total));
+                        }
+                        ui.monospace(format!("{:3}", game.away.lob));
                         ui.end_row();
                         ui.monospace(hometeam.abbr());
                         for homerun in homeruns.iter() {
@@ -638,103 +1079,128 @@ impl App for Imp019App {
                         if awayruns.len() > homeruns.len() {
                             ui.monospace("X");
                         }
-                        ui.monospace(format!("{:3}", game.home.r));
-                        ui.monospace(format!("{:3}", game.home.h));
-                        ui.monospace(format!("{:3}", game.home.e));
+                        for total in boxscore.rhe(true) {
+                            ui.monospace(format!("{:3}", total));
+                        }
+                        ui.monospace(format!("{:3}", game.home.lob));
                         ui.end_row();
                     });
 
                     ui.horizontal(|ui| {
-                        if let Some(w) = winner {
-                            let pitcher = self.player_map.get(&w).unwrap();
+                        if let Some(w) = boxscore.winner {
+                            let pitcher = self.engine.player_map.get(&w).unwrap();
                             ui.label(format!("W: {}", pitcher.fname()));
                         }
-                        if let Some(l) = loser {
-                            let pitcher = self.player_map.get(&l).unwrap();
+                        if let Some(l) = boxscore.loser {
+                            let pitcher = self.engine.player_map.get(&l).unwrap();
                             ui.label(format!("L: {}", pitcher.fname()));
                         }
-                        if let Some(sv) = save {
-                            let pitcher = self.player_map.get(&sv).unwrap();
+                        if let Some(sv) = boxscore.save {
+                            let pitcher = self.engine.player_map.get(&sv).unwrap();
                             ui.label(format!("SV: {}", pitcher.fname()));
                         }
+                        if game.walkoff {
+                            ui.label("Walk-off");
+                        }
                     });
 
+                    ui.label(game.recap(&self.engine.team_map, &self.engine.player_map));
+
                     ui.separator();
 
-                    ui.columns(2, |cols| {
-                        for (i, col) in cols.iter_mut().enumerate() {
-                            match i {
-                                0 => {
-                                    egui::Grid::new("Away Batting").show(col, |ui| {
-                                        display_bo(ui, &game.away, awayteam, &self.player_map, &stat_map);
-                                    });
-                                }
-                                1 => {
-                                    egui::Grid::new("Home Batting").show(col, |ui| {
-                                        display_bo(ui, &game.home, hometeam, &self.player_map, &stat_map);
-                                    });
+                    if game.has_playbyplay() {
+                        ui.columns(2, |cols| {
+                            for (i, col) in cols.iter_mut().enumerate() {
+                                match i {
+                                    0 => {
+                                        egui::Grid::new("Away Batting").show(col, |ui| {
+                                            display_bo(ui, &game.away, awayteam, &self.engine.player_map, &boxscore.stat_map);
+                                        });
+                                    }
+                                    1 => {
+                                        egui::Grid::new("Home Batting").show(col, |ui| {
+                                            display_bo(ui, &game.home, hometeam, &self.engine.player_map, &boxscore.stat_map);
+                                        });
+                                    }
+                                    _ => {}
                                 }
-                                _ => {}
                             }
-                        }
-                    });
+                        });
 
-                    ui.separator();
+                        ui.separator();
 
-                    ui.columns(2, |cols| {
-                        for (i, col) in cols.iter_mut().enumerate() {
-                            match i {
-                                0 => {
-                                    egui::Grid::new("Away Pitching").show(col, |ui| {
-                                        display_pitching(ui, &game.away, awayteam, &self.player_map, &stat_map);
-                                    });
-                                }
-                                1 => {
-                                    egui::Grid::new("Home Pitching").show(col, |ui| {
-                                        display_pitching(ui, &game.home, hometeam, &self.player_map, &stat_map);
-                                    });
+                        ui.columns(2, |cols| {
+                            for (i, col) in cols.iter_mut().enumerate() {
+                                match i {
+                                    0 => {
+                                        egui::Grid::new("Away Pitching").show(col, |ui| {
+                                            display_pitching(ui, &game.away, awayteam, &self.engine.player_map);
+                                        });
+                                    }
+                                    1 => {
+                                        egui::Grid::new("Home Pitching").show(col, |ui| {
+                                            display_pitching(ui, &game.home, hometeam, &self.engine.player_map);
+                                        });
+                                    }
+                                    _ => {}
                                 }
-                                _ => {}
                             }
-                        }
-                    });
+                        });
+                    } else {
+                        ui.label("Play-by-play not recorded");
+                    }
 
                     ui.separator();
 
                     mode
                 }
                 Mode::GameLog(disp_league, game_idx) => {
-                    let league = &self.leagues[*disp_league];
+                    let league = &self.engine.leagues[*disp_league];
                     let mut mode = Mode::GameLog(*disp_league, *game_idx);
                     let game = &league.schedule.games[*game_idx];
 
                     if ui.button("Box Score").clicked() {
                         mode = Mode::BoxScore(*disp_league, *game_idx);
                     }
+                    if !game.has_playbyplay() {
+                        ui.label("Play-by-play not recorded");
+                    }
 
                     ScrollArea::both().show(ui, |ui| {
                         let mut prevhalf = false;
                         let mut previnn = 0;
 
-                        for_each_event(game, |inning, tophalf, event, error| {
-                            let player = self.player_map.get(&event.player).unwrap();
+                        for annotated in game.events() {
+                            let (inning, tophalf, event) = (annotated.inning, annotated.tophalf, annotated.event);
+                            let player = self.engine.player_map.get(&event.player).unwrap();
                             let player_str = player.fullname();
 
                             let pitching_change = event.event == Stat::G && player.pos.is_pitcher();
+                            let substitution = event.event == Stat::G && !player.pos.is_pitcher();
 
-                            if !pitching_change && (!event.event.is_batting() || event.event == Stat::Brbi) {
-                                return;
+                            if !pitching_change && !substitution && (!event.event.is_batting() || event.event == Stat::Brbi) {
+                                continue;
                             }
 
                             if prevhalf != tophalf || previnn != inning {
-                                ui.heading(format!("{} of the {}", if tophalf { "Top" } else { "Bottom" }, Ordinal(inning)));
+                                ui.heading(format!("{} of the {} ({}-{})", if tophalf { "Top" } else { "Bottom" }, Ordinal(inning), annotated.away_r, annotated.home_r));
                                 prevhalf = tophalf;
                                 previnn = inning;
                             }
 
                             if pitching_change {
                                 ui.label(format!("{} is now pitching.", player_str));
-                                return;
+                                continue;
+                            }
+                            if substitution {
+                                let replaced = game.replaced(event.player).and_then(|o| self.engine.player_map.get(&o)).map_or("".to_string(), |o| o.fullname());
+                                let batting = if tophalf { &game.away } else { &game.home };
+                                if batting.subs.iter().any(|o| o.1 == event.player) {
+                                    ui.label(format!("{} pinch-hitting for {}.", player_str, replaced));
+                                } else {
+                                    ui.label(format!("{} replaces {} at {}.", player_str, replaced, event.target.map_or("".to_string(), |o| o.to_string())));
+                                }
+                                continue;
                             }
 
                             let target_str = if let Some(target) = event.target {
@@ -755,68 +1221,278 @@ impl App for Imp019App {
                                 Stat::Bgidp => " grounds into double play",
                                 Stat::Bsb => " steals second",
                                 Stat::Bcs => " is thrown out stealing",
-                                Stat::Bo => if error {
-                                    " reaches on error"
-                                } else {
-                                    " flies out"
+                                Stat::Boob => " is thrown out trying for an extra base",
+                                Stat::Bo => match annotated.error {
+                                    Some(ErrorType::Fielding) => " reaches on a fielding error",
+                                    Some(ErrorType::Throwing) => " reaches on a throwing error",
+                                    None => " flies out",
                                 },
                                 Stat::Br => " scores",
                                 _ => ""
                             };
 
-                            ui.label(format!("{}{}{}.", player_str, result_str, target_str));
-                        });
+                            ui.label(format!("{} out: {}{}{}.", annotated.outs, player_str, result_str, target_str));
+                        }
                     });
 
                     mode
                 }
                 Mode::Standings(disp_league) => {
-                    let league = &self.leagues[*disp_league];
+                    let league = &self.engine.leagues[*disp_league];
                     let mut mode = Mode::Standings(*disp_league);
-                    egui::Grid::new("standings").show(ui, |ui| {
+
+                    let rows = league.standings(&self.engine.team_map);
+
+                    // the header takes the first grid row
+                    let favorite_row = rows.iter().position(|o| Some(o.team_id) == self.favorite).map(|o| o + 1);
+                    let color = highlight_color(ui);
+                    egui::Grid::new("standings").with_row_color(move |row, _| (Some(row) == favorite_row).then_some(color)).show(ui, |ui| {
                         ui.label("Rank");
                         ui.label("Abbr");
                         ui.label("Team");
                         ui.label("Record");
+                        ui.label("Pct");
+                        ui.label("GB");
+                        ui.label("RD");
+                        ui.label("GR");
+                        ui.label("Magic");
                         ui.end_row();
 
-                        let teams = &mut league.teams.iter().collect::<Vec<_>>();
-                        teams.sort_by_key(|o| {
-                            let team = self.team_map.get(*o).unwrap();
-                            team.win_pct()
-                        });
-                        teams.reverse();
+                        // the top league plays for first place, the rest for promotion
+                        let spots = if *disp_league == 0 { 1 } else { PROMOTION_COUNT };
 
-
-                        let mut rank = 1;
-                        for team_id in teams.iter() {
-                            let team = self.team_map.get(*team_id).unwrap();
-                            ui.label(format!("{}", rank));
+                        for row in &rows {
+                            let team = self.engine.team_map.get(&row.team_id).unwrap();
+                            ui.label(format!("{}", row.rank));
                             ui.label(team.abbr());
                             if ui.add(Button::new(team.name()).frame(false)).clicked() {
-                                mode = Mode::Team(*disp_league, **team_id);
+                                mode = Mode::Team(*disp_league, row.team_id);
                             }
-                            ui.label(format!("{}-{}", team.get_wins(), team.get_losses()));
+                            ui.label(format!("{}-{}", row.wins, row.losses));
+                            ui.label(Stat::Bavg.value(row.pct));
+                            ui.label(if row.rank == 1 { "-".to_string() } else { format!("{:.1}", row.games_behind) });
+                            ui.label(format!("{:+}", row.run_diff));
+                            ui.label(format!("{}", league.games_remaining(row.team_id)));
+                            ui.label(match league.magic_number(row.team_id, spots, &self.engine.team_map) {
+                                Some(0) => "Clinched".to_string(),
+                                Some(magic) => magic.to_string(),
+                                None => "E".to_string(),
+                            });
                             ui.end_row();
-                            rank += 1;
                         }
                     });
+
+                    if let Some(playoffs) = &league.playoffs {
+                        ui.heading(format!("{} Playoffs", playoffs.year));
+                        egui::Grid::new("playoffs").striped(true).show(ui, |ui| {
+                            for (round, series_list) in playoffs.rounds.iter().enumerate() {
+                                ui.label(format!("Round {}", round + 1));
+                                for series in series_list {
+                                    let (winner, loser) = if series.wins(series.high) > series.wins(series.low) { (series.high, series.low) } else { (series.low, series.high) };
+                                    let winner_team = self.engine.team_map.get(&winner).unwrap();
+                                    let loser_team = self.engine.team_map.get(&loser).unwrap();
+                                    ui.label(format!("{} def. {} {}-{}", winner_team.abbr(), loser_team.abbr(), series.wins(winner), series.wins(loser)));
+                                }
+                                ui.end_row();
+                            }
+                        });
+                    }
+                    mode
+                }
+                Mode::DepthChart(disp_league, id) => {
+                    let mut mode = Mode::DepthChart(*disp_league, *id);
+                    if ui.button("Back").clicked() {
+                        mode = Mode::Team(*disp_league, *id);
+                    }
+
+                    let team = self.engine.team_map.get(id).unwrap();
+                    ui.heading(format!("{} Depth Chart", team.name()));
+
+                    // the rotation is stored in the order starts will be made, next starter first
+                    let mut swap = None;
+
+                    let mut player_button = |ui: &mut Ui, player_id: PlayerId| {
+                        let player = self.engine.player_map.get(&player_id).unwrap();
+                        if ui.add(Button::new(player.fname()).frame(false)).clicked() {
+                            mode = Mode::Player(*disp_league, player_id, Some(*id));
+                        }
+                    };
+
+                    ui.horizontal(|ui| {
+                        ui.vertical(|ui| {
+                            ui.heading("Lineup");
+                            egui::Grid::new("depth_lineup").striped(true).show(ui, |ui| {
+                                for pos in all::<Position>().filter(|o| !o.is_pitcher()) {
+                                    // same order setup_bo picks starters in
+                                    let mut at_pos = team.iter_players(&self.engine.player_map).filter(|o| o.1.pos == pos).map(|o| o.0).collect::<Vec<_>>();
+                                    at_pos.sort_by_cached_key(|o| std::cmp::Reverse(self.engine.player_map.get(o).unwrap().get_stats().b_obp));
+
+                                    ui.label(pos.to_string());
+                                    for player_id in at_pos {
+                                        player_button(ui, player_id);
+                                    }
+                                    ui.end_row();
+                                }
+                            });
+                        });
+
+                        ui.vertical(|ui| {
+                            ui.heading("Rotation");
+                            egui::Grid::new("depth_rotation").striped(true).show(ui, |ui| {
+                                for (idx, player_id) in team.rotation.iter().enumerate() {
+                                    ui.label(format!("{}.", idx + 1));
+                                    player_button(ui, *player_id);
+                                    if ui.add_enabled(idx > 0, Button::new("Up")).clicked() {
+                                        swap = Some((idx, idx - 1));
+                                    }
+                                    if ui.add_enabled(idx < team.rotation.len() - 1, Button::new("Down")).clicked() {
+                                        swap = Some((idx, idx + 1));
+                                    }
+                                    ui.end_row();
+                                }
+                            });
+
+                            ui.heading("Bullpen");
+                            egui::Grid::new("depth_bullpen").striped(true).show(ui, |ui| {
+                                for pos in all::<Position>().filter(|o| o.is_pitcher() && *o != Position::StartingPitcher) {
+                                    ui.label(pos.to_string());
+                                    for (player_id, _) in team.iter_players(&self.engine.player_map).filter(|o| o.1.pos == pos) {
+                                        player_button(ui, player_id);
+                                    }
+                                    ui.end_row();
+                                }
+                            });
+                        });
+                    });
+
+                    if let Some((a, b)) = swap {
+                        self.engine.team_map.get_mut(id).unwrap().rotation.swap(a, b);
+                    }
+
+                    mode
+                }
+                Mode::TeamScoring(disp_league, id) => {
+                    let mut mode = Mode::TeamScoring(*disp_league, *id);
+                    if ui.button("Back").clicked() {
+                        mode = Mode::Team(*disp_league, *id);
+                    }
+
+                    let team = self.engine.team_map.get(id).unwrap();
+                    ui.heading(format!("{} Scoring", team.name()));
+
+                    // teams can move between leagues, so look up where this one plays now
+                    if let Some(league) = self.engine.leagues.iter().find(|o| o.teams.contains(id)) {
+                        let report = league.scoring_report(*id);
+                        let total = report.by_inning.iter().sum::<u32>();
+
+                        ui.label(format!("Games: {} Runs: {}", report.games, total));
+                        ui.heading("Runs by Inning");
+                        egui::Grid::new("by_inning").striped(true).show(ui, |ui| {
+                            ui.label("Inning");
+                            ui.label("R");
+                            ui.label("%");
+                            ui.end_row();
+                            for (inning, runs) in report.by_inning.iter().enumerate() {
+                                ui.label(format!("{}", inning + 1));
+                                ui.label(format!("{}", runs));
+                                ui.label(format!("{:.1}", *runs as f64 * 100.0 / total.max(1) as f64));
+                                ui.end_row();
+                            }
+                        });
+
+                        ui.heading("Runs per Game");
+                        egui::Grid::new("per_game").striped(true).show(ui, |ui| {
+                            for (runs, games) in report.per_game.iter().enumerate() {
+                                ui.label(format!("{}", runs));
+                                ui.label(format!("{}", games));
+                                ui.monospace("#".repeat(*games as usize));
+                                ui.end_row();
+                            }
+                        });
+                    }
+
                     mode
                 }
                 Mode::Team(disp_league, id) => {
                     let mut mode = Mode::Team(*disp_league, *id);
-                    if ui.button("Close").clicked() {
-                        mode = Mode::Standings(*disp_league);
-                    }
+                    let mut new_favorite = None;
+                    ui.horizontal(|ui| {
+                        if ui.button("Close").clicked() {
+                            mode = Mode::Standings(*disp_league);
+                        }
+                        if ui.button("Depth Chart").clicked() {
+                            mode = Mode::DepthChart(*disp_league, *id);
+                        }
+                        if ui.button("Scoring").clicked() {
+                            mode = Mode::TeamScoring(*disp_league, *id);
+                        }
+                        if ui.button("Compare").clicked() {
+                            // start against the closest rival, the team just above in the standings or just below for the leader
+                            let standings = self.engine.leagues[*disp_league].final_standings(&self.engine.team_map);
+                            let rival = standings.iter().position(|o| o == id).and_then(|o| standings.get(if o == 0 { 1 } else { o - 1 }).copied());
+                            mode = Mode::CompareTeams(*id, rival.unwrap_or(*id));
+                        }
+                        let is_favorite = self.favorite == Some(*id);
+                        if ui.selectable_label(is_favorite, "Favorite").clicked() {
+                            new_favorite = Some(if is_favorite { None } else { Some(*id) });
+                        }
+                    });
 
-                    let team = self.team_map.get(id).unwrap();
-                    ui.label(team.name());
+                    let team = self.engine.team_map.get(id).unwrap();
+                    ui.horizontal(|ui| {
+                        ui.label(team.name());
+                        let championships = team.championships();
+                        if championships > 0 {
+                            ui.label(format!("🏆 x{}", championships));
+                        }
+                    });
                     ui.label(format!("Founded: {}", team.history.founded));
+                    ui.label(format!("Popularity: {:.0}", team.popularity));
+                    ui.label(format!("Roster: {}/{}", team.players.len(), team.roster_size));
+                    for (year, name) in &team.history.former_names {
+                        ui.label(format!("Formerly: {} (until {})", name, year - 1));
+                    }
                     ui.label(format!("Best: {}", as_league(team.history.best)));
                     ui.label(format!("Worst: {}", as_league(team.history.worst)));
                     ui.label(format!("Wins: {}", team.history.wins));
                     ui.label(format!("Losses: {}", team.history.losses));
 
+                    let expected_wins = team.results.expected_wins();
+                    let expected_losses = team.results.games() - team.get_ties() - expected_wins;
+                    let delta = team.get_wins() as i32 - expected_wins as i32;
+                    let ties = if team.get_ties() > 0 { format!("-{}", team.get_ties()) } else { String::new() };
+                    ui.label(format!("Record: {}-{}{} Expected: {}-{} ({:+})", team.get_wins(), team.get_losses(), ties, expected_wins, expected_losses, delta));
+
+                    let mut new_lineup = None;
+                    ui.collapsing("Lineup", |ui| {
+                        let lineup = team.lineup.unwrap_or_else(|| team.auto_lineup(&self.engine.player_map));
+                        ui.horizontal(|ui| {
+                            ui.label(if team.lineup.is_some() { "Custom" } else { "Automatic" });
+                            if team.lineup.is_some() && ui.button("Reset").clicked() {
+                                new_lineup = Some(None);
+                            }
+                        });
+                        let positions = Team::lineup_positions(&lineup, &self.engine.player_map);
+                        egui::Grid::new("lineup").show(ui, |ui| {
+                            for (idx, player_id) in lineup.iter().enumerate() {
+                                let player = self.engine.player_map.get(player_id).unwrap();
+                                let pos = positions.as_ref().map_or(player.pos, |o| o[idx]);
+                                ui.label(format!("{}. {} {}", idx + 1, player.fname(), pos));
+                                if ui.add_enabled(idx > 0, Button::new("Up")).clicked() {
+                                    let mut lineup = lineup;
+                                    lineup.swap(idx, idx - 1);
+                                    new_lineup = Some(Some(lineup));
+                                }
+                                if ui.add_enabled(idx < lineup.len() - 1, Button::new("Down")).clicked() {
+                                    let mut lineup = lineup;
+                                    lineup.swap(idx, idx + 1);
+                                    new_lineup = Some(Some(lineup));
+                                }
+                                ui.end_row();
+                            }
+                        });
+                    });
+
                     ui.horizontal(|ui| {
                         if !team.history.results.is_empty() {
                             ui.vertical(|ui| {
@@ -832,7 +1508,7 @@ impl App for Imp019App {
                                     ui.end_row();
 
                                     for result in &team.history.results {
-                                        ui.label(format!("{}", result.year));
+                                        ui.label(format!("{}{}", result.year, if result.champion { " 🏆" } else { "" }));
                                         ui.label(format!("League {}", result.league));
                                         ui.label(format!("{}", Ordinal(result.rank)));
                                         ui.label(format!("{}", result.win));
@@ -849,93 +1525,230 @@ impl App for Imp019App {
                                 ui.heading("Batting");
 
                                 egui::Grid::new("batting").striped(true).show(ui, |ui| {
-                                    if let Some(player_id) = display_team_stats(ui, true, &BATTING_HEADERS, &team.players, &self.player_map) {
+                                    if let Some(player_id) = display_team_stats(ui, true, &BATTING_HEADERS, team, &self.engine.player_map) {
                                         mode = Mode::Player(*disp_league, player_id, Some(*id));
                                     }
                                 });
                                 ui.heading("Pitching");
                                 egui::Grid::new("pitching").striped(true).show(ui, |ui| {
-                                    if let Some(player_id) = display_team_stats(ui, false, &PITCHING_HEADERS, &team.players, &self.player_map) {
+                                    if let Some(player_id) = display_team_stats(ui, false, &PITCHING_HEADERS, team, &self.engine.player_map) {
                                         mode = Mode::Player(*disp_league, player_id, Some(*id));
                                     }
                                 });
+                                ui.heading("Fielding");
+                                egui::Grid::new("fielding").striped(true).show(ui, |ui| {
+                                    display_fielding(ui, team, &self.engine.player_map);
+                                });
                             });
                         }
                     });
 
+                    if let Some(lineup) = new_lineup {
+                        self.engine.team_map.get_mut(id).unwrap().lineup = lineup;
+                    }
+                    if let Some(favorite) = new_favorite {
+                        self.favorite = favorite;
+                    }
 
                     mode
                 }
                 Mode::Player(disp_league, player_id, team_id) => {
                     let mut mode = Mode::Player(*disp_league, *player_id, *team_id);
 
-                    let player = self.player_map.get(player_id).unwrap();
+                    let player = self.engine.player_map.get(player_id).unwrap();
 
-                    if ui.button("Close").clicked() {
-                        if let Some(team_id) = team_id {
-                            mode = Mode::Team(*disp_league, *team_id);
-                        } else if player.pos.is_pitcher() {
-                            mode = Mode::PitLeaders(*disp_league, Stat::Pw, true);
-                        } else {
-                            mode = Mode::BatLeaders(*disp_league, Stat::Bhr, true);
+                    ui.horizontal(|ui| {
+                        if ui.button("Close").clicked() {
+                            if let Some(team_id) = team_id {
+                                mode = Mode::Team(*disp_league, *team_id);
+                            } else if player.pos.is_pitcher() {
+                                mode = Mode::PitLeaders(*disp_league, Stat::Pw, true, None, true);
+                            } else {
+                                mode = Mode::BatLeaders(*disp_league, Stat::Bhr, true, None, true);
+                            }
                         }
-                    }
+                        if ui.button("Compare").clicked() {
+                            mode = Mode::Compare(*player_id, *player_id);
+                        }
+                    });
                     ui.label(format!("Name: {}", player.fullname()));
-                    ui.label(format!("Age: {} Born: {} {}", player.age(self.year), player.born, player.birthplace));
+                    ui.label(format!("Age: {} Born: {} {}", player.age(self.engine.year), player.born, player.birthplace));
                     ui.label(format!("Pos: {}", player.pos));
+                    if !player.secondary.is_empty() {
+                        ui.label(format!("Can also play: {}", player.secondary.iter().map(|o| o.to_string()).collect::<Vec<_>>().join(", ")));
+                    }
                     ui.label(format!("Bats: {}", player.bats));
                     ui.label(format!("Throws: {}", player.throws));
+                    if !player.pos.is_pitcher() {
+                        ui.label(format!("Baserunning: {:.0}", player.baserunning * 100.0))
+                            .on_hover_text("How often the runner tries for an extra base on a hit, from 0 to 100");
+                        let risp = player.get_risp_stats();
+                        ui.label(format!("AVG w/ RISP: {} ({}-for-{})", Stat::Bavg.value(risp.b_avg), risp.b_h, risp.b_ab));
+                        if let Some(clutch) = player.clutch() {
+                            let rating = if clutch >= 30 { "Clutch" } else if clutch <= -30 { "Chokes" } else { "Steady" };
+                            let sign = if clutch < 0 { "-" } else { "+" };
+                            ui.label(format!("{}: {}{} wOBA in high leverage ({} PA)", rating, sign, Stat::Bwoba.value(clutch.unsigned_abs()), player.get_clutch_stats().b_pa))
+                                .on_hover_text("Late and close plate appearances against the season as a whole");
+                        }
+                    }
+                    if player.pos.is_pitcher() {
+                        ui.label(format!("Velocity: {} Movement: {}", Arsenal::grade(player.arsenal.velocity), Arsenal::grade(player.arsenal.movement)))
+                            .on_hover_text("Scouting grades from 20 to 80, velocity misses bats and movement keeps the ball in the park");
+                    }
+                    if player.pos == Position::Catcher {
+                        let career = player.career_stats();
+                        ui.label(format!("Pop time: {:.2}s", player.pop_time()));
+                        ui.label(format!("Caught stealing: {} of {}", career.f_cs, career.f_sb + career.f_cs));
+                    }
+
+                    // raw lines are the default, the toggle is view state like the trend stat below
+                    let neutral_id = ui.make_persistent_id("neutralized");
+                    let mut neutralized = ui.data_mut(|o| *o.get_temp_mut_or(neutral_id, false));
+                    ui.horizontal(|ui| {
+                        ui.heading(if player.pos.is_pitcher() { "Pitching History" } else { "Batting History" });
+                        ui.checkbox(&mut neutralized, "Neutralized").on_hover_text("Each season adjusted to a neutral league, so high and low scoring years compare fairly");
+                    });
+                    ui.data_mut(|o| o.insert_temp(neutral_id, neutralized));
+
+                    let historical = if neutralized { neutralize_history(&player.historical, &self.engine.leagues) } else { player.historical.clone() };
+                    let current = self.engine.team_map.iter().find(|kv| kv.1.players.contains(player_id));
+                    let current_league = current.and_then(|team| self.engine.leagues.iter().position(|o| o.teams.contains(team.0)));
+                    let mut season = player.get_stats();
+                    if let (true, Some(league)) = (neutralized, current_league) {
+                        season = season.neutralized(&self.engine.leagues[league].total_stats(&self.engine.team_map, &self.engine.player_map));
+                    }
 
-                    ui.heading(if player.pos.is_pitcher() { "Pitching History" } else { "Batting History" });
                     let headers = if player.pos.is_pitcher() { &PITCHING_HEADERS[..] } else { &BATTING_HEADERS[..] };
                     egui::Grid::new("history").striped(true).show(ui, |ui| {
-                        let mut total = display_historical_stats(ui, headers, &player.historical, &self.team_map);
-                        let stats = player.get_stats();
+                        let mut total = display_historical_stats(ui, headers, &historical, &self.engine.team_map);
+                        let stats = &season;
                         let mut teams = HashSet::new();
 
-                        for historical in &player.historical {
+                        for historical in &historical {
                             teams.insert(historical.team);
                         }
 
                         if stats.g > 0 {
-                            let team = self.team_map.iter().find(|kv| kv.1.players.contains(player_id)).unwrap();
+                            let team = current.unwrap();
                             teams.insert(*team.0);
-                            let league = self.leagues.iter().position(|o| o.teams.contains(team.0)).unwrap();
-                            display_historical_stat_row(ui, headers, &stats, Some(self.year), Some((league + 1) as u32), team.1.abbr());
-                            total.compile(&stats);
+                            let league = current_league.unwrap();
+                            display_historical_stat_row(ui, headers, stats, Some(self.engine.year), Some((league + 1) as u32), team.1.abbr());
+                            total.compile(stats);
                         }
                         let team_count = if teams.len() == 1 { "1 team".to_owned() } else { format!("{} team(s)", teams.len()) };
                         display_historical_stat_row(ui, headers, &total, None, None, team_count.as_str());
                     });
 
+                    let mut points = historical.iter().map(|o| (o.year, &o.stats)).collect::<Vec<_>>();
+                    if season.g > 0 {
+                        points.push((self.engine.year, &season));
+                    }
+                    if points.len() > 1 {
+                        // the chosen stat is view state only, so it lives in egui's memory rather than the mode
+                        let id = ui.make_persistent_id("trend");
+                        let default = if player.pos.is_pitcher() { Stat::Pera } else { Stat::Bhr };
+                        let mut stat = ui.data_mut(|o| *o.get_temp_mut_or(id, default));
+                        ui.horizontal(|ui| {
+                            ui.heading("Trend");
+                            egui::ComboBox::from_id_source("trend_stat").selected_text(stat.to_string()).show_ui(ui, |ui| {
+                                for header in headers {
+                                    ui.selectable_value(&mut stat, *header, header.to_string());
+                                }
+                            });
+                        });
+                        ui.data_mut(|o| o.insert_temp(id, stat));
+
+                        let values = points.iter().map(|o| (o.0, o.1.get_stat(stat))).collect::<Vec<_>>();
+                        display_trend(ui, stat, &values);
+                    }
+
+                    if !player.bat_spray.is_empty() {
+                        ui.collapsing("Spray Chart", |ui| {
+                            let outfield = [Position::LeftField, Position::CenterField, Position::RightField];
+                            let infield = |shares: &dyn Fn(Position) -> f64| all::<Position>().filter(|o| !outfield.contains(o)).map(shares).sum::<f64>();
+                            let pct = |share: f64| format!("{:.0}%", share * 100.0);
+                            let spray = player.hit_spray();
+                            let overall = |pos: Position| spray.get(&pos).copied().unwrap_or(0.0);
+
+                            egui::Grid::new("spray").striped(true).show(ui, |ui| {
+                                for header in ["", "IF", "LF", "CF", "RF"].iter() {
+                                    ui.label(*header);
+                                }
+                                ui.end_row();
+                                for (expect, label) in [(Expect::Single, "1B"), (Expect::Double, "2B"), (Expect::Triple, "3B"), (Expect::HomeRun, "HR")] {
+                                    let chart = &player.bat_spray[&expect];
+                                    let shares = |pos: Position| chart.get(&pos).map_or(0.0, |o| *o as f64 / 1000.0);
+                                    ui.label(label);
+                                    ui.label(pct(infield(&shares)));
+                                    for pos in outfield {
+                                        ui.label(pct(shares(pos)));
+                                    }
+                                    ui.end_row();
+                                }
+                                ui.label("All");
+                                ui.label(pct(infield(&overall)));
+                                for pos in outfield {
+                                    ui.label(pct(overall(pos)));
+                                }
+                                ui.end_row();
+                            });
+
+                            // switch hitters pull to both sides
+                            let pull = match player.bats {
+                                Handedness::Right => Some((Position::LeftField, Position::RightField)),
+                                Handedness::Left => Some((Position::RightField, Position::LeftField)),
+                                Handedness::Switch => None,
+                            };
+                            if let Some((pull, oppo)) = pull {
+                                ui.label(format!("Pull {} / Center {} / Oppo {}", pct(overall(pull)), pct(overall(Position::CenterField)), pct(overall(oppo))));
+                            }
+                        });
+                    }
+
+                    let slot_stats = player.get_slot_stats();
+                    if !slot_stats.is_empty() {
+                        ui.heading("Batting Order");
+                        egui::Grid::new("slots").striped(true).show(ui, |ui| {
+                            ui.label("Slot");
+                            for header in &BATTING_HEADERS[1..] {
+                                ui.label(header.to_string()).on_hover_text(header.explanation());
+                            }
+                            ui.end_row();
+
+                            for (slot, stats) in &slot_stats {
+                                ui.label(format!("{}", Ordinal(slot + 1)));
+                                for header in &BATTING_HEADERS[1..] {
+                                    ui.label(header.value(stats.get_stat(*header)));
+                                }
+                                ui.end_row();
+                            }
+                        });
+                    }
+
                     mode
                 }
-                Mode::BatLeaders(disp_league, result, reverse) => {
-                    let league = &self.leagues[*disp_league];
-                    let mut mode = Mode::BatLeaders(*disp_league, *result, *reverse);
+                Mode::BatLeaders(disp_league, result, reverse, filter, qualified) => {
+                    let league = &self.engine.leagues[*disp_league];
+                    let mut mode = Mode::BatLeaders(*disp_league, *result, *reverse, *filter, *qualified);
 
                     ScrollArea::both().show(ui, |ui| {
-                        egui::Grid::new("bleaders").striped(true).show(ui, |ui| {
-                            mode = display_leaders(ui, true, &BATTING_HEADERS, league, &self.team_map, &self.player_map, mode);
-                        });
+                        mode = display_leaders(ui, true, &[&BATTING_HEADERS[..], &[Stat::Bwar]].concat(), league, &self.engine.team_map, &self.engine.player_map, mode);
                     });
 
                     mode
                 }
-                Mode::PitLeaders(disp_league, result, reverse) => {
-                    let league = &self.leagues[*disp_league];
-                    let mut mode = Mode::PitLeaders(*disp_league, *result, *reverse);
+                Mode::PitLeaders(disp_league, result, reverse, filter, qualified) => {
+                    let league = &self.engine.leagues[*disp_league];
+                    let mut mode = Mode::PitLeaders(*disp_league, *result, *reverse, *filter, *qualified);
 
                     ScrollArea::both().show(ui, |ui| {
-                        egui::Grid::new("pleaders").striped(true).show(ui, |ui| {
-                            mode = display_leaders(ui, false, &PITCHING_HEADERS, league, &self.team_map, &self.player_map, mode);
-                        });
+                        mode = display_leaders(ui, false, &[&PITCHING_HEADERS[..], &[Stat::Pwar]].concat(), league, &self.engine.team_map, &self.engine.player_map, mode);
                     });
 
                     mode
                 }
                 Mode::LeagueRecords(disp_league) => {
-                    let league = &self.leagues[*disp_league];
+                    let league = &self.engine.leagues[*disp_league];
 
                     let mut mode = Mode::LeagueRecords(*disp_league);
 
@@ -949,8 +1762,8 @@ impl App for Imp019App {
 
                         for stat in &RECORD_STATS {
                             if let Some(Some(record)) = league.records.get(stat) {
-                                let team = self.team_map.get(&record.team_id).unwrap();
-                                let player = self.player_map.get(&record.player_id).unwrap();
+                                let team = self.engine.team_map.get(&record.team_id).unwrap();
+                                let player = self.engine.player_map.get(&record.player_id).unwrap();
 
                                 if batting != stat.is_batting() {
                                     cnt = 0;
@@ -980,6 +1793,383 @@ impl App for Imp019App {
                         }
                     });
 
+                    ui.separator();
+                    ui.heading("Single Game Records");
+                    egui::Grid::new("single_game_records").striped(true).show(ui, |ui| {
+                        for stat in &SINGLE_GAME_STATS {
+                            if let Some(Some(record)) = league.single_game_records.get(stat) {
+                                let team = self.engine.team_map.get(&record.team_id).unwrap();
+                                let player = self.engine.player_map.get(&record.player_id).unwrap();
+
+                                ui.label(stat.to_string()).on_hover_text(stat.explanation());
+                                ui.label(stat.value(record.record));
+                                if ui.add(Button::new(player.fullname()).frame(false)).clicked() {
+                                    mode = Mode::Player(*disp_league, record.player_id, None);
+                                }
+                                ui.small(format!("{} - {} Day {}", &team.abbr(), record.year, record.day));
+                                ui.end_row();
+                            }
+                        }
+                    });
+
+                    ui.separator();
+                    ui.heading("Career Records");
+                    egui::Grid::new("career_records").striped(true).show(ui, |ui| {
+                        for stat in &CAREER_STATS {
+                            if let Some(record) = self.engine.career_records.get(stat) {
+                                let player = self.engine.player_map.get(&record.player_id).unwrap();
+
+                                ui.label(stat.to_string()).on_hover_text(stat.explanation());
+                                ui.label(stat.value(record.record));
+                                if ui.add(Button::new(player.fullname()).frame(false)).clicked() {
+                                    mode = Mode::Player(*disp_league, record.player_id, None);
+                                }
+                                ui.small(if player.active { "Active" } else { "Retired" });
+                                ui.end_row();
+                            }
+                        }
+                    });
+
+                    mode
+                }
+                Mode::LeagueStats(disp_league) => {
+                    let league = &self.engine.leagues[*disp_league];
+                    let total = league.total_stats(&self.engine.team_map, &self.engine.player_map);
+
+                    ScrollArea::both().show(ui, |ui| {
+                        ui.heading("Batting");
+                        egui::Grid::new("lbatting").striped(true).show(ui, |ui| {
+                            display_league_stats(ui, &BATTING_HEADERS[2..], league, &total);
+                        });
+                        ui.heading("Pitching");
+                        egui::Grid::new("lpitching").striped(true).show(ui, |ui| {
+                            display_league_stats(ui, &PITCHING_HEADERS[1..], league, &total);
+                        });
+                    });
+
+                    Mode::LeagueStats(*disp_league)
+                }
+                Mode::RunExpectancy(disp_league) => {
+                    let run_expectancy = &self.engine.leagues[*disp_league].run_expectancy;
+
+                    ui.heading("Run Expectancy");
+                    ui.label("Average runs scored from each base-out state through the end of the inning, this season");
+                    egui::Grid::new("run_expectancy").striped(true).show(ui, |ui| {
+                        for header in ["Bases", "0 Outs", "1 Out", "2 Outs"].iter() {
+                            ui.label(*header);
+                        }
+                        ui.end_row();
+                        for bases in 0..8 {
+                            let occupied = (1..=3).map(|o| if bases & (1 << (o - 1)) != 0 { char::from(b'0' + o) } else { '-' }).collect::<String>();
+                            ui.monospace(occupied);
+                            for outs in 0..3 {
+                                let text = run_expectancy.expected(bases, outs).map_or("-".to_string(), |o| format!("{:.3}", o));
+                                ui.monospace(text).on_hover_text(format!("{} times", run_expectancy.chances(bases, outs)));
+                            }
+                            ui.end_row();
+                        }
+                    });
+
+                    Mode::RunExpectancy(*disp_league)
+                }
+                Mode::LeagueHistory(disp_league) => {
+                    let league = &self.engine.leagues[*disp_league];
+
+                    ScrollArea::both().show(ui, |ui| {
+                        if league.history.len() > 1 {
+                            ui.horizontal(|ui| {
+                                for stat in [Stat::Bavg, Stat::Pera].iter() {
+                                    ui.vertical(|ui| {
+                                        ui.heading(stat.to_string());
+                                        let values = league.history.iter().map(|o| (o.year, if *stat == Stat::Bavg { o.avg } else { o.era })).collect::<Vec<_>>();
+                                        display_trend(ui, *stat, &values);
+                                    });
+                                }
+                            });
+                        }
+
+                        egui::Grid::new("lhistory").striped(true).show(ui, |ui| {
+                            for header in ["Year", "G", "AVG", "ERA", "R/G", "HR/G"].iter() {
+                                ui.label(*header);
+                            }
+                            ui.end_row();
+                            for season in league.history.iter().rev() {
+                                ui.label(season.year.to_string());
+                                ui.label(season.games.to_string());
+                                ui.label(Stat::Bavg.value(season.avg));
+                                ui.label(Stat::Pera.value(season.era));
+                                ui.label(format!("{:.2}", season.per_game(season.runs)));
+                                ui.label(format!("{:.2}", season.per_game(season.hr)));
+                                ui.end_row();
+                            }
+                        });
+                        if league.history.is_empty() {
+                            ui.label("No completed seasons yet.");
+                        }
+                    });
+
+                    Mode::LeagueHistory(*disp_league)
+                }
+                Mode::Dashboard => {
+                    let mut mode = Mode::Dashboard;
+                    let team_id = self.favorite.unwrap();
+                    let team = self.engine.team_map.get(&team_id).unwrap();
+                    let league_idx = self.engine.leagues.iter().position(|o| o.teams.contains(&team_id)).unwrap();
+                    let league = &self.engine.leagues[league_idx];
+
+                    ui.horizontal(|ui| {
+                        if ui.add(Button::new(RichText::new(team.name()).heading()).frame(false)).clicked() {
+                            mode = Mode::Team(league_idx, team_id);
+                        }
+                    });
+                    let rank = league.final_standings(&self.engine.team_map).iter().position(|o| *o == team_id).unwrap() + 1;
+                    ui.horizontal(|ui| {
+                        ui.label(format!("{}-{}, {} in", team.get_wins(), team.get_losses(), Ordinal(rank)));
+                        if ui.add(Button::new(format!("League {}", league_idx + 1)).frame(false)).clicked() {
+                            mode = Mode::Standings(league_idx);
+                        }
+                    });
+
+                    let plays = |o: &&Game| o.home.id == team_id || o.away.id == team_id;
+                    let played = league.cur_idx.min(league.schedule.games.len());
+                    ui.horizontal(|ui| {
+                        if let Some((idx, game)) = league.schedule.games[..played].iter().enumerate().rev().find(|o| plays(&o.1)) {
+                            ui.vertical(|ui| {
+                                ui.label("Last Game");
+                                if display_game(ui, game, &self.engine.team_map, false) {
+                                    mode = Mode::BoxScore(league_idx, idx);
+                                }
+                            });
+                        }
+                        if let Some(idx) = league.schedule.games[played..].iter().position(|o| plays(&o)) {
+                            let day = (played + idx) / (league.teams.len() / 2);
+                            ui.vertical(|ui| {
+                                if ui.add(Button::new(format!("Next Game: Day {}", day + 1)).frame(false)).clicked() {
+                                    mode = Mode::Schedule(league_idx, Some(day));
+                                }
+                                display_game(ui, &league.schedule.games[played + idx], &self.engine.team_map, false);
+                            });
+                        }
+                    });
+
+                    ui.heading("Team Leaders");
+                    egui::Grid::new("dashboard_leaders").striped(true).show(ui, |ui| {
+                        let games = team.results.games();
+                        for stat in [Stat::Bavg, Stat::Bhr, Stat::Brbi, Stat::Pera, Stat::Pw, Stat::Pso] {
+                            let leader = team.iter_players(&self.engine.player_map)
+                                .filter(|o| o.1.pos.is_pitcher() != stat.is_batting())
+                                .map(|o| (o.0, o.1.get_stats()))
+                                .filter(|o| stat.is_qualified(&o.1, games))
+                                .max_by_key(|o| {
+                                    let value = o.1.get_stat(stat) as i64;
+                                    if stat.is_reverse_sort() { -value } else { value }
+                                });
+                            ui.label(stat.to_string());
+                            if let Some((player_id, stats)) = leader {
+                                let player = self.engine.player_map.get(&player_id).unwrap();
+                                if ui.add(Button::new(player.fname()).frame(false)).clicked() {
+                                    mode = Mode::Player(league_idx, player_id, Some(team_id));
+                                }
+                                ui.label(stat.value(stats.get_stat(stat)));
+                            }
+                            ui.end_row();
+                        }
+                    });
+
+                    ui.heading("Recent News");
+                    for event in self.engine.events.iter().rev().filter(|o| o.involves(team_id, &team.players)).take(5) {
+                        if let Some(new_mode) = display_event(ui, event, &self.engine.team_map, &self.engine.player_map) {
+                            mode = new_mode;
+                        }
+                    }
+
+                    mode
+                }
+                Mode::SeasonSummary(year) => {
+                    let mut mode = Mode::SeasonSummary(*year);
+
+                    ui.horizontal(|ui| {
+                        let played = |year: u32| self.engine.events.iter().any(|o| matches!(o, LeagueEvent::Champion(_, _, y) if *y == year));
+                        if ui.add_enabled(played(year - 1), Button::new("Prev")).clicked() {
+                            mode = Mode::SeasonSummary(year - 1);
+                        }
+                        ui.heading(format!("{} Season", year));
+                        if ui.add_enabled(played(year + 1), Button::new("Next")).clicked() {
+                            mode = Mode::SeasonSummary(year + 1);
+                        }
+                    });
+
+                    ScrollArea::vertical().show(ui, |ui| {
+                        for (section, title) in SUMMARY_SECTIONS.iter().enumerate() {
+                            let events = self.engine.events.iter().filter(|o| o.year() == *year && summary_section(o) == section).collect::<Vec<_>>();
+                            if events.is_empty() {
+                                continue;
+                            }
+                            ui.separator();
+                            ui.heading(*title);
+                            for event in events {
+                                if let Some(new_mode) = display_event(ui, event, &self.engine.team_map, &self.engine.player_map) {
+                                    mode = new_mode;
+                                }
+                            }
+                        }
+                    });
+
+                    mode
+                }
+                Mode::Exhibition(away, home) => {
+                    let (mut away, mut home) = (*away, *home);
+                    let mut teams = self.engine.team_map.iter().collect::<Vec<_>>();
+                    teams.sort_by_key(|o| o.1.name());
+
+                    ui.heading("Exhibition");
+                    ui.horizontal(|ui| {
+                        for (label, team_id) in [("Away", &mut away), ("Home", &mut home)] {
+                            ui.label(label);
+                            egui::ComboBox::from_id_source(label).selected_text(self.engine.team_map[team_id].name()).show_ui(ui, |ui| {
+                                for (id, team) in &teams {
+                                    ui.selectable_value(team_id, **id, team.name());
+                                }
+                            });
+                        }
+                    });
+
+                    // the result is view state only, the game itself never touches the season
+                    let id = ui.make_persistent_id("exhibition");
+                    if ui.add_enabled(away != home, Button::new("Play")).clicked() {
+                        let game = self.engine.exhibition(away, home, rand::random());
+                        let text = format!("{}\n\n{}", game.recap(&self.engine.team_map, &self.engine.player_map), game.to_boxscore_text(&self.engine.team_map, &self.engine.player_map));
+                        ui.data_mut(|o| o.insert_temp(id, (away, home, text)));
+                    }
+                    if let Some((_, _, text)) = ui.data(|o| o.get_temp::<(TeamId, TeamId, String)>(id)).filter(|o| (o.0, o.1) == (away, home)) {
+                        ScrollArea::vertical().show(ui, |ui| {
+                            ui.monospace(text);
+                        });
+                    }
+
+                    Mode::Exhibition(away, home)
+                }
+                Mode::Compare(left, right) => {
+                    let (mut left, mut right) = (*left, *right);
+                    let players = &self.engine.player_map;
+
+                    ui.heading("Compare Players");
+                    ui.columns(2, |columns| {
+                        for (ui, (side, player_id)) in columns.iter_mut().zip([("compare_left", &mut left), ("compare_right", &mut right)]) {
+                            let player = &players[player_id];
+                            ui.strong(player.fullname());
+                            ui.label(format!("{}, age {}", player.pos, player.age(self.engine.year)));
+                            if let Some(picked) = player_search(ui, side, players) {
+                                *player_id = picked;
+                            }
+                        }
+                    });
+                    ui.separator();
+
+                    // the left player's side of the ball sets the stats compared
+                    let (a, b) = (&players[&left], &players[&right]);
+                    let headers = if a.pos.is_pitcher() { &PITCHING_HEADERS[..] } else { &BATTING_HEADERS[..] };
+                    let (season, career) = ((a.get_stats(), b.get_stats()), (a.career_stats(), b.career_stats()));
+                    ScrollArea::vertical().show(ui, |ui| {
+                        egui::Grid::new("compare").striped(true).show(ui, |ui| {
+                            for header in ["", "Season", "", "Career", ""].iter() {
+                                ui.label(*header);
+                            }
+                            ui.end_row();
+                            ui.label("");
+                            for _ in 0..2 {
+                                ui.label(a.fname());
+                                ui.label(b.fname());
+                            }
+                            ui.end_row();
+
+                            for stat in headers {
+                                ui.label(stat.to_string()).on_hover_text(stat.explanation());
+                                for (mine, theirs) in [(&season.0, &season.1), (&season.1, &season.0), (&career.0, &career.1), (&career.1, &career.0)] {
+                                    let (value, other) = (mine.get_stat(*stat), theirs.get_stat(*stat));
+                                    let text = RichText::new(stat.value(value));
+                                    ui.label(if is_better(*stat, value, other) { text.strong().background_color(highlight_color(ui)) } else { text });
+                                }
+                                ui.end_row();
+                            }
+                        });
+                    });
+
+                    Mode::Compare(left, right)
+                }
+                Mode::CompareTeams(left, right) => {
+                    let (mut left, mut right) = (*left, *right);
+                    let mut teams = self.engine.team_map.iter().collect::<Vec<_>>();
+                    teams.sort_by_key(|o| o.1.name());
+
+                    ui.heading("Compare Teams");
+                    ui.horizontal(|ui| {
+                        for (side, team_id) in [("compare_teams_left", &mut left), ("compare_teams_right", &mut right)] {
+                            egui::ComboBox::from_id_source(side).selected_text(self.engine.team_map[team_id].name()).show_ui(ui, |ui| {
+                                for (id, team) in &teams {
+                                    ui.selectable_value(team_id, **id, team.name());
+                                }
+                            });
+                        }
+                    });
+                    ui.separator();
+
+                    let (a, b) = (&self.engine.team_map[&left], &self.engine.team_map[&right]);
+                    let players = &self.engine.player_map;
+                    let ahead = |x: i64, y: i64, higher: bool| if higher { [x > y, y > x] } else { [x < y, y < x] };
+                    // only meetings this season count, so teams in different leagues have none
+                    let h2h = self.engine.leagues.iter().find(|o| o.teams.contains(&left) && o.teams.contains(&right)).filter(|_| left != right).map(|o| [o.head_to_head(left, &[right]), o.head_to_head(right, &[left])]);
+
+                    ScrollArea::vertical().show(ui, |ui| {
+                        egui::Grid::new("compare_teams").striped(true).show(ui, |ui| {
+                            ui.label("");
+                            ui.label(a.abbr());
+                            ui.label(b.abbr());
+                            ui.end_row();
+
+                            let counts = [
+                                ("W", a.get_wins(), b.get_wins(), true),
+                                ("L", a.get_losses(), b.get_losses(), false),
+                                ("RS", a.results.runs_for, b.results.runs_for, true),
+                                ("RA", a.results.runs_against, b.results.runs_against, false),
+                            ];
+                            for (label, x, y, higher) in counts {
+                                display_compare_row(ui, label, [x.to_string(), y.to_string()], ahead(x as i64, y as i64, higher));
+                            }
+                            let (x, y) = (a.win_pct(), b.win_pct());
+                            display_compare_row(ui, "Pct", [Stat::Bavg.value(x), Stat::Bavg.value(y)], ahead(x as i64, y as i64, true));
+                            let (x, y) = (a.results.run_diff(), b.results.run_diff());
+                            display_compare_row(ui, "RD", [format!("{:+}", x), format!("{:+}", y)], ahead(x as i64, y as i64, true));
+                            if let Some([x, y]) = h2h {
+                                display_compare_row(ui, "H2H", [x.to_string(), y.to_string()], ahead(x as i64, y as i64, true));
+                            }
+
+                            for (heading, is_batter, headers) in [("Batting", true, &BATTING_HEADERS[2..]), ("Pitching", false, &PITCHING_HEADERS[1..])] {
+                                ui.strong(heading);
+                                ui.end_row();
+                                let (mine, theirs) = (a.total_stats(players, is_batter), b.total_stats(players, is_batter));
+                                for stat in headers {
+                                    let (x, y) = (mine.get_stat(*stat), theirs.get_stat(*stat));
+                                    display_compare_row(ui, &stat.to_string(), [stat.value(x), stat.value(y)], [is_better(*stat, x, y), is_better(*stat, y, x)]);
+                                }
+                            }
+                        });
+                    });
+
+                    Mode::CompareTeams(left, right)
+                }
+                Mode::News => {
+                    let mut mode = Mode::News;
+
+                    ui.heading("News");
+                    ScrollArea::vertical().show(ui, |ui| {
+                        for event in self.engine.events.iter().rev() {
+                            if let Some(new_mode) = display_event(ui, event, &self.engine.team_map, &self.engine.player_map) {
+                                mode = new_mode;
+                            }
+                        }
+                    });
+
                     mode
                 }
             }
@@ -1002,3 +2192,195 @@ impl App for Imp019App {
     //fn max_size_points(&self) -> egui::Vec2 { egui::Vec2::new(2048.0, 1024.0) }
 }
 
+#[cfg(test)]
+mod tests {
+    use std::time::Instant;
+
+    use crate::app::{is_better, Imp019App, Mode};
+    use crate::data::Data;
+    use crate::engine::{Engine, GameConfig};
+    use crate::league::LeagueEvent;
+    use crate::stat::Stat;
+
+    #[test]
+    fn test_new_with_config() {
+        let config = GameConfig { leagues: 2, teams_per_league: 8, ..GameConfig::default() };
+        assert!(config.validate(&Data::new()).is_ok());
+
+        let app = Imp019App::new_with_config(&config);
+        assert_eq!(app.engine.leagues.len(), 2);
+        assert_eq!(app.engine.team_map.len(), 16);
+        assert_eq!(app.engine.player_map.len(), 16 * config.players_per_team);
+        for league in &app.engine.leagues {
+            assert_eq!(league.teams.len(), 8);
+            assert_eq!(league.games_remaining(league.teams[0]), 8 * 7);
+        }
+
+        let odd = GameConfig { teams_per_league: 7, ..config };
+        assert!(odd.validate(&Data::new()).is_err());
+        let too_many = GameConfig { leagues: 10, teams_per_league: 40, ..config };
+        assert!(too_many.validate(&Data::new()).is_err());
+
+        assert!(Mode::Standings(1).is_valid(&app.engine.leagues, &app.engine.team_map, &app.engine.player_map, None));
+        assert!(!Mode::Standings(2).is_valid(&app.engine.leagues, &app.engine.team_map, &app.engine.player_map, None));
+        assert!(Mode::Team(0, 16).is_valid(&app.engine.leagues, &app.engine.team_map, &app.engine.player_map, None));
+        assert!(!Mode::Team(0, 17).is_valid(&app.engine.leagues, &app.engine.team_map, &app.engine.player_map, None));
+        assert!(!Mode::Player(0, 0, None).is_valid(&app.engine.leagues, &app.engine.team_map, &app.engine.player_map, None));
+        assert!(!Mode::BoxScore(0, 1000).is_valid(&app.engine.leagues, &app.engine.team_map, &app.engine.player_map, None));
+        assert!(!Mode::Dashboard.is_valid(&app.engine.leagues, &app.engine.team_map, &app.engine.player_map, None));
+        assert!(Mode::Dashboard.is_valid(&app.engine.leagues, &app.engine.team_map, &app.engine.player_map, Some(1)));
+        let player_id = *app.engine.player_map.keys().next().unwrap();
+        assert!(Mode::Compare(player_id, player_id).is_valid(&app.engine.leagues, &app.engine.team_map, &app.engine.player_map, None));
+        assert!(!Mode::Compare(player_id, 0).is_valid(&app.engine.leagues, &app.engine.team_map, &app.engine.player_map, None));
+        assert!(Mode::CompareTeams(1, 16).is_valid(&app.engine.leagues, &app.engine.team_map, &app.engine.player_map, None));
+        assert!(!Mode::CompareTeams(1, 17).is_valid(&app.engine.leagues, &app.engine.team_map, &app.engine.player_map, None));
+    }
+
+    #[test]
+    fn test_is_better() {
+        assert!(is_better(Stat::Bhr, 30, 20));
+        assert!(!is_better(Stat::Bhr, 20, 20));
+        assert!(is_better(Stat::Pera, 2500, 3100));
+        assert!(!is_better(Stat::Pera, 3100, 2500));
+    }
+
+    #[test]
+    fn test_undo_day() {
+        let config = GameConfig { leagues: 1, teams_per_league: 4, ..GameConfig::default() };
+        let mut app = Imp019App::new_with_config(&config);
+
+        app.sim_day();
+        let played = app.engine.leagues[0].cur_idx;
+        let wins = app.engine.team_map.values().map(|o| o.get_wins()).sum::<u32>();
+        app.sim_day();
+        assert!(app.engine.leagues[0].cur_idx > played);
+
+        // every game of the day replays from the day's snapshot, the day before has nothing to replay from
+        for (idx, game) in app.engine.leagues[0].schedule.games[..app.engine.leagues[0].cur_idx].iter().enumerate() {
+            match app.replay_start(0, idx) {
+                Some(before) => {
+                    assert!(idx >= played);
+                    let replay = game.replay(&before.team_map, &before.player_map, app.engine.year, app.engine.leagues[0].run_env);
+                    assert!(replay.playbyplay == game.playbyplay);
+                }
+                None => assert!(idx < played),
+            }
+        }
+
+        let first_run = app.engine.leagues[0].schedule.games[played..app.engine.leagues[0].cur_idx].to_vec();
+        app.undo_day();
+        assert_eq!(app.engine.leagues[0].cur_idx, played);
+        assert_eq!(app.engine.team_map.values().map(|o| o.get_wins()).sum::<u32>(), wins);
+
+        // the rng is rewound too, so simming the day again plays out exactly the same
+        app.sim_day();
+        let second_run = &app.engine.leagues[0].schedule.games[played..app.engine.leagues[0].cur_idx];
+        assert_eq!(first_run.len(), second_run.len());
+        for (first, second) in first_run.iter().zip(second_run) {
+            assert_eq!((first.away.r, first.home.r), (second.away.r, second.home.r));
+            assert!(first.playbyplay == second.playbyplay);
+        }
+        app.undo_day();
+
+        // only the last day is kept
+        assert!(app.undo.is_none());
+        app.undo_day();
+        assert_eq!(app.engine.leagues[0].cur_idx, played);
+    }
+
+    #[test]
+    fn test_fast_sim() {
+        let config = GameConfig { leagues: 1, teams_per_league: 4, ..GameConfig::default() };
+        let mut app = Imp019App::new_with_config(&config);
+
+        app.sim_day();
+        app.engine.fast_sim = true;
+        let played = app.engine.leagues[0].cur_idx;
+        app.sim_day();
+
+        let games = &app.engine.leagues[0].schedule.games;
+        assert!(games[..played].iter().all(|o| o.has_playbyplay()));
+        for game in &games[played..app.engine.leagues[0].cur_idx] {
+            assert!(!game.has_playbyplay());
+            assert!(game.playbyplay.is_empty());
+            let boxscore = game.boxscore();
+            assert_eq!(boxscore.home_runs.iter().map(|o| *o as u32).sum::<u32>(), game.home.r as u32);
+            assert!(boxscore.winner.is_some() && boxscore.loser.is_some());
+        }
+    }
+
+    #[test]
+    fn test_season_summary() {
+        let config = GameConfig { leagues: 1, teams_per_league: 4, ..GameConfig::default() };
+        let mut app = Imp019App::new_with_config(&config);
+        let year = app.engine.year;
+
+        while app.engine.year == year {
+            app.sim_day();
+        }
+        assert!(app.disp_mode == Mode::SeasonSummary(year));
+        assert!(app.engine.events.iter().any(|o| o.year() == year && matches!(o, LeagueEvent::Champion(1, _, _))));
+    }
+
+    #[test]
+    fn test_seeded_league() {
+        let config = GameConfig { leagues: 2, teams_per_league: 4, seed: Some(19), ..GameConfig::default() };
+        let mut first = Imp019App::new_with_config(&config);
+        let mut second = Imp019App::new_with_config(&config);
+
+        // the whole first season, rollover included, plays out the same from the same seed
+        let year = first.engine.year;
+        while first.engine.year == year {
+            first.sim_day();
+            second.sim_day();
+        }
+        second.sim_day();
+        first.sim_day();
+        assert!(first.engine.leagues.iter().zip(&second.engine.leagues).all(|(a, b)| a.teams == b.teams));
+        for (a, b) in first.engine.leagues.iter().zip(&second.engine.leagues) {
+            assert!(a.schedule.games.iter().zip(&b.schedule.games).all(|(a, b)| a.playbyplay == b.playbyplay));
+        }
+        assert_eq!(first.engine.events.len(), second.engine.events.len());
+    }
+
+    // cargo test --release bench_fast_sim_memory -- --ignored --nocapture
+    #[test]
+    #[ignore]
+    fn bench_fast_sim_memory() {
+        const SEASONS: usize = 10;
+        const MB: f64 = 1024.0 * 1024.0;
+        for fast_sim in [false, true].iter() {
+            let mut engine = Engine::with_config(&GameConfig { seed: Some(2049), ..GameConfig::default() });
+            engine.fast_sim = *fast_sim;
+            let held = |engine: &Engine| engine.leagues.iter().flat_map(|o| &o.schedule.games).map(|o| o.heap_bytes()).sum::<usize>();
+
+            // schedules are rebuilt every season, so logs never outlive the year they were played in
+            let mut peak = 0;
+            for _ in 0..SEASONS {
+                while !engine.sim_day() {
+                    peak = peak.max(held(&engine));
+                }
+            }
+            println!("fast sim {}: game logs peak at {:.2}MB over {} seasons", fast_sim, peak as f64 / MB, SEASONS);
+        }
+    }
+
+    // cargo test --release bench_season -- --ignored --nocapture
+    #[test]
+    #[ignore]
+    fn bench_season() {
+        let config = GameConfig { seed: Some(2049), ..GameConfig::default() };
+        let mut app = Imp019App::new_with_config(&config);
+
+        let start = Instant::now();
+        let mut peak = 0;
+        while !app.engine.sim_day() {
+            let held = app.engine.leagues.iter().flat_map(|o| &o.schedule.games).filter(|o| o.has_playbyplay()).count();
+            peak = peak.max(held);
+        }
+        let elapsed = start.elapsed();
+
+        let games = app.engine.leagues.iter().map(|o| o.schedule.games.len()).sum::<usize>();
+        println!("{} games in {:.2?}, {:.2?} per game, peak {} game logs held", games, elapsed, elapsed / games as u32, peak);
+    }
+}
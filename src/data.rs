@@ -1,8 +1,8 @@
 use std::collections::{HashMap, HashSet};
 use std::hash::{Hash, Hasher};
 
-use rand::rngs::ThreadRng;
-use rand::seq::SliceRandom;
+use rand::Rng;
+use rand::seq::{IteratorRandom, SliceRandom};
 
 #[derive(Clone, PartialEq, Eq, Hash)]
 pub(crate) struct LocData {
@@ -16,6 +16,11 @@ pub(crate) struct LocData {
 }
 
 impl LocData {
+    // bigger markets start out with more fans, roughly 50 to 70
+    pub(crate) fn base_popularity(&self) -> f64 {
+        (self.population.max(1) as f64).log10() * 10.0
+    }
+
     fn parse(in_str: &'static str) -> Self {
         let mut parts = in_str.split(',');
         let abbr = parts.next().unwrap_or("");
@@ -70,8 +75,13 @@ impl PartialEq for NickData {
 }
 
 impl NickData {
+    // a language missing from the nick headers, or left blank, gets English and then the first one there is
     pub(crate) fn name(&self, location: &LocData) -> &'static str {
-        self.localized.get(location.lang).unwrap_or(&"")
+        let found = |lang: &str| self.localized.get(lang).copied().filter(|o| !o.is_empty());
+        found(location.lang)
+            .or_else(|| found(DEFAULT_NICK_LANG))
+            .or_else(|| self.localized.iter().filter(|o| !o.1.is_empty()).min_by_key(|o| *o.0).map(|o| *o.1))
+            .unwrap_or("")
     }
 
     fn parse(in_str: &'static str, headers: &[&'static str]) -> Self {
@@ -81,6 +91,7 @@ impl NickData {
     }
 }
 
+#[derive(Default)]
 pub(crate) struct Data {
     loc: Vec<LocData>,
     nick: Vec<NickData>,
@@ -89,17 +100,8 @@ pub(crate) struct Data {
     pub(crate) age: Vec<AgeData>,
 }
 
-impl Default for Data {
-    fn default() -> Self {
-        Self {
-            loc: Vec::new(),
-            nick: Vec::new(),
-            names_first: HashMap::new(),
-            names_last: HashMap::new(),
-            age: Vec::new(),
-        }
-    }
-}
+const DEFAULT_NAME_COUNTRY: &str = "US";
+const DEFAULT_NICK_LANG: &str = "EN";
 
 fn weighted(in_str: &'static str) -> Option<(&'static str, u32)> {
     let mut line = in_str.split(',');
@@ -116,69 +118,106 @@ impl Data {
         let headers = nick_raw.next().unwrap_or("EN").split(',').collect::<Vec<_>>();
         let nick = nick_raw.map(|o| NickData::parse(o, &headers)).collect();
 
-        let mut names_first = HashMap::new();
-        names_first.insert("US", include_str!("../data/names_us_first.csv").lines().map(weighted).flatten().collect());
-        names_first.insert("CA", include_str!("../data/names_ca_first.csv").lines().map(weighted).flatten().collect());
-        names_first.insert("MX", include_str!("../data/names_mx_first.csv").lines().map(weighted).flatten().collect());
-        let mut names_last = HashMap::new();
-        names_last.insert("US", include_str!("../data/names_us_last.csv").lines().map(weighted).flatten().collect());
-        names_last.insert("CA", include_str!("../data/names_ca_last.csv").lines().map(weighted).flatten().collect());
-        names_last.insert("MX", include_str!("../data/names_mx_last.csv").lines().map(weighted).flatten().collect());
-
         let age = include_str!("../data/age.csv").lines().map(AgeData::parse).collect();
 
-        Self {
+        let mut data = Self {
             loc,
             nick,
-            names_first,
-            names_last,
+            names_first: HashMap::new(),
+            names_last: HashMap::new(),
             age,
-        }
+        };
+        data.add_names("US", include_str!("../data/names_us_first.csv"), include_str!("../data/names_us_last.csv"));
+        data.add_names("CA", include_str!("../data/names_ca_first.csv"), include_str!("../data/names_ca_last.csv"));
+        data.add_names("MX", include_str!("../data/names_mx_first.csv"), include_str!("../data/names_mx_last.csv"));
+        data
     }
 
-    pub(crate) fn get_locs(&self, existing: &mut HashSet<LocData>, rng: &mut ThreadRng, count: usize) -> Vec<LocData> {
-        while existing.len() != count {
-            existing.insert(self.loc.choose(rng).unwrap().clone());
-        }
-        existing.iter().cloned().collect()
+    // first and last name pools for a country, as name,weight lines
+    pub(crate) fn add_names(&mut self, country: &'static str, first: &'static str, last: &'static str) {
+        self.names_first.insert(country, first.lines().filter_map(weighted).collect());
+        self.names_last.insert(country, last.lines().filter_map(weighted).collect());
     }
 
-    pub(crate) fn get_nicks(&self, nicks: &mut HashSet<NickData>, rng: &mut ThreadRng, count: usize) -> Vec<NickData> {
-        while nicks.len() != count {
-            nicks.insert(self.nick.choose(rng).unwrap().clone());
-        }
-        nicks.iter().cloned().collect()
+    pub(crate) fn loc_count(&self) -> usize {
+        self.loc.len()
+    }
+
+    pub(crate) fn nick_count(&self) -> usize {
+        self.nick.len()
     }
 
-    pub(crate) fn choose_name_first(&self, country: &str, rng: &mut ThreadRng) -> &'static str {
-        if let Ok(first_name) = self.names_first.get(country).unwrap().choose_weighted(rng, |o| o.1) {
-            first_name.0
-        } else {
-            ""
+    // walking a shuffled copy stops once the data runs out, so asking for too many can't hang
+    // new picks come back in draw order, so a seeded league names its teams the same way
+    pub(crate) fn get_locs(&self, existing: &mut HashSet<LocData>, rng: &mut impl Rng, count: usize) -> Vec<LocData> {
+        let mut drawn = existing.iter().cloned().collect::<Vec<_>>();
+        for loc in self.loc.choose_multiple(rng, self.loc.len()) {
+            if existing.len() >= count {
+                break;
+            }
+            if existing.insert(loc.clone()) {
+                drawn.push(loc.clone());
+            }
         }
+        drawn
     }
 
-    pub(crate) fn choose_name_last(&self, country: &str, rng: &mut ThreadRng) -> &'static str {
-        if let Ok(last_name) = self.names_last.get(country).unwrap().choose_weighted(rng, |o| o.1) {
-            last_name.0
-        } else {
-            ""
+    pub(crate) fn get_nicks(&self, nicks: &mut HashSet<NickData>, rng: &mut impl Rng, count: usize) -> Vec<NickData> {
+        let mut drawn = nicks.iter().cloned().collect::<Vec<_>>();
+        for nick in self.nick.choose_multiple(rng, self.nick.len()) {
+            if nicks.len() >= count {
+                break;
+            }
+            if nicks.insert(nick.clone()) {
+                drawn.push(nick.clone());
+            }
         }
+        drawn
+    }
+
+    pub(crate) fn get_unused_loc(&self, used: &HashSet<LocData>, rng: &mut impl Rng) -> Option<LocData> {
+        self.loc.iter().filter(|o| !used.contains(o)).choose(rng).cloned()
+    }
+
+    pub(crate) fn get_unused_nick(&self, used: &HashSet<NickData>, rng: &mut impl Rng) -> Option<NickData> {
+        self.nick.iter().filter(|o| !used.contains(o)).choose(rng).cloned()
+    }
+
+    // countries without their own pool borrow the default one, or failing that the largest left
+    // so a city from anywhere can't crash player generation
+    fn choose_name(names: &HashMap<&'static str, Vec<(&'static str, u32)>>, country: &str, rng: &mut impl Rng) -> &'static str {
+        let pool = names.get(country).or_else(|| names.get(DEFAULT_NAME_COUNTRY)).or_else(|| names.values().max_by_key(|o| o.len()));
+        pool.and_then(|o| o.choose_weighted(rng, |o| o.1).ok()).map_or("", |o| o.0)
     }
 
-    pub(crate) fn choose_location(&self, rng: &mut ThreadRng) -> &LocData {
+    pub(crate) fn choose_name_first(&self, country: &str, rng: &mut impl Rng) -> &'static str {
+        Self::choose_name(&self.names_first, country, rng)
+    }
+
+    pub(crate) fn choose_name_last(&self, country: &str, rng: &mut impl Rng) -> &'static str {
+        Self::choose_name(&self.names_last, country, rng)
+    }
+
+    pub(crate) fn choose_location(&self, rng: &mut impl Rng) -> &LocData {
         self.loc.choose_weighted(rng, |o| o.population).unwrap()
     }
 }
 
 #[cfg(test)]
 mod tests {
+    use std::collections::HashSet;
+
+    use rand::SeedableRng;
+    use rand::rngs::StdRng;
+
+    use crate::data::{Data, LocData, NickData, DEFAULT_NAME_COUNTRY};
+    use crate::player::{generate_players, AgeProfile, PlayerMap};
+
     #[test]
     fn test_abbr() {
         let mut abbr = include_str!("../data/loc.csv")
             .lines()
-            .map(|o| o.split(',').next())
-            .flatten()
+            .filter_map(|o| o.split(',').next())
             .collect::<Vec<_>>();
 
         abbr.sort_unstable();
@@ -211,4 +250,66 @@ mod tests {
         }
         assert_eq!(error, 0, "{} duplicates found.", error);
     }
+
+    #[test]
+    fn test_get_too_many() {
+        let data = Data::new();
+        let mut rng = StdRng::seed_from_u64(2049);
+
+        let nicks = data.get_nicks(&mut HashSet::new(), &mut rng, data.nick_count() + 10);
+        assert!(nicks.len() <= data.nick_count());
+        let locs = data.get_locs(&mut HashSet::new(), &mut rng, data.loc_count() + 10);
+        assert!(locs.len() <= data.loc_count());
+
+        assert_eq!(data.get_nicks(&mut HashSet::new(), &mut rng, 20).len(), 20);
+    }
+
+    #[test]
+    fn test_unsupported_country_names() {
+        let mut data = Data::new();
+        let mut rng = StdRng::seed_from_u64(2049);
+
+        // no pool for Japan, so the default one is used rather than panicking
+        let first = data.choose_name_first("JP", &mut rng);
+        assert!(data.names_first[DEFAULT_NAME_COUNTRY].iter().any(|o| o.0 == first));
+        assert!(!data.choose_name_last("JP", &mut rng).is_empty());
+
+        data.add_names("JP", "Haruto,1", "Sato,1");
+        assert_eq!(data.choose_name_first("JP", &mut rng), "Haruto");
+        assert_eq!(data.choose_name_last("JP", &mut rng), "Sato");
+    }
+
+    #[test]
+    fn test_generate_from_unsupported_country() {
+        let mut data = Data::new();
+        let mut rng = StdRng::seed_from_u64(2049);
+        data.loc = vec![LocData::parse("TKY,Tokyo,TK,JP,13960000,JA,35.6895,139.6917")];
+
+        let mut players = PlayerMap::new();
+        generate_players(&mut players, 20, 2049, &data, &AgeProfile::default(), &mut rng);
+        assert!(players.values().all(|o| o.birthplace.ends_with("JP") && !o.fullname().trim().is_empty()));
+
+        // without the default pool the largest one left stands in, and with none at all the name is blank
+        data.names_first.remove(DEFAULT_NAME_COUNTRY);
+        let first = data.choose_name_first("JP", &mut rng);
+        assert!(data.names_first.values().max_by_key(|o| o.len()).unwrap().iter().any(|o| o.0 == first));
+        data.names_first.clear();
+        assert_eq!(data.choose_name_first("JP", &mut rng), "");
+    }
+
+    #[test]
+    fn test_nick_fallback() {
+        let tokyo = LocData::parse("TKY,Tokyo,TK,JP,13960000,JA,35.6895,139.6917");
+        let montreal = LocData::parse("MTL,Montreal,QC,CA,1762949,FR,45.5017,-73.5673");
+
+        let nick = NickData::parse("Owls,Buhos,Hiboux", &["EN", "ES", "FR"]);
+        assert_eq!(nick.name(&montreal), "Hiboux");
+        assert_eq!(nick.name(&tokyo), "Owls");
+
+        // no English either, so the first language there is
+        let nick = NickData::parse("Buhos,Hiboux", &["ES", "FR"]);
+        assert_eq!(nick.name(&tokyo), "Buhos");
+        let nick = NickData::parse("Owls,,Hiboux", &["EN", "ES", "FR"]);
+        assert_eq!(nick.name(&LocData::parse("MEX,Mexico City,DF,MX,8918653,ES,19.4326,-99.1332")), "Owls");
+    }
 }
@@ -0,0 +1,328 @@
+use std::collections::{HashMap, HashSet};
+
+use rand::SeedableRng;
+use rand::rngs::StdRng;
+
+use crate::data::Data;
+use crate::game::Game;
+use crate::league::{career_records, end_of_season, CareerRecord, League, LeagueEvent, PROMOTION_COUNT};
+use crate::player::{collect_all_active, generate_players, AgeProfile, PlayerId, PlayerMap};
+use crate::playoffs::PlayoffConfig;
+use crate::stat::{Stat, Stats};
+use crate::team::{Team, TeamId, TeamMap, DEFAULT_ROSTER_SIZE, MAX_ROSTER_SIZE};
+
+#[derive(Copy, Clone)]
+pub(crate) struct GameConfig {
+    pub(crate) leagues: usize,
+    pub(crate) teams_per_league: usize,
+    // size of the generated player pool per team, rosters are filled from it
+    pub(crate) players_per_team: usize,
+    pub(crate) roster_size: usize,
+    pub(crate) playoffs: PlayoffConfig,
+    // scales the league-wide offense, below 1.0 for a dead-ball era and above for a juiced ball
+    pub(crate) run_env: f64,
+    // (lead, inning), off by default
+    pub(crate) mercy_rule: Option<(u8, u8)>,
+    // fixed for reproducible leagues and benchmarks, drawn at random otherwise
+    pub(crate) seed: Option<u64>,
+    // ages of generated players, both the starting pool and later replacements
+    pub(crate) ages: AgeProfile,
+    // designated hitter rule for every league, see League::dh
+    pub(crate) dh: bool,
+}
+
+impl Default for GameConfig {
+    fn default() -> Self {
+        Self {
+            leagues: 3,
+            teams_per_league: 20,
+            players_per_team: 60,
+            roster_size: DEFAULT_ROSTER_SIZE,
+            playoffs: PlayoffConfig::default(),
+            run_env: 1.0,
+            mercy_rule: None,
+            seed: None,
+            ages: AgeProfile::default(),
+            dh: true,
+        }
+    }
+}
+
+impl GameConfig {
+    pub(crate) const MIN_PLAYERS_PER_TEAM: usize = 40;
+    pub(crate) const MIN_RUN_ENV: f64 = 0.5;
+    pub(crate) const MAX_RUN_ENV: f64 = 2.0;
+    pub(crate) const DEFAULT_MERCY_RULE: (u8, u8) = (10, 7);
+
+    fn teams(&self) -> usize {
+        self.leagues * self.teams_per_league
+    }
+
+    pub(crate) fn validate(&self, data: &Data) -> Result<(), String> {
+        if self.leagues == 0 {
+            return Err("There must be at least one league.".to_string());
+        }
+        if self.teams_per_league < 2 || !self.teams_per_league.is_multiple_of(2) {
+            return Err("Teams per league must be an even number of at least 2.".to_string());
+        }
+        if self.leagues > 1 && self.teams_per_league < PROMOTION_COUNT * 2 {
+            return Err(format!("Leagues need at least {} teams to promote and relegate {}.", PROMOTION_COUNT * 2, PROMOTION_COUNT));
+        }
+        if !(Team::min_roster_size()..=MAX_ROSTER_SIZE).contains(&self.roster_size) {
+            return Err(format!("Rosters must hold between {} and {} players.", Team::min_roster_size(), MAX_ROSTER_SIZE));
+        }
+        if self.players_per_team < Self::MIN_PLAYERS_PER_TEAM {
+            return Err(format!("At least {} players per team are needed to fill rosters.", Self::MIN_PLAYERS_PER_TEAM));
+        }
+        self.playoffs.validate(self.teams_per_league)?;
+        if !(Self::MIN_RUN_ENV..=Self::MAX_RUN_ENV).contains(&self.run_env) {
+            return Err(format!("Run environment must be between {} and {}.", Self::MIN_RUN_ENV, Self::MAX_RUN_ENV));
+        }
+        if self.mercy_rule.is_some_and(|(lead, inning)| lead == 0 || !(1..=9).contains(&inning)) {
+            return Err("Mercy rule needs a lead of at least 1 run after an inning from 1 to 9.".to_string());
+        }
+        self.ages.validate()?;
+        let available = data.loc_count().min(data.nick_count());
+        if self.teams() > available {
+            return Err(format!("Only {} teams can be named.", available));
+        }
+        Ok(())
+    }
+}
+
+/// The simulation without the GUI: leagues, teams and players, advanced a day or a season at a time.
+#[cfg_attr(feature = "persistence", derive(serde::Deserialize, serde::Serialize))]
+pub struct Engine {
+    // what rng started from, the same seed and config build the same world
+    seed: u64,
+    pub(crate) rng: StdRng,
+    pub(crate) data: Data,
+    pub(crate) player_map: PlayerMap,
+    pub(crate) team_map: TeamMap,
+    pub(crate) leagues: Vec<League>,
+    pub(crate) year: u32,
+    pub(crate) events: Vec<LeagueEvent>,
+    // sim regular season games without keeping a play-by-play, for fast-forwarding many seasons
+    pub(crate) fast_sim: bool,
+    // days of play-by-play kept in full, older games keep only their box score summary
+    pub(crate) keep_log_days: Option<usize>,
+    // careers cross leagues, so these are kept for the whole game and refreshed each offseason
+    pub(crate) career_records: HashMap<Stat, CareerRecord>,
+}
+
+impl Default for Engine {
+    fn default() -> Self {
+        let seed = rand::random();
+        Engine {
+            seed,
+            rng: StdRng::seed_from_u64(seed),
+            data: Data::new(),
+            player_map: PlayerMap::new(),
+            team_map: TeamMap::new(),
+            leagues: Vec::new(),
+            year: 2030,
+            events: Vec::new(),
+            fast_sim: false,
+            keep_log_days: None,
+            career_records: HashMap::new(),
+        }
+    }
+}
+
+impl Engine {
+    /// A new game with the default leagues, reproducible when given a seed.
+    pub fn new(seed: Option<u64>) -> Self {
+        Self::with_config(&GameConfig { seed, ..GameConfig::default() })
+    }
+
+    pub(crate) fn with_config(config: &GameConfig) -> Self {
+        let seed = config.seed.unwrap_or_else(rand::random);
+        let mut rng = StdRng::seed_from_u64(seed);
+        let data = Data::new();
+        let year = 2049;
+        let team_count = config.teams();
+
+        let mut players = PlayerMap::new();
+        generate_players(&mut players, team_count * config.players_per_team, year, &data, &config.ages, &mut rng);
+
+        let mut available = collect_all_active(&players);
+
+        let locs = data.get_locs(&mut HashSet::new(), &mut rng, team_count);
+        let nicks = data.get_nicks(&mut HashSet::new(), &mut rng, team_count);
+
+        let mut teams = TeamMap::new();
+        for team_id in 0..team_count {
+            let loc = locs[team_id].clone();
+            let nick = nicks[team_id].clone();
+            let mut team = Team::new(loc, nick, year);
+            team.roster_size = config.roster_size;
+
+            team.populate(&mut available, &players);
+
+            let team_id = (team_id + 1) as TeamId;
+            teams.insert(team_id, team);
+        }
+
+        let mut remaining_teams = teams.keys().copied().collect();
+
+        let leagues = (1..=config.leagues)
+            .map(|id| {
+                let mut league = League::new(id as u32, config.teams_per_league, &mut remaining_teams, &mut rng);
+                league.playoff_config = config.playoffs;
+                league.run_env = config.run_env;
+                league.mercy_rule = config.mercy_rule;
+                league.ages = config.ages;
+                league.dh = config.dh;
+                league
+            })
+            .collect();
+
+        Engine {
+            seed,
+            rng,
+            data,
+            player_map: players,
+            team_map: teams,
+            leagues,
+            year,
+            events: Vec::new(),
+            fast_sim: false,
+            keep_log_days: None,
+            career_records: HashMap::new(),
+        }
+    }
+
+    /// The seed the world was generated from, pass it back to `new` to build the same one again.
+    pub fn seed(&self) -> u64 {
+        self.seed
+    }
+
+    pub fn year(&self) -> u32 {
+        self.year
+    }
+
+    pub fn league_count(&self) -> usize {
+        self.leagues.len()
+    }
+
+    // one day of games in every league, false once none are left
+    pub(crate) fn sim_games(&mut self) -> bool {
+        let mut result = false;
+        for league in &mut self.leagues {
+            let start = league.cur_idx;
+            result = league.sim(&mut self.team_map, &mut self.player_map, &mut self.events, self.year, self.fast_sim, &mut self.rng) || result;
+            if self.fast_sim {
+                // fast games never had a play-by-play, this drops the stat map kept for the league's record checks
+                let end = league.cur_idx.min(league.schedule.games.len());
+                for game in &mut league.schedule.games[start.min(end)..end] {
+                    game.discard_playbyplay();
+                }
+            }
+            if let Some(days) = self.keep_log_days {
+                league.trim_playbyplay(days);
+            }
+        }
+        result
+    }
+
+    /// Plays the next day of every league's schedule. Once the schedules are done it runs the
+    /// playoffs and offseason instead and returns true, the year having rolled over.
+    pub fn sim_day(&mut self) -> bool {
+        if self.sim_games() {
+            return false;
+        }
+        let events = end_of_season(&mut self.leagues, &mut self.team_map, &mut self.player_map, PROMOTION_COUNT, self.year, &self.data, &mut self.rng);
+        self.events.extend(events);
+        self.career_records = career_records(&self.player_map);
+        self.year += 1;
+        true
+    }
+
+    /// Plays out the rest of the current season, offseason included.
+    pub fn sim_season(&mut self) {
+        while !self.sim_day() {}
+    }
+
+    // a one-off game between any two teams, played on copies so the standings and stats never see it
+    pub(crate) fn exhibition(&self, away: TeamId, home: TeamId, seed: u64) -> Game {
+        let mut teams = self.team_map.clone();
+        let mut players = self.player_map.clone();
+        let run_env = self.leagues.iter().find(|o| o.teams.contains(&home)).map_or(1.0, |o| o.run_env);
+        let mut game = Game::new(home, away);
+        game.sim(&mut teams, &mut players, self.year, run_env, None, seed);
+        game
+    }
+
+    /// Team ids in order, best first, or None for a league that doesn't exist.
+    pub fn standings(&self, league: usize) -> Option<Vec<TeamId>> {
+        self.leagues.get(league).map(|o| o.final_standings(&self.team_map))
+    }
+
+    /// The league's leaders in a stat, best first, or None for a league that doesn't exist.
+    pub fn leaders(&self, league: usize, stat: Stat, is_batter: bool, qualified_only: bool) -> Option<Vec<(PlayerId, u32)>> {
+        self.leagues.get(league).map(|o| o.leaders(stat, is_batter, qualified_only, &self.team_map, &self.player_map))
+    }
+
+    /// The player's stats for the season in progress.
+    pub fn player_stats(&self, player_id: PlayerId) -> Option<Stats> {
+        self.player_map.get(&player_id).map(|o| o.get_stats())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::engine::{Engine, GameConfig};
+    use crate::stat::Stat;
+
+    #[test]
+    fn test_sim_season() {
+        let config = GameConfig { leagues: 2, teams_per_league: 4, seed: Some(7), dh: false, ..GameConfig::default() };
+        let mut engine = Engine::with_config(&config);
+        let year = engine.year();
+        assert!(engine.leagues.iter().all(|o| !o.dh));
+
+        assert!(!engine.sim_day());
+        let batter = engine.team_map[&1].players[0];
+        assert!(engine.player_stats(batter).is_some());
+        assert!(engine.player_stats(0).is_none());
+
+        engine.sim_season();
+        assert_eq!(engine.year(), year + 1);
+        assert_eq!(engine.league_count(), 2);
+        for league in 0..engine.league_count() {
+            let standings = engine.standings(league).unwrap();
+            assert_eq!(standings.len(), 4);
+            assert!(standings.iter().all(|o| engine.team_map.contains_key(o)));
+        }
+        assert!(engine.standings(2).is_none());
+        assert!(engine.leaders(0, Stat::Bhr, true, false).is_some_and(|o| !o.is_empty()));
+    }
+
+    #[test]
+    fn test_seed() {
+        let config = GameConfig { leagues: 1, teams_per_league: 4, ..GameConfig::default() };
+        let random = Engine::with_config(&config);
+        let again = Engine::with_config(&GameConfig { seed: Some(random.seed()), ..config });
+        assert_eq!(again.seed(), random.seed());
+        let names = |engine: &Engine| engine.team_map.values().map(|o| o.name()).collect::<Vec<_>>();
+        assert_eq!(names(&again), names(&random));
+    }
+
+    #[test]
+    fn test_exhibition() {
+        let config = GameConfig { leagues: 2, teams_per_league: 4, seed: Some(7), ..GameConfig::default() };
+        let mut engine = Engine::with_config(&config);
+        engine.sim_day();
+
+        // teams from different leagues can meet, and nothing carries over into the season
+        let (away, home) = (engine.leagues[0].teams[0], engine.leagues[1].teams[0]);
+        let plate_appearances = |engine: &Engine| engine.team_map[&away].players.iter().map(|o| engine.player_stats(*o).unwrap().b_pa).collect::<Vec<_>>();
+        let before = plate_appearances(&engine);
+        let game = engine.exhibition(away, home, 11);
+        assert_ne!(game.home.r, game.away.r);
+        assert!(game.playbyplay.iter().any(|o| o.player == game.away.bo[0].player));
+        assert_eq!(plate_appearances(&engine), before);
+        assert_eq!(engine.team_map[&away].results.games(), 1);
+        assert_eq!(engine.team_map[&home].results.games(), 1);
+    }
+}
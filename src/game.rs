@@ -1,30 +1,17 @@
-use std::collections::HashMap;
+use std::collections::{HashMap, HashSet};
+use std::fmt::Write;
 
-use lazy_static::lazy_static;
 use rand::Rng;
-use rand::rngs::ThreadRng;
+use rand::SeedableRng;
+use rand::rngs::StdRng;
 use rand::seq::{IteratorRandom, SliceRandom};
 
-use crate::player::{Expect, ExpectMap, Handedness, Player, PlayerId, PlayerMap, Position};
-use crate::stat::Stat;
-use crate::team::{TeamId, TeamMap};
+use crate::player::{ErrorType, Expect, ExpectMap, Handedness, Player, PlayerId, PlayerMap, Position};
+use crate::sim::{league_avg_vs, matchup_morey_z};
+use crate::stat::{Stat, Stats};
+use crate::team::{Team, TeamId, TeamMap};
 use crate::util::gen_gamma;
 
-lazy_static! {
-    static ref LEAGUE_AVG: ExpectMap = {
-        let mut expect = HashMap::new();
-        expect.insert(Expect::Single, 0.1379988963);
-        expect.insert(Expect::Double, 0.045119492);
-        expect.insert(Expect::Triple, 0.004006693438);
-        expect.insert(Expect::HomeRun, 0.03522694576);
-        expect.insert(Expect::Walk, 0.08492014357);
-        expect.insert(Expect::HitByPitch, 0.01096355115);
-        expect.insert(Expect::Strikeout, 0.19);
-        expect.insert(Expect::Out, 0.4909664694);
-        expect
-    };
-}
-
 #[derive(PartialEq)]
 pub(crate) enum PaResult {
     Single,
@@ -36,7 +23,7 @@ pub(crate) enum PaResult {
     HitByPitch,
     Strikeout,
     Out,
-    Error,
+    Error(ErrorType),
 }
 
 impl PaResult {
@@ -64,7 +51,7 @@ impl PaResult {
             Self::HitByPitch => Stat::Bhbp,
             Self::Strikeout => Stat::Bso,
             Self::Out => if outs == 1 { Stat::Bo } else { Stat::Bgidp }
-            Self::Error => Stat::Bo,
+            Self::Error(_) => Stat::Bo,
         }
     }
     pub(crate) fn to_pitching_stat(&self) -> Option<Stat> {
@@ -78,7 +65,7 @@ impl PaResult {
             Self::HitByPitch => Some(Stat::Phbp),
             Self::Strikeout => Some(Stat::Pso),
             Self::Out => Some(Stat::Po),
-            Self::Error => None,
+            Self::Error(_) => None,
         }
     }
 }
@@ -89,6 +76,15 @@ struct RunnerInfo {
     id: PlayerId,
     pitcher: PlayerId,
     earned: bool,
+    inherited: Option<PlayerId>,
+}
+
+impl RunnerInfo {
+    // a run is only earned if the runner reached cleanly and it scored before the
+    // inning would have ended had the defense not committed an error
+    fn is_earned(&self, virtual_outs: u8) -> bool {
+        self.earned && virtual_outs < 3
+    }
 }
 
 #[derive(Clone, Default)]
@@ -97,29 +93,71 @@ pub(crate) struct DefenseInfo {
     pub(crate) pos: Position,
 }
 
+#[derive(Copy, Clone, Default)]
+pub(crate) struct PitchingLine {
+    pub(crate) h: u8,
+    pub(crate) r: u8,
+    pub(crate) er: u8,
+    pub(crate) bb: u8,
+    pub(crate) so: u8,
+    pub(crate) hr: u8,
+}
+
+impl PitchingLine {
+    fn record(&mut self, result: &PaResult) {
+        match result {
+            PaResult::Single | PaResult::Double | PaResult::Triple => self.h += 1,
+            PaResult::HomeRun => {
+                self.h += 1;
+                self.hr += 1;
+            }
+            PaResult::Walk | PaResult::IntentionalWalk => self.bb += 1,
+            PaResult::Strikeout => self.so += 1,
+            _ => {}
+        }
+    }
+
+    fn record_run(&mut self, earned: bool) {
+        self.r += 1;
+        if earned {
+            self.er += 1;
+        }
+    }
+}
+
 #[derive(Clone, Default)]
 pub(crate) struct PitcherRecord {
     pub(crate) pitcher: PlayerId,
-    outs: u8,
+    pub(crate) pitches: u32,
+    pub(crate) bf: u32,
+    pub(crate) line: PitchingLine,
+    pub(crate) outs: u8,
     save_situation: bool,
     run_diff_out: i8,
 }
 
-#[derive(Default)]
+#[derive(Clone, Default)]
 pub(crate) struct Scoreboard {
     pub(crate) id: TeamId,
     pub(crate) r: u8,
     pub(crate) h: u8,
     pub(crate) e: u8,
+    pub(crate) lob: u8,
     onbase: [Option<RunnerInfo>; 4],
     runs_in: Vec<RunnerInfo>,
     pub(crate) bo: [DefenseInfo; 9],
     ab: usize,
     pitcher: PlayerId,
     pitches: u32,
+    pitcher_bf: u32,
+    pitcher_line: PitchingLine,
     pitcher_outs: u8,
     pitcher_save_sit: bool,
     pub(crate) pitcher_record: Vec<PitcherRecord>,
+    // (replaced, replacement) for everyone taken out of the lineup, the replaced player is done for the game
+    pub(crate) subs: Vec<(PlayerId, PlayerId)>,
+    // everyone who has appeared, a player taken out can't come back in
+    used: HashSet<PlayerId>,
 }
 
 impl Scoreboard {
@@ -147,24 +185,99 @@ impl Scoreboard {
 
 
     fn advance_batter(&mut self, batter: PlayerId, pitcher: PlayerId, earned: bool, amt: usize) {
-        self.onbase[0] = Some(RunnerInfo { id: batter, pitcher, earned });
+        self.onbase[0] = Some(RunnerInfo { id: batter, pitcher, earned, inherited: None });
         for idx in 0..amt {
             self.advance_onbase(idx);
         }
     }
 
+    // bases a runner takes on a single by where it lands, infield singles only move runners who are forced
+    fn single_bases(target: Position, base: usize) -> usize {
+        if !target.is_outfield() {
+            return 0;
+        }
+        match base {
+            // the short throw from left holds a runner from second at third
+            2 if target == Position::LeftField => 1,
+            2 => 2,
+            // the long throw from right lets a runner from first go to third
+            1 if target == Position::RightField => 2,
+            _ => 1,
+        }
+    }
+
+    // lead runners move first, a runner stops short of a base still held, then the batter forces along anyone left.
+    // on a ball to the outfield the lead runner held to a single base can try for the next if it's open, once a play,
+    // returning the runner and the base they were thrown out going for
+    fn advance_on_single(&mut self, target: Position, batter: PlayerId, pitcher: PlayerId, earned: bool, mut try_extra: impl FnMut(PlayerId) -> Option<bool>) -> Option<(PlayerId, usize)> {
+        let mut thrown_out = None;
+        let mut tried = false;
+        for base in (1..=3).rev() {
+            if let Some(runner) = self.onbase[base] {
+                let mut dest = base + Self::single_bases(target, base);
+                while dest > base && dest <= 3 && self.onbase[dest].is_some() {
+                    dest -= 1;
+                }
+                if !tried && target.is_outfield() && base < 3 && dest == base + 1 && (dest == 3 || self.onbase[dest + 1].is_none()) {
+                    tried = true;
+                    match try_extra(runner.id) {
+                        Some(true) => dest += 1,
+                        Some(false) => {
+                            thrown_out = Some((runner.id, dest + 1));
+                            self.onbase[base] = None;
+                            continue;
+                        }
+                        None => {}
+                    }
+                }
+                if dest > 3 {
+                    self.runs_in.push(runner);
+                    self.onbase[base] = None;
+                } else if dest > base {
+                    self.onbase[dest] = Some(runner);
+                    self.onbase[base] = None;
+                }
+            }
+        }
+        self.advance_batter(batter, pitcher, earned, 1);
+        thrown_out
+    }
+
     fn player_at_pos(&self, pos: Position) -> PlayerId {
         if pos.is_pitcher() { self.pitcher } else { self.bo.iter().find(|o| o.pos == pos).unwrap().player }
     }
 
+    // occupied bases as bits, first base the lowest
+    fn base_state(&self) -> usize {
+        (1..=3).filter(|o| self.onbase[*o].is_some()).map(|o| 1 << (o - 1)).sum()
+    }
+
+    // runners still aboard when the third out is made
+    fn strand_runners(&mut self) {
+        self.lob += self.onbase[1..].iter().filter(|o| o.is_some()).count() as u8;
+        self.onbase.fill(None);
+    }
+
     fn record_runs(&mut self) {
         self.r += self.runs_in.len() as u8;
         self.runs_in.clear();
     }
 
+    // runs can be charged to a pitcher who has already left the game
+    fn pitching_line(&mut self, pitcher: PlayerId) -> &mut PitchingLine {
+        if pitcher == self.pitcher {
+            &mut self.pitcher_line
+        } else {
+            &mut self.pitcher_record.iter_mut().find(|o| o.pitcher == pitcher).unwrap().line
+        }
+    }
+
     fn record_pitcher(&mut self, other_r: i8) {
         self.pitcher_record.push(PitcherRecord {
             pitcher: self.pitcher,
+            pitches: self.pitches,
+            bf: self.pitcher_bf,
+            line: self.pitcher_line,
             outs: self.pitcher_outs,
             save_situation: self.pitcher_save_sit,
             run_diff_out: self.r as i8 - other_r,
@@ -172,16 +285,22 @@ impl Scoreboard {
     }
 }
 
-#[derive(PartialEq)]
+#[derive(PartialEq, Default)]
 enum InningHalf {
+    #[default]
     Top,
     Middle,
     Bottom,
     End,
 }
 
-impl Default for InningHalf {
-    fn default() -> Self { Self::Top }
+// how much a plate appearance can swing the game, from the inning, the score and the runners on
+#[derive(Copy, Clone, PartialEq, Default)]
+pub(crate) enum Leverage {
+    #[default]
+    Low,
+    Medium,
+    High,
 }
 
 #[derive(Default)]
@@ -190,19 +309,286 @@ struct Inning {
     half: InningHalf,
 }
 
+#[derive(Clone, PartialEq)]
 pub(crate) struct GameLogEvent {
     pub(crate) player: PlayerId,
     pub(crate) event: Stat,
     pub(crate) target: Option<Position>,
+    // plate appearance that started with a runner on second or third
+    pub(crate) risp: bool,
+    pub(crate) leverage: Leverage,
 }
 
 pub(crate) type GameLog = Vec<GameLogEvent>;
 
+// a log event along with the game state as it stood when the event happened
+pub(crate) struct AnnotatedEvent<'a> {
+    pub(crate) event: &'a GameLogEvent,
+    pub(crate) inning: usize,
+    pub(crate) tophalf: bool,
+    pub(crate) outs: u8,
+    pub(crate) away_r: u8,
+    pub(crate) home_r: u8,
+    pub(crate) error: Option<ErrorType>,
+}
+
+// everything a box score shows that has to be pieced together from the play-by-play
+#[derive(Clone, Default)]
+pub(crate) struct BoxScore {
+    pub(crate) away_runs: Vec<u8>,
+    pub(crate) home_runs: Vec<u8>,
+    pub(crate) away_hits: Vec<u8>,
+    pub(crate) home_hits: Vec<u8>,
+    // charged to the side in the field
+    pub(crate) away_errors: Vec<u8>,
+    pub(crate) home_errors: Vec<u8>,
+    pub(crate) stat_map: HashMap<PlayerId, Vec<Stat>>,
+    pub(crate) winner: Option<PlayerId>,
+    pub(crate) loser: Option<PlayerId>,
+    pub(crate) save: Option<PlayerId>,
+}
+
+impl BoxScore {
+    // runs, hits and errors for one side, totalled from its innings
+    pub(crate) fn rhe(&self, home: bool) -> [u32; 3] {
+        let total = |line: &Vec<u8>| line.iter().map(|o| *o as u32).sum::<u32>();
+        if home {
+            [total(&self.home_runs), total(&self.home_hits), total(&self.home_errors)]
+        } else {
+            [total(&self.away_runs), total(&self.away_hits), total(&self.away_errors)]
+        }
+    }
+
+    fn tally(&mut self, event: &GameLogEvent, inning: usize, tophalf: bool) {
+        self.stat_map.entry(event.player).or_default().push(event.event);
+
+        match event.event {
+            Stat::Pw => self.winner = Some(event.player),
+            Stat::Pl => self.loser = Some(event.player),
+            Stat::Psv => self.save = Some(event.player),
+            _ => {}
+        };
+
+        // only batting events reliably belong to the half inning being played
+        if event.event.is_batting() {
+            let runs = if tophalf { &mut self.away_runs } else { &mut self.home_runs };
+            if runs.len() < inning {
+                runs.push(0);
+            }
+            if event.event == Stat::Br {
+                runs[inning - 1] += 1;
+            }
+            if matches!(event.event, Stat::B1b | Stat::B2b | Stat::B3b | Stat::Bhr) {
+                let hits = if tophalf { &mut self.away_hits } else { &mut self.home_hits };
+                *inning_tally(hits, inning) += 1;
+            }
+        }
+        if matches!(event.event, Stat::Fe | Stat::Fte) {
+            let errors = if tophalf { &mut self.home_errors } else { &mut self.away_errors };
+            *inning_tally(errors, inning) += 1;
+        }
+    }
+}
+
+// fast sim: events go straight into the line score and each player's streams, no play-by-play is kept
 #[derive(Default)]
+struct FastTally {
+    summary: BoxScore,
+    risp: HashMap<PlayerId, Vec<Stat>>,
+    clutch: HashMap<PlayerId, Vec<Stat>>,
+    // the half inning being played
+    inning: usize,
+    tophalf: bool,
+}
+
+// where a game's events go as it is played
+enum EventSink {
+    Log(GameLog),
+    Fast(Box<FastTally>),
+}
+
+impl EventSink {
+    fn new(fast: bool) -> Self {
+        if fast {
+            EventSink::Fast(Box::default())
+        } else {
+            EventSink::Log(GameLog::new())
+        }
+    }
+
+    fn push(&mut self, event: GameLogEvent) {
+        match self {
+            EventSink::Log(log) => log.push(event),
+            EventSink::Fast(tally) => {
+                tally.summary.tally(&event, tally.inning, tally.tophalf);
+                if event.risp {
+                    tally.risp.entry(event.player).or_default().push(event.event);
+                }
+                if event.leverage == Leverage::High {
+                    tally.clutch.entry(event.player).or_default().push(event.event);
+                }
+            }
+        }
+    }
+
+    fn set_inning(&mut self, inning: &Inning) {
+        if let EventSink::Fast(tally) = self {
+            tally.inning = inning.number as usize;
+            tally.tophalf = inning.half == InningHalf::Top;
+        }
+    }
+
+    #[cfg(test)]
+    fn log(&self) -> &GameLog {
+        match self {
+            EventSink::Log(log) => log,
+            EventSink::Fast(_) => panic!("fast sim keeps no log"),
+        }
+    }
+}
+
+// runs scored from each base-out state through the end of the inning, over every half inning played to three outs
+#[derive(Clone, Default)]
+pub(crate) struct RunExpectancy {
+    // indexed by occupied bases, first base the lowest bit, then outs
+    runs: [[u32; 3]; 8],
+    chances: [[u32; 3]; 8],
+}
+
+impl RunExpectancy {
+    fn record(&mut self, bases: usize, outs: u8, runs: u8) {
+        self.runs[bases][outs as usize] += runs as u32;
+        self.chances[bases][outs as usize] += 1;
+    }
+
+    pub(crate) fn add(&mut self, other: &RunExpectancy) {
+        for bases in 0..8 {
+            for outs in 0..3 {
+                self.runs[bases][outs] += other.runs[bases][outs];
+                self.chances[bases][outs] += other.chances[bases][outs];
+            }
+        }
+    }
+
+    pub(crate) fn chances(&self, bases: usize, outs: usize) -> u32 {
+        self.chances[bases][outs]
+    }
+
+    // None for a state that never came up
+    pub(crate) fn expected(&self, bases: usize, outs: usize) -> Option<f64> {
+        let chances = self.chances[bases][outs];
+        (chances > 0).then(|| self.runs[bases][outs] as f64 / chances as f64)
+    }
+}
+
+// the count for an inning, filling in any innings before it with zeroes
+fn inning_tally(line: &mut Vec<u8>, inning: usize) -> &mut u8 {
+    if line.len() < inning {
+        line.resize(inning, 0);
+    }
+    &mut line[inning - 1]
+}
+
+// hits climb each time a pitcher works through the lineup, the last entry covers every later trip
+const TIMES_THROUGH_PENALTY: [f64; 3] = [1.0, 1.04, 1.12];
+// how much lower a reliever's on-base allowed against the batter's side must be to bring him in
+const SPECIALIST_EDGE: f64 = 0.02;
+// how much higher a bench bat's on-base against the pitcher's hand must be to send him up
+const PINCH_HIT_EDGE: f64 = 0.03;
+// how much less often a bench glove has to boot the ball to come in for defense
+const DEFENSIVE_SUB_EDGE: f64 = 0.01;
+// walk rate change per point of patience above the typical batter's, scaled up against wilder pitchers
+const DISCIPLINE_EFFECT: f64 = 0.04;
+const TYPICAL_PATIENCE: f64 = 4.5;
+const TYPICAL_CONTROL: f64 = 4.0;
+// share of a pitcher's limit thrown at full strength, every pitch past it makes each way on base more likely
+const FATIGUE_START: f64 = 0.8;
+const FATIGUE_PER_PITCH: f64 = 0.01;
+const MAX_FATIGUE: f64 = 1.5;
+
+pub(crate) const BOXSCORE_BATTING: [Stat; 6] = [
+    Stat::Bab,
+    Stat::Br,
+    Stat::Bh,
+    Stat::Brbi,
+    Stat::Bbb,
+    Stat::Bso,
+];
+
+pub(crate) const BOXSCORE_PITCHING: [Stat; 7] = [
+    Stat::Po,
+    Stat::Ph,
+    Stat::Pr,
+    Stat::Per,
+    Stat::Pbb,
+    Stat::Pso,
+    Stat::Phr,
+];
+
+impl PitcherRecord {
+    pub(crate) fn boxscore_values(&self) -> [u8; 7] {
+        let line = &self.line;
+        [self.outs, line.h, line.r, line.er, line.bb, line.so, line.hr]
+    }
+}
+
+fn batting_text(out: &mut String, scoreboard: &Scoreboard, team: &Team, players: &PlayerMap, stat_map: &HashMap<PlayerId, Vec<Stat>>) {
+    let _ = write!(out, "{:28}", format!("{} {} Batters", team.abbr(), team.nickname()));
+    for header in BOXSCORE_BATTING.iter() {
+        let _ = write!(out, "{:>5}", header.to_string());
+    }
+    let _ = writeln!(out, "{:>6}{:>6}", Stat::Bavg.to_string(), "OPS");
+
+    for (idx, def) in scoreboard.bo.iter().enumerate() {
+        let batter = players.get(&def.player).unwrap();
+        let stats = Stats::compile_stats(stat_map.get(&def.player).unwrap_or(&Vec::new()));
+        let full_stats = batter.get_stats();
+
+        let _ = write!(out, "{:28}", format!("{}. {} {}", idx + 1, batter.fname(), def.pos));
+        for header in BOXSCORE_BATTING.iter() {
+            let _ = write!(out, "{:>5}", header.value(stats.get_stat(*header)));
+        }
+        let ops = full_stats.b_obp + full_stats.b_slg;
+        let _ = writeln!(out, "{:>6}{:>6}", Stat::Bavg.value(full_stats.get_stat(Stat::Bavg)), Stat::Bobp.value(ops));
+    }
+}
+
+fn pitching_text(out: &mut String, scoreboard: &Scoreboard, team: &Team, players: &PlayerMap) {
+    let _ = write!(out, "{:28}", format!("{} {} Pitchers", team.abbr(), team.nickname()));
+    for header in BOXSCORE_PITCHING.iter() {
+        let _ = write!(out, "{:>5}", header.to_string());
+    }
+    let _ = writeln!(out, "{:>5}{:>5}{:>7}", "P", "BF", Stat::Pera.to_string());
+
+    for rec in scoreboard.pitcher_record.iter() {
+        let pitcher = players.get(&rec.pitcher).unwrap();
+        let _ = write!(out, "{:28}", pitcher.fname());
+        for (header, value) in BOXSCORE_PITCHING.iter().zip(rec.boxscore_values()) {
+            let _ = write!(out, "{:>5}", header.value(value as u32));
+        }
+        let _ = writeln!(out, "{:>5}{:>5}{:>7}", rec.pitches, rec.bf, Stat::Pera.value(pitcher.get_stats().p_era));
+    }
+}
+
+#[derive(Clone, Default)]
 pub(crate) struct Game {
     pub(crate) home: Scoreboard,
     pub(crate) away: Scoreboard,
     pub(crate) playbyplay: GameLog,
+    pub(crate) walkoff: bool,
+    // kept out of the regular season records and stats
+    pub(crate) postseason: bool,
+    // regular season only: stats go straight to the players and only the line score is kept, see EventSink
+    pub(crate) fast: bool,
+    // everything random in the game is drawn from this, so it can be replayed
+    pub(crate) seed: u64,
+    // (lead, inning): a lead of at least that many runs once that inning is complete ends the game
+    pub(crate) mercy_rule: Option<(u8, u8)>,
+    // exhibition only: still level once this inning is complete, the game ends in a tie
+    pub(crate) max_innings: Option<u8>,
+    // the line score and decisions left after the play-by-play is thrown away
+    summary: Option<BoxScore>,
+    pub(crate) run_expectancy: RunExpectancy,
 }
 
 
@@ -212,28 +598,258 @@ impl Game {
             home: Scoreboard::new(home),
             away: Scoreboard::new(away),
             playbyplay: Vec::new(),
+            walkoff: false,
+            postseason: false,
+            fast: false,
+            seed: 0,
+            mercy_rule: None,
+            max_innings: None,
+            summary: None,
+            run_expectancy: RunExpectancy::default(),
+        }
+    }
+
+    // keeps what the schedule and standings pages need, the player stats are already recorded
+    pub(crate) fn discard_playbyplay(&mut self) {
+        let mut summary = self.summary.take().unwrap_or_else(|| self.boxscore());
+        // clearing would keep the map's allocation
+        summary.stat_map = HashMap::new();
+        self.summary = Some(summary);
+        self.playbyplay = GameLog::new();
+    }
+
+    pub(crate) fn has_playbyplay(&self) -> bool {
+        self.summary.is_none()
+    }
+
+    // what the log and summary hold on the heap, for measuring what fast sim saves
+    #[cfg(test)]
+    pub(crate) fn heap_bytes(&self) -> usize {
+        let log = self.playbyplay.capacity() * std::mem::size_of::<GameLogEvent>();
+        let summary = self.summary.as_ref().map_or(0, |o| {
+            let lines = [&o.away_runs, &o.home_runs, &o.away_hits, &o.home_hits, &o.away_errors, &o.home_errors];
+            lines.iter().map(|o| o.capacity()).sum::<usize>() + o.stat_map.capacity() * std::mem::size_of::<(PlayerId, Vec<Stat>)>()
+        });
+        log + summary
+    }
+
+    // re-sims the game from copies of the teams and players as they were before it was played
+    pub(crate) fn replay(&self, teams: &TeamMap, players: &PlayerMap, year: u32, run_env: f64) -> Game {
+        let mut teams = teams.clone();
+        let mut players = players.clone();
+        let mut game = Game::new(self.home.id, self.away.id);
+        game.postseason = self.postseason;
+        game.max_innings = self.max_innings;
+        game.sim(&mut teams, &mut players, year, run_env, self.mercy_rule, self.seed);
+        game
+    }
+
+    // the player a substitute came in for
+    pub(crate) fn replaced(&self, sub: PlayerId) -> Option<PlayerId> {
+        self.away.subs.iter().chain(self.home.subs.iter()).find(|o| o.1 == sub).map(|o| o.0)
+    }
+
+    pub(crate) fn events(&self) -> impl Iterator<Item = AnnotatedEvent<'_>> {
+        let mut inning = 1;
+        let mut tophalf = true;
+        let mut outs = 0;
+        let mut away_r = 0;
+        let mut home_r = 0;
+        let mut error = None;
+
+        self.playbyplay.iter().map(move |event| {
+            let annotated = AnnotatedEvent { event, inning, tophalf, outs, away_r, home_r, error };
+
+            if event.event == Stat::Fe {
+                error = Some(ErrorType::Fielding);
+            }
+            if event.event == Stat::Fte {
+                error = Some(ErrorType::Throwing);
+            }
+
+            if event.event == Stat::Br {
+                if tophalf { away_r += 1 } else { home_r += 1 }
+            }
+
+            if event.event == Stat::Bgidp {
+                outs += 1;  // add the second out below
+            }
+
+            if matches!(event.event, Stat::Bo | Stat::Bso | Stat::Bgidp | Stat::Bcs | Stat::Boob) {
+                if error.is_none() {
+                    outs += 1;
+                }
+                error = None;
+                if outs == 3 {
+                    if !tophalf {
+                        inning += 1;
+                    }
+                    tophalf = !tophalf;
+                    outs = 0;
+                }
+            }
+
+            annotated
+        })
+    }
+
+    pub(crate) fn boxscore(&self) -> BoxScore {
+        if let Some(summary) = &self.summary {
+            return summary.clone();
+        }
+        let mut boxscore = BoxScore::default();
+        for annotated in self.events() {
+            boxscore.tally(annotated.event, annotated.inning, annotated.tophalf);
         }
+        self.check_line(&boxscore);
+
+        boxscore
+    }
+
+    // the events are the source of truth, a mismatch with the scoreboard means one was recorded wrong
+    fn check_line(&self, boxscore: &BoxScore) {
+        debug_assert_eq!(boxscore.rhe(false), [self.away.r as u32, self.away.h as u32, self.away.e as u32]);
+        debug_assert_eq!(boxscore.rhe(true), [self.home.r as u32, self.home.h as u32, self.home.e as u32]);
+    }
+
+    pub(crate) fn to_boxscore_text(&self, teams: &TeamMap, players: &PlayerMap) -> String {
+        let boxscore = self.boxscore();
+        let awayteam = teams.get(&self.away.id).unwrap();
+        let hometeam = teams.get(&self.home.id).unwrap();
+        let mut out = String::new();
+
+        let innings = boxscore.away_runs.len().max(boxscore.home_runs.len());
+        let _ = write!(out, "{:4}", "");
+        for inning in 1..=innings {
+            let _ = write!(out, "{:>3}", inning);
+        }
+        let _ = writeln!(out, "{:>4}{:>4}{:>4}{:>4}", "R", "H", "E", "LOB");
+
+        for (team, runs, scoreboard, home) in [(awayteam, &boxscore.away_runs, &self.away, false), (hometeam, &boxscore.home_runs, &self.home, true)] {
+            let _ = write!(out, "{:4}", team.abbr());
+            for run in runs.iter() {
+                let _ = write!(out, "{:>3}", run);
+            }
+            if runs.len() < innings {
+                let _ = write!(out, "{:>3}", "X");
+            }
+            let [r, h, e] = boxscore.rhe(home);
+            let _ = writeln!(out, "{:>4}{:>4}{:>4}{:>4}", r, h, e, scoreboard.lob);
+        }
+
+        let decisions = [("W", boxscore.winner), ("L", boxscore.loser), ("SV", boxscore.save)];
+        let decisions = decisions.iter()
+            .filter_map(|(label, player)| Some(format!("{}: {}", label, players.get(&(*player)?)?.fname())))
+            .collect::<Vec<_>>();
+        if !decisions.is_empty() {
+            let _ = writeln!(out, "{}", decisions.join("  "));
+        }
+        if self.walkoff {
+            let _ = writeln!(out, "Walk-off");
+        }
+        let _ = writeln!(out, "{}", self.recap(teams, players));
+
+        for (scoreboard, team) in [(&self.away, awayteam), (&self.home, hometeam)] {
+            let _ = writeln!(out);
+            batting_text(&mut out, scoreboard, team, players, &boxscore.stat_map);
+        }
+        for (scoreboard, team) in [(&self.away, awayteam), (&self.home, hometeam)] {
+            let _ = writeln!(out);
+            pitching_text(&mut out, scoreboard, team, players);
+        }
+
+        out
+    }
+
+    pub(crate) fn recap(&self, teams: &TeamMap, players: &PlayerMap) -> String {
+        let boxscore = self.boxscore();
+        let awayteam = teams.get(&self.away.id).unwrap();
+        let hometeam = teams.get(&self.home.id).unwrap();
+        let (winner, loser) = if self.home.r > self.away.r { ((hometeam, &self.home), (awayteam, &self.away)) } else { ((awayteam, &self.away), (hometeam, &self.home)) };
+        let mut sentences = Vec::new();
+
+        if self.is_tie() {
+            sentences.push(format!("The {} {} and the {} {} played to a {}-{} tie.",
+                                   awayteam.loc.city, awayteam.nickname(), hometeam.loc.city, hometeam.nickname(), self.away.r, self.home.r));
+        } else {
+            sentences.push(format!("The {} {} {} the {} {}, {}-{}.",
+                                   winner.0.loc.city, winner.0.nickname(), if self.walkoff { "walked off" } else { "beat" },
+                                   loser.0.loc.city, loser.0.nickname(), winner.1.r, loser.1.r));
+        }
+
+        let name = |player: Option<PlayerId>| player.and_then(|o| players.get(&o)).map(|o| o.fname());
+        if let (Some(w), Some(l)) = (name(boxscore.winner), name(boxscore.loser)) {
+            sentences.push(format!("{} got the win and {} took the loss.", w, l));
+        }
+        if let Some(sv) = name(boxscore.save) {
+            sentences.push(format!("{} earned the save.", sv));
+        }
+
+        for def in self.away.bo.iter().chain(self.home.bo.iter()) {
+            let stats = Stats::compile_stats(boxscore.stat_map.get(&def.player).unwrap_or(&Vec::new()));
+            let batter = players.get(&def.player).unwrap().fname();
+            if stats.b_hr > 1 {
+                sentences.push(format!("{} hit {} home runs.", batter, stats.b_hr));
+            }
+            if stats.b_h > 2 {
+                sentences.push(format!("{} had {} hits.", batter, stats.b_h));
+            }
+        }
+
+        // a tie in between still counts as one lead change
+        let mut lead_changes = 0;
+        let mut leader = None;
+        for annotated in self.events().filter(|o| o.event.event == Stat::Br) {
+            let (away_r, home_r) = if annotated.tophalf { (annotated.away_r + 1, annotated.home_r) } else { (annotated.away_r, annotated.home_r + 1) };
+            let cur = if away_r > home_r { Some(true) } else if home_r > away_r { Some(false) } else { None };
+            if cur.is_some() && cur != leader {
+                if leader.is_some() {
+                    lead_changes += 1;
+                }
+                leader = cur;
+            }
+        }
+        if lead_changes > 0 {
+            sentences.push(format!("The lead changed hands {} time{}.", lead_changes, if lead_changes == 1 { "" } else { "s" }));
+        }
+
+        sentences.join(" ")
+    }
+
+    pub(crate) fn is_tie(&self) -> bool {
+        self.home.r == self.away.r
     }
 
     fn is_complete(&self, inning: &Inning) -> bool {
-        inning.number >= 9 && ((inning.half != InningHalf::Top && self.home.r > self.away.r) || (inning.half == InningHalf::End && self.away.r > self.home.r))
+        let capped = self.max_innings.is_some_and(|max| inning.number >= max && inning.half == InningHalf::End && self.is_tie());
+        capped || self.is_mercy(inning) || (inning.number >= 9 && ((inning.half != InningHalf::Top && self.home.r > self.away.r) || (inning.half == InningHalf::End && self.away.r > self.home.r)))
     }
 
-    fn is_away_ab(&self, inning: &Inning) -> bool {
-        inning.half == InningHalf::Top || inning.half == InningHalf::Middle
+    // the home team can end it mid-inning, the away team only once the home team has batted
+    fn is_mercy(&self, inning: &Inning) -> bool {
+        self.mercy_rule.is_some_and(|(lead, after)| {
+            let (home, away, lead) = (self.home.r as i16, self.away.r as i16, lead as i16);
+            inning.number >= after && match inning.half {
+                InningHalf::Top => false,
+                InningHalf::Middle | InningHalf::Bottom => home - away >= lead,
+                InningHalf::End => (home - away).abs() >= lead,
+            }
+        })
     }
 
-    fn matchup_morey_z(batter: f64, pitcher: f64, league: f64) -> f64 {
-        let sqrt_league = (league * (1.0 - league)).sqrt();
-        let top_left = (batter - league) / sqrt_league;
-        let top_right = (pitcher - league) / sqrt_league;
-        let left = (top_left + top_right) / 2.0f64.sqrt();
-        (left * sqrt_league) + league
+    // the home team took the lead in its final at-bat, ending the game
+    fn is_walkoff(&self, inning: &Inning, score_diff_pre: i8) -> bool {
+        inning.half == InningHalf::Bottom && score_diff_pre >= 0 && self.is_complete(inning)
     }
 
-    fn setup_pitcher(players: &mut PlayerMap, teams: &mut TeamMap, scoreboard: &mut Scoreboard, boxscore: &mut GameLog) -> Handedness {
+    fn is_away_ab(&self, inning: &Inning) -> bool {
+        inning.half == InningHalf::Top || inning.half == InningHalf::Middle
+    }
+
+    fn setup_pitcher(players: &mut PlayerMap, teams: &mut TeamMap, scoreboard: &mut Scoreboard, boxscore: &mut EventSink) -> Handedness {
         let team = teams.get_mut(&scoreboard.id).unwrap();
         scoreboard.pitcher = team.rotation[0];
+        scoreboard.used.insert(scoreboard.pitcher);
         Self::record_stat(boxscore, team.rotation[0], Stat::Gs, None);
         team.rotation.rotate_left(1);
 
@@ -241,34 +857,36 @@ impl Game {
         pitcher.throws
     }
 
-    fn setup_bo(players: &mut PlayerMap, teams: &mut TeamMap, scoreboard: &mut Scoreboard, boxscore: &mut GameLog, year: u32, rng: &mut ThreadRng) {
+    fn setup_bo(players: &mut PlayerMap, teams: &mut TeamMap, scoreboard: &mut Scoreboard, boxscore: &mut EventSink, year: u32, rng: &mut StdRng) {
         let team = teams.get_mut(&scoreboard.id).unwrap();
-        let mut team_players = team.players.iter().map(|o| (*o, players.get(o).unwrap())).filter(|o| !o.1.pos.is_pitcher()).collect::<Vec<_>>();
-        team_players.sort_by_cached_key(|o| o.1.get_stats().b_obp);
-        team_players.reverse();
-
-        let mut index = 0;
-        for (id, player) in &team_players {
-            if !scoreboard.bo.iter().any(|o| o.pos == player.pos) {
-                scoreboard.bo[index] = DefenseInfo {
-                    player: *id,
-                    pos: player.pos,
-                };
-                index += 1;
-            }
+        let team_players = team.iter_players(players).filter(|o| !o.1.pos.is_pitcher()).collect::<Vec<_>>();
+
+        let lineup = team.lineup.filter(|o| team.is_valid_lineup(o, players)).unwrap_or_else(|| team.auto_lineup(players));
+        let positions = Team::lineup_positions(&lineup, players).unwrap_or_else(|| Team::emergency_positions(&lineup, players));
+        for (idx, id) in lineup.iter().enumerate() {
+            scoreboard.bo[idx] = DefenseInfo {
+                player: *id,
+                pos: positions[idx],
+            };
         }
 
-        for starter in scoreboard.bo.iter_mut() {
-            if let Some(replacement) = team_players.iter().find(|o| o.0 != starter.player && o.1.pos == starter.pos) {
+        // a tired starter sits for the best bat at the same position, or failing that someone who can cover it
+        for idx in 0..scoreboard.bo.len() {
+            let starter = scoreboard.bo[idx].clone();
+            let in_lineup = scoreboard.bo.iter().map(|o| o.player).collect::<Vec<_>>();
+            let replacement = team.best_at(starter.pos, players, &in_lineup, |o| o.get_stats().b_obp)
+                .or_else(|| team_players.iter().find(|o| !in_lineup.contains(&o.0) && o.1.can_play(starter.pos)).map(|o| o.0));
+            if let Some(replacement) = replacement {
                 let starter_player = players.get(&starter.player).unwrap();
                 let fat_pct = starter_player.fatigue as f64 / starter_player.fatigue_threshold(year);
                 if rng.gen_bool(fat_pct.min(1.0)) {
-                    starter.player = replacement.0;
+                    scoreboard.bo[idx].player = replacement;
                 }
             }
         }
 
         for starter in scoreboard.bo.iter() {
+            scoreboard.used.insert(starter.player);
             Self::record_stat(boxscore, starter.player, Stat::Gs, None);
 
             let player = players.get_mut(&starter.player).unwrap();
@@ -276,7 +894,7 @@ impl Game {
         }
     }
 
-    fn setup_game(&mut self, players: &mut PlayerMap, teams: &mut TeamMap, boxscore: &mut GameLog, year: u32, rng: &mut ThreadRng) {
+    fn setup_game(&mut self, players: &mut PlayerMap, teams: &mut TeamMap, boxscore: &mut EventSink, year: u32, rng: &mut StdRng) {
         let _home_hand = Self::setup_pitcher(players, teams, &mut self.home, boxscore);
         let _away_hand = Self::setup_pitcher(players, teams, &mut self.away, boxscore);
 
@@ -284,26 +902,96 @@ impl Game {
         Self::setup_bo(players, teams, &mut self.away, boxscore, year, rng);
     }
 
-    fn expected_pa(batter: &HashMap<Expect, f64>, pitcher: &HashMap<Expect, f64>, rng: &mut ThreadRng) -> Expect {
-        *batter.iter().map(|kv| {
+    fn times_through(pitcher_bf: u32) -> usize {
+        pitcher_bf as usize / 9 + 1
+    }
+
+    // run_env scales every way of reaching base against the outs, a tiring pitcher gives up more hits
+    // both sides are measured against the league average for the pitcher's hand
+    fn pa_weights<'a>(batter: &'a ExpectMap, pitcher: &ExpectMap, league: &ExpectMap, run_env: f64, times_through: usize) -> Vec<(&'a Expect, u32)> {
+        let penalty = TIMES_THROUGH_PENALTY[times_through.clamp(1, TIMES_THROUGH_PENALTY.len()) - 1];
+        batter.iter().map(|kv| {
             let bval = kv.1;
             let pval = pitcher.get(kv.0).unwrap_or(&0.0);
-            let lval = LEAGUE_AVG.get(kv.0).unwrap_or(&0.0);
-            let res = (Self::matchup_morey_z(*bval, *pval, *lval) * 1000.0) as u32;
+            let lval = league.get(kv.0).unwrap_or(&0.0);
+            let scale = match kv.0 {
+                Expect::Strikeout | Expect::Out => 1.0,
+                Expect::Single | Expect::Double | Expect::Triple | Expect::HomeRun => run_env * penalty,
+                _ => run_env,
+            };
+            let res = (matchup_morey_z(*bval, *pval, *lval) * scale * 1000.0) as u32;
             (kv.0, res)
-        }).collect::<Vec<_>>().choose_weighted(rng, |o| o.1).unwrap().0
+        }).collect()
+    }
+
+    // a patient batter takes his walks, and fewer strikeouts, when the pitcher can't find the zone
+    fn disciplined(batter: &ExpectMap, patience: f64, control: f64) -> ExpectMap {
+        let wildness = TYPICAL_CONTROL / control.max(1.0);
+        let factor = (1.0 + DISCIPLINE_EFFECT * (patience - TYPICAL_PATIENCE) * wildness).clamp(0.75, 1.33);
+        let mut expect = batter.clone();
+        let before = expect[&Expect::Walk] + expect[&Expect::Strikeout];
+        *expect.get_mut(&Expect::Walk).unwrap() *= factor;
+        *expect.get_mut(&Expect::Strikeout).unwrap() /= factor;
+        let after = expect[&Expect::Walk] + expect[&Expect::Strikeout];
+        let out = expect.get_mut(&Expect::Out).unwrap();
+        *out = (*out + before - after).max(0.0);
+        expect
     }
 
-    fn record_stat(boxscore: &mut GameLog, player: PlayerId, event: Stat, target: Option<Position>) {
+    fn fatigue_factor(pitches: u32, max: u32) -> f64 {
+        let over = pitches as f64 - max as f64 * FATIGUE_START;
+        (1.0 + over.max(0.0) * FATIGUE_PER_PITCH).min(MAX_FATIGUE)
+    }
+
+    // a tiring arm gives up more of everything but strikeouts and outs, outs absorb the difference
+    fn tire(pitcher: &mut ExpectMap, pitches: u32, max: u32) {
+        let factor = Self::fatigue_factor(pitches, max);
+        if factor <= 1.0 {
+            return;
+        }
+        let mut added = 0.0;
+        for (_, value) in pitcher.iter_mut().filter(|(k, _)| !matches!(k, Expect::Strikeout | Expect::Out)) {
+            added += *value * (factor - 1.0);
+            *value *= factor;
+        }
+        let out = pitcher.get_mut(&Expect::Out).unwrap();
+        *out = (*out - added).max(0.0);
+    }
+
+    fn expected_pa(batter: &ExpectMap, pitcher: &ExpectMap, league: &ExpectMap, run_env: f64, times_through: usize, rng: &mut StdRng) -> Expect {
+        *Self::pa_weights(batter, pitcher, league, run_env, times_through).choose_weighted(rng, |o| o.1).unwrap().0
+    }
+
+    // margin is the batting side's runs less the pitching side's
+    fn leverage(inning: u8, margin: i32, runners: usize) -> Leverage {
+        // the tying run is on base or at the plate, or the batting side leads by one
+        let close = margin <= 1 && -margin <= runners as i32 + 1;
+        let late = inning >= 7;
+        if close && late {
+            Leverage::High
+        } else if close || (late && margin.abs() <= 3) {
+            Leverage::Medium
+        } else {
+            Leverage::Low
+        }
+    }
+
+    fn record_stat(boxscore: &mut EventSink, player: PlayerId, event: Stat, target: Option<Position>) {
+        Self::record_pa(boxscore, player, event, target, false, Leverage::Low);
+    }
+
+    fn record_pa(boxscore: &mut EventSink, player: PlayerId, event: Stat, target: Option<Position>, risp: bool, leverage: Leverage) {
         boxscore.push(GameLogEvent {
             player,
             event,
             target,
+            risp,
+            leverage,
         });
     }
 
-    fn batting_pitching(&mut self, inning: &Inning) -> (&mut Scoreboard, &Scoreboard) {
-        if self.is_away_ab(inning) { (&mut self.away, &self.home) } else { (&mut self.home, &self.away) }
+    fn batting_pitching(&mut self, inning: &Inning) -> (&mut Scoreboard, &mut Scoreboard) {
+        if self.is_away_ab(inning) { (&mut self.away, &mut self.home) } else { (&mut self.home, &mut self.away) }
     }
 
     fn batting(&mut self, inning: &Inning) -> &mut Scoreboard {
@@ -314,22 +1002,22 @@ impl Game {
         if self.is_away_ab(inning) { &mut self.home } else { &mut self.away }
     }
 
-    fn check_for_error(players: &PlayerMap, fielder_id: PlayerId, result: PaResult, rng: &mut ThreadRng) -> PaResult {
+    fn check_for_error(players: &PlayerMap, fielder_id: PlayerId, target: Position, result: PaResult, rng: &mut StdRng) -> PaResult {
         let fielder = players.get(&fielder_id).unwrap();
-        if result == PaResult::Out && fielder.check_for_e(rng) {
-            PaResult::Error
-        } else {
-            result
+        if result != PaResult::Out {
+            return result;
         }
+        fielder.check_for_e(target, rng).map_or(result, PaResult::Error)
     }
 
-    fn check_for_sb(bat_scoreboard: &Scoreboard, players: &PlayerMap, rng: &mut ThreadRng) -> Option<(bool, PlayerId)> {
+    fn check_for_sb(bat_scoreboard: &Scoreboard, players: &PlayerMap, catcher_id: PlayerId, rng: &mut StdRng) -> Option<(bool, PlayerId)> {
         if bat_scoreboard.onbase[2].is_none() {
             if let Some(runner) = bat_scoreboard.onbase[1] {
                 let player = players.get(&runner.id).unwrap();
                 let attempt = player.check_for_sb(rng) && player.check_for_sb(rng) && player.check_for_sb(rng);
                 if attempt {
                     let success = player.check_for_sb(rng) || (player.check_for_sb(rng) && player.check_for_sb(rng));
+                    let success = success && !players.get(&catcher_id).unwrap().check_for_throw_out(rng);
                     return Some((success, runner.id));
                 }
             }
@@ -348,12 +1036,12 @@ impl Game {
         }
     }
 
-    fn sub_pitcher(&mut self, inning: &Inning, teams: &mut TeamMap, players: &mut PlayerMap, boxscore: &mut GameLog, rng: &mut ThreadRng) {
+    fn sub_pitcher(&mut self, inning: &Inning, teams: &mut TeamMap, players: &mut PlayerMap, boxscore: &mut EventSink, rng: &mut StdRng) {
         let bat_scoreboard = self.batting(inning);
         let bat_r = bat_scoreboard.r as i8;
         let on_base = bat_scoreboard.onbase.iter().filter(|o| o.is_some()).count() as i8;
-        //let batter_id = bat_scoreboard.bo[bat_scoreboard.ab].player;
-        //let batter_hand = players.get(&batter_id).unwrap().bats;
+        let batter_id = bat_scoreboard.bo[bat_scoreboard.ab].player;
+        let batter_hand = players.get(&batter_id).unwrap().bats;
 
         let pit_scoreboard = self.pitching(inning);
         let pit_r = pit_scoreboard.r as i8;
@@ -365,9 +1053,7 @@ impl Game {
         let save_threat = (on_base + 2 - run_diff) >= 0;
         let save_situation = save_threat || (run_diff > 0 && run_diff <= 3);
 
-        let mut used_pitchers = pit_scoreboard.pitcher_record.iter().map(|o| o.pitcher).collect::<Vec<_>>();
-        used_pitchers.push(pit_scoreboard.pitcher);
-        let available = pit_team.players.iter().filter(|o| !used_pitchers.contains(*o)).collect::<Vec<_>>();
+        let available = pit_team.players.iter().filter(|o| !pit_scoreboard.used.contains(*o)).collect::<Vec<_>>();
 
         let sub = if save_situation {
             if inning.number == 8 && cur_pitching != Position::Setup {
@@ -391,18 +1077,98 @@ impl Game {
             sub
         };
 
+        // late and close, a dangerous bat gets whichever reliever handles his side best
+        let sub = if sub.is_none() && inning.number >= 7 && run_diff.abs() <= 2 && pit_scoreboard.pitcher_bf > 0 {
+            let current = players.get(&pit_scoreboard.pitcher).unwrap();
+            let batter_hr = *players.get(&batter_id).unwrap().bat_expect_vs(current.throws).get(&Expect::HomeRun).unwrap();
+            let dangerous = batter_hr > *league_avg_vs(current.throws).get(&Expect::HomeRun).unwrap();
+            let allowed = |o: &PlayerId| players.get(o).unwrap().obp_allowed_vs(batter_hand);
+            available.iter()
+                .filter(|o| matches!(players.get(o).unwrap().pos, Position::ShortRelief | Position::LongRelief))
+                .min_by(|a, b| allowed(a).partial_cmp(&allowed(b)).unwrap())
+                .filter(|o| dangerous && allowed(o) + SPECIALIST_EDGE < current.obp_allowed_vs(batter_hand))
+        } else {
+            sub
+        };
+
         if let Some(&&new_pitcher) = sub {
             pit_scoreboard.record_pitcher(bat_r);
 
             pit_scoreboard.pitcher = new_pitcher;
+            pit_scoreboard.used.insert(new_pitcher);
             pit_scoreboard.pitches = 0;
+            pit_scoreboard.pitcher_bf = 0;
+            pit_scoreboard.pitcher_line = PitchingLine::default();
             pit_scoreboard.pitcher_outs = 0;
             pit_scoreboard.pitcher_save_sit = save_situation;
             Self::record_stat(boxscore, new_pitcher, Stat::G, None);
+
+            for runner in self.batting(inning).onbase.iter_mut().flatten() {
+                runner.inherited = Some(new_pitcher);
+                Self::record_stat(boxscore, new_pitcher, Stat::Pir, None);
+            }
+        }
+    }
+
+    // late and close, a weak bat due up makes way for the best one left on the bench who can take his place in the field
+    fn pinch_hit(&mut self, inning: &Inning, teams: &TeamMap, players: &PlayerMap, boxscore: &mut EventSink) {
+        let pit_scoreboard = self.pitching(inning);
+        let pit_r = pit_scoreboard.r as i8;
+        let throws = players.get(&pit_scoreboard.pitcher).unwrap().throws;
+
+        let bat_scoreboard = self.batting(inning);
+        let run_diff = bat_scoreboard.r as i8 - pit_r;
+        if inning.number < 7 || run_diff.abs() > 2 {
+            return;
+        }
+
+        let slot = bat_scoreboard.bo[bat_scoreboard.ab].clone();
+        let current = players.get(&slot.player).unwrap().obp_vs(throws);
+        let bench = teams.get(&bat_scoreboard.id).unwrap().iter_players(players)
+            .filter(|o| !o.1.pos.is_pitcher() && !bat_scoreboard.used.contains(&o.0))
+            .filter(|o| slot.pos == Position::DesignatedHitter || o.1.can_play(slot.pos))
+            .max_by(|a, b| a.1.obp_vs(throws).partial_cmp(&b.1.obp_vs(throws)).unwrap());
+
+        if let Some((sub, player)) = bench {
+            if player.obp_vs(throws) > current + PINCH_HIT_EDGE {
+                bat_scoreboard.bo[bat_scoreboard.ab].player = sub;
+                bat_scoreboard.subs.push((slot.player, sub));
+                bat_scoreboard.used.insert(sub);
+                Self::record_stat(boxscore, sub, Stat::G, Some(slot.pos));
+            }
+        }
+    }
+
+    // protecting a lead after the seventh, a shaky glove makes way for a surer one at the same position
+    fn defensive_sub(&mut self, inning: &Inning, teams: &TeamMap, players: &PlayerMap, boxscore: &mut EventSink) {
+        let bat_r = self.batting(inning).r;
+        let pit_scoreboard = self.pitching(inning);
+        if inning.number <= 7 || pit_scoreboard.r <= bat_r {
+            return;
+        }
+
+        let team = teams.get(&pit_scoreboard.id).unwrap();
+        for idx in 0..pit_scoreboard.bo.len() {
+            let slot = pit_scoreboard.bo[idx].clone();
+            if slot.pos == Position::DesignatedHitter {
+                continue;
+            }
+            let current = players.get(&slot.player).unwrap().error_rate;
+            let glove = team.iter_players(players)
+                .filter(|o| o.1.pos == slot.pos && !pit_scoreboard.used.contains(&o.0))
+                .min_by(|a, b| a.1.error_rate.partial_cmp(&b.1.error_rate).unwrap());
+            if let Some((sub, player)) = glove {
+                if player.error_rate + DEFENSIVE_SUB_EDGE < current {
+                    pit_scoreboard.bo[idx].player = sub;
+                    pit_scoreboard.subs.push((slot.player, sub));
+                    pit_scoreboard.used.insert(sub);
+                    Self::record_stat(boxscore, sub, Stat::G, Some(slot.pos));
+                }
+            }
         }
     }
 
-    fn record_wls(boxscore: &mut GameLog, sb: &Scoreboard, oppo_r: i8) {
+    fn record_wls(boxscore: &mut EventSink, sb: &Scoreboard, oppo_r: i8) {
         let last_pitcher = sb.pitcher_record.len() - 1;
         let mut idx = last_pitcher;
         let mut winner = None;
@@ -462,23 +1228,90 @@ impl Game {
         }
     }
 
-    fn end_of_game(&mut self, players: &mut PlayerMap, boxscore: GameLog) {
+    fn record_qs(boxscore: &mut EventSink, sb: &Scoreboard) {
+        let starter = &sb.pitcher_record[0];
+        if starter.outs >= 18 && starter.line.er <= 3 {
+            Self::record_stat(boxscore, starter.pitcher, Stat::Pqs, None);
+        }
+    }
+
+    fn end_of_game(&mut self, players: &mut PlayerMap, boxscore: EventSink) {
+        let boxscore = match boxscore {
+            EventSink::Log(log) => log,
+            EventSink::Fast(tally) => {
+                let FastTally { summary, risp, clutch, .. } = *tally;
+                self.check_line(&summary);
+                let slots = self.batting_slots();
+                for (player_id, stream) in &summary.stat_map {
+                    let player = players.get_mut(player_id).unwrap();
+                    for stat in stream {
+                        player.record_stat(*stat);
+                        if let Some(slot) = slots.get(player_id) {
+                            player.record_slot_stat(*slot, *stat);
+                        }
+                    }
+                }
+                for (player_id, stream) in &risp {
+                    let player = players.get_mut(player_id).unwrap();
+                    stream.iter().for_each(|o| player.record_risp_stat(*o));
+                }
+                for (player_id, stream) in &clutch {
+                    let player = players.get_mut(player_id).unwrap();
+                    stream.iter().for_each(|o| player.record_clutch_stat(*o));
+                }
+                // the stat map stays until the league has checked the game for records
+                self.summary = Some(summary);
+                return;
+            }
+        };
+        if self.postseason {
+            self.playbyplay = boxscore;
+            return;
+        }
+
+        let slots = self.batting_slots();
         for event in &boxscore {
             let player = players.get_mut(&event.player).unwrap();
             player.record_stat(event.event);
+            if let Some(slot) = slots.get(&event.player) {
+                player.record_slot_stat(*slot, event.event);
+            }
+            if event.risp {
+                player.record_risp_stat(event.event);
+            }
+            if event.leverage == Leverage::High {
+                player.record_clutch_stat(event.event);
+            }
         }
 
         self.playbyplay = boxscore;
     }
 
-    pub(crate) fn sim(&mut self, teams: &mut TeamMap, players: &mut PlayerMap, year: u32, rng: &mut ThreadRng) {
-        let mut boxscore = GameLog::new();
+    // the lineup slot each player who appeared batted in
+    fn batting_slots(&self) -> HashMap<PlayerId, usize> {
+        let mut slots = self.away.bo.iter().enumerate().chain(self.home.bo.iter().enumerate()).map(|(slot, o)| (o.player, slot)).collect::<HashMap<_, _>>();
+        // latest first, so a sub who was later replaced himself already has his slot
+        for (replaced, sub) in self.away.subs.iter().rev().chain(self.home.subs.iter().rev()) {
+            if let Some(slot) = slots.get(sub).copied() {
+                slots.insert(*replaced, slot);
+            }
+        }
+        slots
+    }
+
+    pub(crate) fn sim(&mut self, teams: &mut TeamMap, players: &mut PlayerMap, year: u32, run_env: f64, mercy_rule: Option<(u8, u8)>, seed: u64) {
+        self.seed = seed;
+        self.mercy_rule = mercy_rule;
+        let rng = &mut StdRng::seed_from_u64(seed);
+        let mut boxscore = EventSink::new(self.fast && !self.postseason);
         let mut inning = Inning {
             number: 1,
             half: InningHalf::Top,
         };
         let mut outs = 0;
         let mut virtual_outs = 0;
+        // (bases, outs, runs) as each plate appearance of the half inning began
+        let mut half_states = Vec::new();
 
         self.setup_game(players, teams, &mut boxscore, year, rng);
 
@@ -499,20 +1332,28 @@ impl Game {
                 continue;
             }
 
+            boxscore.set_inning(&inning);
             self.sub_pitcher(&inning, teams, players, &mut boxscore, rng);
-
+            self.defensive_sub(&inning, teams, players, &mut boxscore);
+            self.pinch_hit(&inning, teams, players, &mut boxscore);
 
             let (bat_scoreboard, pit_scoreboard) = self.batting_pitching(&inning);
+            half_states.push((bat_scoreboard.base_state(), outs, bat_scoreboard.r));
 
             let mut cs_outs = 0;
             if outs < 2 {
-                if let Some((sb, runner_id)) = Self::check_for_sb(bat_scoreboard, players, rng) {
+                let catcher_id = pit_scoreboard.player_at_pos(Position::Catcher);
+                if let Some((sb, runner_id)) = Self::check_for_sb(bat_scoreboard, players, catcher_id, rng) {
                     if sb {
                         bat_scoreboard.advance_onbase(1);
                         Self::record_stat(&mut boxscore, runner_id, Stat::Bsb, None);
+                        Self::record_stat(&mut boxscore, catcher_id, Stat::Fsb, None);
                     } else {
                         bat_scoreboard.onbase[1] = None;
                         Self::record_stat(&mut boxscore, runner_id, Stat::Bcs, None);
+                        Self::record_stat(&mut boxscore, catcher_id, Stat::Fcs, None);
+                        Self::record_stat(&mut boxscore, catcher_id, Stat::Fa, None);
+                        Self::record_stat(&mut boxscore, pit_scoreboard.player_at_pos(Position::ShortStop), Stat::Fpo, None);
                         cs_outs = 1;
                     }
                 }
@@ -523,22 +1364,28 @@ impl Game {
             let pitcher = players.get(&pitcher_id).unwrap();
 
             let batter_id = bat_scoreboard.bo[bat_scoreboard.ab].player;
+            let risp = bat_scoreboard.onbase[2].is_some() || bat_scoreboard.onbase[3].is_some();
+            let leverage = Self::leverage(inning.number, bat_scoreboard.r as i32 - pit_scoreboard.r as i32, bat_scoreboard.onbase[1..].iter().flatten().count());
             let batter = players.get(&batter_id).unwrap();
 
             let batter_expect = batter.bat_expect_vs(pitcher.throws);
-            let pitcher_expect = pitcher.pit_expect_vs(batter.bats);
+            let disciplined = Self::disciplined(batter_expect, batter.patience, pitcher.control);
+            let mut pitcher_expect = pitcher.arsenal.apply(pitcher.pit_expect_vs(batter.bats));
+            Self::tire(&mut pitcher_expect, pit_scoreboard.pitches, Self::max_pitches_for_pos(pitcher.pos));
+            let league_expect = league_avg_vs(pitcher.throws);
 
             let pitch_avg = (batter.patience + pitcher.control) / 2.0;
             let mut pitches = gen_gamma(rng, pitch_avg, 1.0).round().max(1.0) as u32;
 
-            let expect = Self::expected_pa(batter_expect, pitcher_expect, rng);
+            let times_through = Self::times_through(pit_scoreboard.pitcher_bf);
+            let expect = Self::expected_pa(&disciplined, &pitcher_expect, league_expect, run_env, times_through, rng);
             let mut result = PaResult::from(expect);
 
             let mut ibb_cond = inning.number > 6;
             ibb_cond = ibb_cond && outs == 1;
             ibb_cond = ibb_cond && bat_scoreboard.onbase[1].is_none();
             ibb_cond = ibb_cond && bat_scoreboard.onbase[2].is_some();
-            ibb_cond = ibb_cond && ( batter_expect.get(&Expect::HomeRun).unwrap() * 0.7 ) > *LEAGUE_AVG.get(&Expect::HomeRun).unwrap();
+            ibb_cond = ibb_cond && ( batter_expect.get(&Expect::HomeRun).unwrap() * 0.7 ) > *league_expect.get(&Expect::HomeRun).unwrap();
             if ibb_cond {
                 result = PaResult::IntentionalWalk;
                 pitches = 0;
@@ -547,23 +1394,22 @@ impl Game {
             let target = Player::determine_spray(&batter.bat_spray, &pitcher.pit_spray, &expect, rng);
 
             let fielder_id = pit_scoreboard.player_at_pos(target);
-            let result = Self::check_for_error(players, fielder_id, result, rng);
+            let result = Self::check_for_error(players, fielder_id, target, result, rng);
 
             let mut box_target = None;
+            let mut thrown_out = None;
 
-            let earned = virtual_outs < 3;
+            let earned = virtual_outs + cs_outs < 3;
 
             let result_outs = match result {
                 PaResult::Single => {
                     box_target = Some(target);
-
-                    if target == Position::RightField {
-                        bat_scoreboard.advance_onbase(3);
-                        bat_scoreboard.advance_onbase(2);
-                    }
-
                     bat_scoreboard.h += 1;
-                    bat_scoreboard.advance_batter(batter_id, pitcher_id, earned, 1);
+                    // a third out on the bases would leave it unclear which runs count, so nobody runs with two down
+                    let can_run = outs + cs_outs < 2;
+                    thrown_out = bat_scoreboard.advance_on_single(target, batter_id, pitcher_id, earned, |runner| {
+                        if can_run { players.get(&runner).unwrap().try_extra_base(rng) } else { None }
+                    });
                     0
                 }
                 PaResult::Double => {
@@ -598,22 +1444,28 @@ impl Game {
                     bat_scoreboard.advance_batter(batter_id, pitcher_id, earned, 1);
                     0
                 }
-                PaResult::Error => {
+                PaResult::Error(error_type) => {
                     box_target = Some(target);
-                    Self::record_stat(&mut boxscore, fielder_id, Stat::Fe, None);
-                    bat_scoreboard.e += 1;
-                    bat_scoreboard.advance_batter(batter_id, pitcher_id, false, 1);
+                    let (stat, bases) = match error_type {
+                        ErrorType::Fielding => (Stat::Fe, 1),
+                        ErrorType::Throwing => (Stat::Fte, 2),
+                    };
+                    Self::record_stat(&mut boxscore, fielder_id, stat, None);
+                    pit_scoreboard.e += 1;
+                    bat_scoreboard.advance_batter(batter_id, pitcher_id, false, bases);
                     0
                 }
                 PaResult::Strikeout => {
                     pitches = pitches.max(3);
+                    Self::record_stat(&mut boxscore, pit_scoreboard.player_at_pos(Position::Catcher), Stat::Fpo, None);
                     1
                 }
                 PaResult::Out => {
                     box_target = Some(target);
 
                     let mut add_outs = 1;
-                    if outs < 2 {
+                    // a runner caught stealing counts, nobody advances on the third out
+                    if outs + cs_outs < 2 {
                         match target {
                             Position::LeftField |
                             Position::CenterField |
@@ -635,37 +1487,70 @@ impl Game {
                         }
                     }
 
-                    Self::record_stat(&mut boxscore, fielder_id, Stat::Fpo, None);
+                    // infield grounders are thrown to first, going through the middle infielder covering second on a double play
+                    if matches!(target, Position::LeftField | Position::CenterField | Position::RightField) {
+                        Self::record_stat(&mut boxscore, fielder_id, Stat::Fpo, None);
+                    } else {
+                        if add_outs > 1 {
+                            let pivot = if matches!(target, Position::FirstBase | Position::SecondBase) { Position::ShortStop } else { Position::SecondBase };
+                            let pivot_id = pit_scoreboard.player_at_pos(pivot);
+                            Self::record_stat(&mut boxscore, fielder_id, Stat::Fa, None);
+                            Self::record_stat(&mut boxscore, pivot_id, Stat::Fpo, None);
+                            Self::record_stat(&mut boxscore, pivot_id, Stat::Fa, None);
+                        } else if target != Position::FirstBase {
+                            Self::record_stat(&mut boxscore, fielder_id, Stat::Fa, None);
+                        }
+                        Self::record_stat(&mut boxscore, pit_scoreboard.player_at_pos(Position::FirstBase), Stat::Fpo, None);
+                    }
                     add_outs
                 }
             };
-            Self::record_stat(&mut boxscore, batter_id, result.to_batting_stat(result_outs), box_target);
-
-            let new_outs = result_outs + cs_outs;
+            Self::record_pa(&mut boxscore, batter_id, result.to_batting_stat(result_outs), box_target, risp, leverage);
+
+            let runner_outs = if let Some((runner_id, base)) = thrown_out {
+                let covering = if base > 3 { Position::Catcher } else { Position::ThirdBase };
+                Self::record_stat(&mut boxscore, runner_id, Stat::Boob, None);
+                Self::record_stat(&mut boxscore, fielder_id, Stat::Fa, None);
+                Self::record_stat(&mut boxscore, pit_scoreboard.player_at_pos(covering), Stat::Fpo, None);
+                Self::record_stat(&mut boxscore, pitcher_id, Stat::Po, None);
+                1
+            } else {
+                0
+            };
+            let new_outs = result_outs + cs_outs + runner_outs;
+            let virtual_outs_on_play = virtual_outs + new_outs + if matches!(result, PaResult::Error(_)) { 1 } else { 0 };
 
-            if result != PaResult::Error {
+            if !matches!(result, PaResult::Error(_)) {
                 for _ in &bat_scoreboard.runs_in {
                     Self::record_stat(&mut boxscore, batter_id, Stat::Brbi, None);
                 }
             }
 
+            // a runner caught stealing is an out even if the batter then reaches on an error
+            for _ in 0..cs_outs {
+                Self::record_stat(&mut boxscore, pitcher_id, Stat::Po, None);
+            }
             if let Some(pitching_stat) = result.to_pitching_stat() {
                 Self::record_stat(&mut boxscore, pitcher_id, pitching_stat, None);
-                for _ in 0..cs_outs {
-                    Self::record_stat(&mut boxscore, pitcher_id, Stat::Po, None);
-                }
                 for _ in 1..result_outs {
                     Self::record_stat(&mut boxscore, pitcher_id, Stat::Po, None);
                 }
             }
 
+            let mut charged = Vec::new();
             for runner in &bat_scoreboard.runs_in {
                 Self::record_stat(&mut boxscore, runner.id, Stat::Br, None);
-                if runner.earned {
+                Self::record_stat(&mut boxscore, bat_scoreboard.pitcher, Stat::Prs, None);
+                let earned = runner.is_earned(virtual_outs_on_play);
+                charged.push((runner.pitcher, earned));
+                if earned {
                     Self::record_stat(&mut boxscore, runner.pitcher, Stat::Per, None);
                 } else {
                     Self::record_stat(&mut boxscore, runner.pitcher, Stat::Pr, None);
                 }
+                if let Some(reliever) = runner.inherited {
+                    Self::record_stat(&mut boxscore, reliever, Stat::Pirs, None);
+                }
             }
 
             let score_diff_pre = pit_scoreboard.r as i8 - bat_scoreboard.r as i8;
@@ -678,16 +1563,25 @@ impl Game {
 
             bat_scoreboard.ab = (bat_scoreboard.ab + 1) % 9;
 
+            self.walkoff = self.is_walkoff(&inning, score_diff_pre);
+
             let pit_scoreboard = self.pitching(&inning);
             pit_scoreboard.pitches += pitches;
+            pit_scoreboard.pitcher_bf += 1;
+            pit_scoreboard.pitcher_line.record(&result);
+            for (pitcher, earned) in charged {
+                pit_scoreboard.pitching_line(pitcher).record_run(earned);
+            }
             pit_scoreboard.pitcher_outs += new_outs;
 
             outs += new_outs;
-            virtual_outs += new_outs;
-            if result == PaResult::Error {
-                virtual_outs += 1;
-            }
+            virtual_outs = virtual_outs_on_play;
             if outs >= 3 {
+                let runs = self.batting(&inning).r;
+                for (bases, state_outs, before) in half_states.drain(..) {
+                    self.run_expectancy.record(bases, state_outs, runs - before);
+                }
+                self.batting(&inning).strand_runners();
                 if inning.half == InningHalf::Top {
                     inning.half = InningHalf::Middle;
                 } else if inning.half == InningHalf::Bottom {
@@ -701,15 +1595,19 @@ impl Game {
         let pitching = self.pitching(&inning);
         pitching.record_pitcher(bat_r);
         Self::record_wls(&mut boxscore, pitching, bat_r);
+        Self::record_qs(&mut boxscore, pitching);
 
         let pit_r = pitching.r as i8;
 
         let batting = self.batting(&inning);
         batting.record_pitcher(pit_r);
         Self::record_wls(&mut boxscore, batting, pit_r);
+        Self::record_qs(&mut boxscore, batting);
 
-        teams.get_mut(&self.home.id).unwrap().results(self.home.r, self.away.r);
-        teams.get_mut(&self.away.id).unwrap().results(self.away.r, self.home.r);
+        if !self.postseason {
+            teams.get_mut(&self.home.id).unwrap().results(self.home.r, self.away.r);
+            teams.get_mut(&self.away.id).unwrap().results(self.away.r, self.home.r);
+        }
 
         self.end_of_game(players, boxscore);
     }
@@ -717,7 +1615,18 @@ impl Game {
 
 #[cfg(test)]
 mod tests {
-    use crate::game::{RunnerInfo, Scoreboard};
+    use std::collections::HashSet;
+
+    use rand::{Rng, SeedableRng};
+    use rand::rngs::StdRng;
+
+    use crate::data::Data;
+    use crate::game::{EventSink, Game, Inning, InningHalf, Leverage, PaResult, PitcherRecord, PitchingLine, RunnerInfo, Scoreboard, MAX_FATIGUE};
+    use crate::player::{Arsenal, ErrorType, Expect, ExpectMap, Handedness, Player, Position};
+    use crate::sim::league_avg_vs;
+    use crate::stat::{Stat, Stats};
+    use crate::team::tests::setup_teams;
+    use crate::team::TeamId;
 
     #[test]
     fn test_advance_onbase() {
@@ -733,7 +1642,7 @@ mod tests {
         assert!(test.onbase[2].is_none());
         assert!(test.onbase[3].is_none());
 
-        test.onbase[0] = Some(RunnerInfo { id: 23, pitcher: 0, earned: true });
+        test.onbase[0] = Some(RunnerInfo { id: 23, pitcher: 0, earned: true, inherited: None });
         test.advance_onbase(0);
         assert!(test.onbase[0].is_none());
         assert!(test.onbase[1].is_some());
@@ -760,10 +1669,10 @@ mod tests {
         assert!(test.onbase[3].is_none());
         assert_eq!(test.runs_in.len(), 1);
 
-        test.onbase[0] = Some(RunnerInfo { id: 23, pitcher: 0, earned: true });
-        test.onbase[1] = Some(RunnerInfo { id: 23, pitcher: 0, earned: true });
-        test.onbase[2] = Some(RunnerInfo { id: 23, pitcher: 0, earned: true });
-        test.onbase[3] = Some(RunnerInfo { id: 23, pitcher: 0, earned: true });
+        test.onbase[0] = Some(RunnerInfo { id: 23, pitcher: 0, earned: true, inherited: None });
+        test.onbase[1] = Some(RunnerInfo { id: 23, pitcher: 0, earned: true, inherited: None });
+        test.onbase[2] = Some(RunnerInfo { id: 23, pitcher: 0, earned: true, inherited: None });
+        test.onbase[3] = Some(RunnerInfo { id: 23, pitcher: 0, earned: true, inherited: None });
         test.advance_onbase(0);
         assert!(test.onbase[0].is_none());
         assert!(test.onbase[1].is_some());
@@ -778,7 +1687,7 @@ mod tests {
         assert!(test.onbase[3].is_some());
         assert_eq!(test.runs_in.len(), 3);
 
-        test.onbase[0] = Some(RunnerInfo { id: 23, pitcher: 0, earned: true });
+        test.onbase[0] = Some(RunnerInfo { id: 23, pitcher: 0, earned: true, inherited: None });
         test.advance_onbase(0);
         assert!(test.onbase[0].is_none());
         assert!(test.onbase[1].is_some());
@@ -787,6 +1696,23 @@ mod tests {
         assert_eq!(test.runs_in.len(), 3);
     }
 
+    #[test]
+    fn test_strand_runners() {
+        let mut test = Scoreboard::new(0);
+        for batter in 1..=3 {
+            test.advance_batter(batter, 0, true, 1);
+        }
+        assert!(test.onbase[1..].iter().all(|o| o.is_some()));
+
+        test.strand_runners();
+        assert_eq!(test.lob, 3);
+        assert!(test.onbase.iter().all(|o| o.is_none()));
+
+        test.advance_batter(4, 0, true, 2);
+        test.strand_runners();
+        assert_eq!(test.lob, 4);
+    }
+
     #[test]
     fn test_advance_onbase_n() {
         let mut test = Scoreboard::new(0);
@@ -842,4 +1768,733 @@ mod tests {
         assert!(test.onbase[3].is_none());
         assert_eq!(test.runs_in.len(), 1);
     }
+
+    #[test]
+    fn test_unearned_runs_in_sim() {
+        let mut rng = StdRng::seed_from_u64(2049);
+        let year = 2049;
+        let (mut teams, mut players) = setup_teams(2, year, &Data::new(), &mut rng);
+
+        // the visitors boot a ball every few chances, the home side never does
+        for (team_id, error_rate) in [(1, 0.15), (2, 0.0)] {
+            for player_id in &teams[&team_id].players {
+                players.get_mut(player_id).unwrap().error_rate = error_rate;
+            }
+        }
+        let mut extended_runs = 0;
+        for _ in 0..20 {
+            let mut game = Game::new(1, 2);
+            game.sim(&mut teams, &mut players, year, 1.0, None, rng.gen());
+
+            // an error with two down should have ended the inning, nothing scored after it is earned
+            let mut extended = None;
+            for annotated in game.events() {
+                let half = (annotated.inning, annotated.tophalf);
+                if matches!(annotated.event.event, Stat::Fe | Stat::Fte) && annotated.outs == 2 {
+                    extended = Some(half);
+                }
+                if extended == Some(half) {
+                    assert_ne!(annotated.event.event, Stat::Per);
+                    if annotated.event.event == Stat::Pr {
+                        extended_runs += 1;
+                    }
+                }
+            }
+        }
+        assert!(extended_runs > 0);
+
+        // (runs, earned runs, errors) charged to each side's defense
+        let allowed = |team_id: TeamId| teams[&team_id].iter_players(&players).map(|o| o.1.get_stats()).fold((0, 0, 0), |acc, o| (acc.0 + o.p_r, acc.1 + o.p_er, acc.2 + o.f_e));
+        let (r, er, e) = allowed(2);
+        assert_eq!(e, 0);
+        assert_eq!(r, er);
+        let (r, er, e) = allowed(1);
+        assert!(e > 0);
+        assert!(er < r);
+    }
+
+    #[test]
+    fn test_fast_sim_matches_log() {
+        let mut rng = StdRng::seed_from_u64(2049);
+        let year = 2049;
+        let (teams, players) = setup_teams(2, year, &Data::new(), &mut rng);
+        let seed = rng.gen();
+
+        let sim = |fast: bool| {
+            let (mut teams, mut players) = (teams.clone(), players.clone());
+            let mut game = Game::new(1, 2);
+            game.fast = fast;
+            game.sim(&mut teams, &mut players, year, 1.0, None, seed);
+            (game, players)
+        };
+        let (full, full_players) = sim(false);
+        let (fast, fast_players) = sim(true);
+
+        assert!(fast.playbyplay.is_empty() && !fast.has_playbyplay());
+        let (full_box, fast_box) = (full.boxscore(), fast.boxscore());
+        assert_eq!((&full_box.away_runs, &full_box.home_runs), (&fast_box.away_runs, &fast_box.home_runs));
+        assert_eq!((&full_box.away_hits, &full_box.home_hits), (&fast_box.away_hits, &fast_box.home_hits));
+        assert_eq!((full_box.winner, full_box.loser, full_box.save), (fast_box.winner, fast_box.loser, fast_box.save));
+        assert_eq!(full_box.stat_map, fast_box.stat_map);
+
+        // every stream the log feeds gets the same stats without it
+        for (player_id, player) in &full_players {
+            let other = &fast_players[player_id];
+            let line = |o: &Player| {
+                let (stats, risp, clutch) = (o.get_stats(), o.get_risp_stats(), o.get_clutch_stats());
+                let slots = o.get_slot_stats().iter().map(|(slot, o)| (*slot, o.b_pa, o.b_h)).collect::<Vec<_>>();
+                (stats.b_pa, stats.b_h, stats.b_rbi, stats.p_er, stats.p_qs, stats.f_po, risp.b_pa, risp.b_h, clutch.b_pa, clutch.b_h, slots)
+            };
+            assert_eq!(line(player), line(other));
+        }
+    }
+
+    #[test]
+    fn test_walkoff() {
+        let mut game = Game::new(1, 2);
+        game.away.r = 3;
+        game.home.r = 3;
+
+        // home scores the go-ahead run in the bottom of the 9th
+        let inning = Inning { number: 9, half: InningHalf::Bottom };
+        let score_diff_pre = game.away.r as i8 - game.home.r as i8;
+        game.home.r += 1;
+        assert!(game.is_walkoff(&inning, score_diff_pre));
+
+        // adding on after already leading isn't a walk-off
+        let score_diff_pre = game.away.r as i8 - game.home.r as i8;
+        game.home.r += 1;
+        assert!(!game.is_walkoff(&inning, score_diff_pre));
+
+        // nor is taking the lead earlier in the game
+        let inning = Inning { number: 8, half: InningHalf::Bottom };
+        game.home.r = 4;
+        assert!(!game.is_walkoff(&inning, 0));
+
+        // nor is the away team taking the lead in the top half
+        let inning = Inning { number: 9, half: InningHalf::Top };
+        game.home.r = 3;
+        game.away.r = 4;
+        assert!(!game.is_walkoff(&inning, 0));
+    }
+
+    #[test]
+    fn test_mercy_rule() {
+        let mut game = Game::new(1, 2);
+        game.home.r = 12;
+        game.away.r = 2;
+        let after_seventh = Inning { number: 7, half: InningHalf::End };
+        assert!(!game.is_complete(&after_seventh));
+
+        game.mercy_rule = Some((10, 7));
+        assert!(game.is_complete(&after_seventh));
+        assert!(game.is_complete(&Inning { number: 7, half: InningHalf::Middle }));
+        assert!(!game.is_complete(&Inning { number: 6, half: InningHalf::End }));
+
+        // the home team still gets its turn to answer a big away lead
+        game.home.r = 2;
+        game.away.r = 12;
+        assert!(!game.is_complete(&Inning { number: 7, half: InningHalf::Middle }));
+        assert!(game.is_complete(&after_seventh));
+
+        // any lead after the first ends it, and the shortened game still gets a decision
+        let mut rng = StdRng::seed_from_u64(2049);
+        let year = 2049;
+        let (mut teams, mut players) = setup_teams(2, year, &Data::new(), &mut rng);
+        let mut game = Game::new(1, 2);
+        game.sim(&mut teams, &mut players, year, 1.0, Some((1, 1)), rng.gen());
+        assert_ne!(game.home.r, game.away.r);
+        assert_eq!(game.playbyplay.iter().filter(|o| o.event == Stat::Pw).count(), 1);
+        assert_eq!(game.playbyplay.iter().filter(|o| o.event == Stat::Pl).count(), 1);
+        let starters_went_the_distance = [&game.home, &game.away].iter().filter(|o| o.pitcher_record.len() == 1).count();
+        assert_eq!(game.playbyplay.iter().filter(|o| o.event == Stat::Pcg).count(), starters_went_the_distance);
+    }
+
+    #[test]
+    fn test_boxscore_matches_scoreboard() {
+        let mut rng = StdRng::seed_from_u64(2049);
+        let year = 2049;
+        let (mut teams, mut players) = setup_teams(2, year, &Data::new(), &mut rng);
+
+        for _ in 0..50 {
+            let mut game = Game::new(1, 2);
+            game.sim(&mut teams, &mut players, year, 1.0, None, rng.gen());
+            let boxscore = game.boxscore();
+            for (runs, hits, scoreboard) in [(&boxscore.away_runs, &boxscore.away_hits, &game.away), (&boxscore.home_runs, &boxscore.home_hits, &game.home)] {
+                assert_eq!(runs.iter().map(|o| *o as u32).sum::<u32>(), scoreboard.r as u32);
+                assert_eq!(hits.iter().map(|o| *o as u32).sum::<u32>(), scoreboard.h as u32);
+            }
+        }
+    }
+
+    #[test]
+    fn test_max_innings() {
+        let mut rng = StdRng::seed_from_u64(2049);
+        let year = 2049;
+        let (mut teams, mut players) = setup_teams(2, year, &Data::new(), &mut rng);
+
+        // a scoreless first is common enough that one turns up quickly
+        let game = (0..200).map(|_| {
+            let mut game = Game::new(1, 2);
+            game.max_innings = Some(1);
+            game.sim(&mut teams, &mut players, year, 1.0, None, rng.gen());
+            game
+        }).find(|o| o.is_tie()).unwrap();
+        assert!(game.playbyplay.iter().all(|o| o.event != Stat::Pw && o.event != Stat::Pl));
+        assert!(teams[&1].get_ties() > 0 && teams[&1].get_ties() == teams[&2].get_ties());
+        assert!(game.recap(&teams, &players).contains("tie"));
+
+        // without a cap play goes on until someone wins
+        let mut game = Game::new(1, 2);
+        game.sim(&mut teams, &mut players, year, 1.0, None, rng.gen());
+        assert!(!game.is_tie());
+    }
+
+    #[test]
+    fn test_quality_start() {
+        let mut test = Scoreboard::new(0);
+        test.pitcher_record.push(PitcherRecord { pitcher: 7, outs: 21, ..PitcherRecord::default() });
+
+        test.pitcher_record[0].line = PitchingLine { r: 3, er: 2, ..PitchingLine::default() };
+
+        let mut boxscore = EventSink::new(false);
+        Game::record_qs(&mut boxscore, &test);
+        assert!(boxscore.log().iter().any(|o| o.player == 7 && o.event == Stat::Pqs));
+
+        // a fourth earned run costs the quality start
+        test.pitcher_record[0].line.er = 4;
+        let mut boxscore = EventSink::new(false);
+        Game::record_qs(&mut boxscore, &test);
+        assert!(!boxscore.log().iter().any(|o| o.event == Stat::Pqs));
+
+        // as does leaving before completing six innings
+        test.pitcher_record[0].line.er = 0;
+        test.pitcher_record[0].outs = 17;
+        let mut boxscore = EventSink::new(false);
+        Game::record_qs(&mut boxscore, &test);
+        assert!(!boxscore.log().iter().any(|o| o.event == Stat::Pqs));
+    }
+
+    #[test]
+    fn test_pitching_line() {
+        let mut test = Scoreboard::new(0);
+        test.pitcher = 1;
+        test.pitcher_line.record(&PaResult::HomeRun);
+        test.pitcher_line.record(&PaResult::Walk);
+        test.record_pitcher(0);
+
+        test.pitcher = 2;
+        test.pitcher_line = Default::default();
+        test.pitcher_line.record(&PaResult::Strikeout);
+
+        // the reliever lets the starter's runner score
+        test.pitching_line(1).record_run(true);
+        test.pitching_line(2).record_run(false);
+
+        let starter = &test.pitcher_record[0].line;
+        assert_eq!((starter.h, starter.hr, starter.bb, starter.r, starter.er), (1, 1, 1, 1, 1));
+        assert_eq!((test.pitcher_line.so, test.pitcher_line.r, test.pitcher_line.er), (1, 1, 0));
+    }
+
+    #[test]
+    fn test_times_through_order() {
+        assert_eq!(Game::times_through(0), 1);
+        assert_eq!(Game::times_through(17), 2);
+        assert_eq!(Game::times_through(18), 3);
+
+        let mut rng = StdRng::seed_from_u64(2049);
+        let (_, players) = setup_teams(2, 2049, &Data::new(), &mut rng);
+        let pitcher = players.values().find(|o| o.pos.is_pitcher()).unwrap();
+        // a power bat, so the home run weight is big enough not to get lost in rounding
+        let batter = players.values().filter(|o| !o.pos.is_pitcher())
+            .max_by(|a, b| a.bat_expect_vs(pitcher.throws)[&Expect::HomeRun].partial_cmp(&b.bat_expect_vs(pitcher.throws)[&Expect::HomeRun]).unwrap())
+            .unwrap();
+        let batter_expect = batter.bat_expect_vs(pitcher.throws);
+        let pitcher_expect = pitcher.pit_expect_vs(batter.bats);
+
+        // share of plate appearances that end in a hit, and in a home run
+        let hit_rates = |times_through| {
+            let weights = Game::pa_weights(batter_expect, pitcher_expect, league_avg_vs(pitcher.throws), 1.0, times_through);
+            let total = weights.iter().map(|o| o.1).sum::<u32>() as f64;
+            let weight_of = |pred: &dyn Fn(&Expect) -> bool| weights.iter().filter(|o| pred(o.0)).map(|o| o.1).sum::<u32>() as f64 / total;
+            (weight_of(&|o| matches!(o, Expect::Single | Expect::Double | Expect::Triple | Expect::HomeRun)), weight_of(&|o| *o == Expect::HomeRun))
+        };
+        let first = hit_rates(1);
+        let third = hit_rates(3);
+        assert!(third.0 > first.0);
+        assert!(third.1 > first.1);
+        assert_eq!(hit_rates(5), third);
+    }
+
+    #[test]
+    fn test_league_split() {
+        let mut rng = StdRng::seed_from_u64(2049);
+        let (_, players) = setup_teams(2, 2049, &Data::new(), &mut rng);
+        let batter = players.values().find(|o| !o.pos.is_pitcher()).unwrap();
+        let pitcher = players.values().find(|o| o.pos.is_pitcher()).unwrap();
+        let batter_expect = batter.bat_expect_vs(Handedness::Right);
+        let pitcher_expect = pitcher.pit_expect_vs(batter.bats);
+
+        // the same two players, only the league they're measured against changes with the pitcher's hand
+        let weight = |throws: Handedness, outcome: Expect| {
+            Game::pa_weights(batter_expect, pitcher_expect, league_avg_vs(throws), 1.0, 1).iter().find(|o| *o.0 == outcome).unwrap().1
+        };
+        assert!(weight(Handedness::Left, Expect::Strikeout) > weight(Handedness::Right, Expect::Strikeout));
+        assert!(weight(Handedness::Left, Expect::Single) < weight(Handedness::Right, Expect::Single));
+    }
+
+    #[test]
+    fn test_arsenal() {
+        let mut rng = StdRng::seed_from_u64(2049);
+        let (_, players) = setup_teams(2, 2049, &Data::new(), &mut rng);
+        let batter = players.values().find(|o| !o.pos.is_pitcher()).unwrap();
+        let pitcher = players.values().find(|o| o.pos.is_pitcher()).unwrap();
+        let batter_expect = batter.bat_expect_vs(pitcher.throws);
+        let base = pitcher.pit_expect_vs(batter.bats);
+
+        // neutral stuff leaves the pitcher as he was
+        let neutral = Arsenal::default().apply(base);
+        assert!(neutral.iter().all(|(k, v)| (v - base[k]).abs() < 1e-12));
+        assert_eq!(Arsenal::grade(1.0), 50);
+
+        let ace = Arsenal { velocity: 1.15, movement: 1.15 }.apply(base);
+        assert!((ace.values().sum::<f64>() - base.values().sum::<f64>()).abs() < 1e-9);
+        let weight = |expect: &ExpectMap, outcome: Expect| {
+            Game::pa_weights(batter_expect, expect, league_avg_vs(pitcher.throws), 1.0, 1).iter().find(|o| *o.0 == outcome).unwrap().1
+        };
+        assert!(weight(&ace, Expect::Strikeout) > weight(base, Expect::Strikeout));
+        assert!(weight(&ace, Expect::HomeRun) <= weight(base, Expect::HomeRun));
+    }
+
+    #[test]
+    fn test_discipline() {
+        let mut rng = StdRng::seed_from_u64(2049);
+        let (_, players) = setup_teams(2, 2049, &Data::new(), &mut rng);
+        let batter = players.values().find(|o| !o.pos.is_pitcher()).unwrap();
+        let pitcher = players.values().find(|o| o.pos.is_pitcher()).unwrap();
+        let batter_expect = batter.bat_expect_vs(pitcher.throws);
+        let pitcher_expect = pitcher.pit_expect_vs(batter.bats);
+
+        let weight = |patience: f64, control: f64, outcome: Expect| {
+            let expect = Game::disciplined(batter_expect, patience, control);
+            assert!((expect.values().sum::<f64>() - batter_expect.values().sum::<f64>()).abs() < 1e-9);
+            let weights = Game::pa_weights(&expect, pitcher_expect, league_avg_vs(pitcher.throws), 1.0, 1);
+            weights.iter().find(|o| *o.0 == outcome).unwrap().1
+        };
+
+        // against a wild pitcher the patient hitter walks more and strikes out less
+        assert!(weight(9.0, 1.0, Expect::Walk) > weight(1.0, 1.0, Expect::Walk));
+        assert!(weight(9.0, 1.0, Expect::Strikeout) < weight(1.0, 1.0, Expect::Strikeout));
+        // and the gap narrows against a pitcher who lives in the zone
+        let wild_gap = weight(9.0, 1.0, Expect::Walk) - weight(1.0, 1.0, Expect::Walk);
+        let sharp_gap = weight(9.0, 8.0, Expect::Walk) - weight(1.0, 8.0, Expect::Walk);
+        assert!(sharp_gap < wild_gap);
+    }
+
+    #[test]
+    fn test_pitch_fatigue() {
+        assert_eq!(Game::fatigue_factor(0, 110), 1.0);
+        assert_eq!(Game::fatigue_factor(88, 110), 1.0);
+        assert!(Game::fatigue_factor(100, 110) > 1.0);
+        assert_eq!(Game::fatigue_factor(500, 110), MAX_FATIGUE);
+
+        let mut rng = StdRng::seed_from_u64(2049);
+        let (_, players) = setup_teams(2, 2049, &Data::new(), &mut rng);
+        let batter = players.values().find(|o| !o.pos.is_pitcher()).unwrap();
+        let pitcher = players.values().find(|o| o.pos == Position::StartingPitcher).unwrap();
+        let fresh = pitcher.pit_expect_vs(batter.bats).clone();
+        let mut tired = fresh.clone();
+        Game::tire(&mut tired, 130, 110);
+
+        // a pitcher deep past the limit gives up more and gets fewer outs, rates still summing to one
+        assert!((tired.values().sum::<f64>() - fresh.values().sum::<f64>()).abs() < 1e-9);
+        assert!(tired[&Expect::Single] > fresh[&Expect::Single] && tired[&Expect::Walk] > fresh[&Expect::Walk]);
+        assert!(tired[&Expect::Out] < fresh[&Expect::Out]);
+        assert_eq!(tired[&Expect::Strikeout], fresh[&Expect::Strikeout]);
+    }
+
+    #[test]
+    fn test_events() {
+        let mut game = Game::new(1, 2);
+        let mut boxscore = EventSink::new(false);
+        Game::record_stat(&mut boxscore, 10, Stat::B1b, None);
+        Game::record_stat(&mut boxscore, 11, Stat::Bhr, None);
+        Game::record_stat(&mut boxscore, 10, Stat::Br, None);
+        Game::record_stat(&mut boxscore, 11, Stat::Br, None);
+        Game::record_stat(&mut boxscore, 12, Stat::Bso, None);
+        Game::record_stat(&mut boxscore, 13, Stat::Fe, None);
+        Game::record_stat(&mut boxscore, 14, Stat::Bo, None);
+        Game::record_stat(&mut boxscore, 15, Stat::Bgidp, None);
+        Game::record_stat(&mut boxscore, 20, Stat::B2b, None);
+        game.playbyplay = boxscore.log().clone();
+
+        let events = game.events().collect::<Vec<_>>();
+        assert_eq!(events.len(), 9);
+        assert!(events[..8].iter().all(|o| o.inning == 1 && o.tophalf));
+
+        // the error means the batter reached, so only the strikeout counts
+        assert_eq!(events[6].outs, 1);
+        assert_eq!(events[6].error, Some(ErrorType::Fielding));
+        assert_eq!(events[7].outs, 1);
+
+        assert_eq!((events[4].away_r, events[4].home_r), (2, 0));
+
+        // the double play ends the half inning
+        let last = &events[8];
+        assert_eq!((last.inning, last.tophalf, last.outs), (1, false, 0));
+        assert_eq!(last.event.player, 20);
+    }
+
+    #[test]
+    fn test_replay() {
+        let mut rng = StdRng::seed_from_u64(2049);
+        let year = 2049;
+        let (mut teams, mut players) = setup_teams(2, year, &Data::new(), &mut rng);
+        let (teams_before, players_before) = (teams.clone(), players.clone());
+
+        let mut game = Game::new(1, 2);
+        game.sim(&mut teams, &mut players, year, 1.0, None, rng.gen());
+
+        let replay = game.replay(&teams_before, &players_before, year, 1.0);
+        assert_eq!(replay.seed, game.seed);
+        assert!(replay.playbyplay == game.playbyplay);
+        assert_eq!((replay.home.r, replay.away.r), (game.home.r, game.away.r));
+    }
+
+    #[test]
+    fn test_boxscore_text() {
+        let mut rng = StdRng::seed_from_u64(2049);
+        let year = 2049;
+        let (mut teams, mut players) = setup_teams(2, year, &Data::new(), &mut rng);
+
+        let mut game = Game::new(1, 2);
+        game.sim(&mut teams, &mut players, year, 1.0, None, rng.gen());
+
+        let text = game.to_boxscore_text(&teams, &players);
+        let lines = text.lines().collect::<Vec<_>>();
+        let boxscore = game.boxscore();
+
+        let innings = boxscore.away_runs.len().max(boxscore.home_runs.len());
+        assert!(innings >= 9);
+        assert_eq!(boxscore.rhe(false), [game.away.r as u32, game.away.h as u32, game.away.e as u32]);
+        assert_eq!(boxscore.rhe(true), [game.home.r as u32, game.home.h as u32, game.home.e as u32]);
+        assert!(boxscore.away_hits.len() <= innings && boxscore.home_errors.len() <= innings);
+        // the home side's errors all came while the away side batted
+        let fielding = game.events().filter(|o| o.tophalf && matches!(o.event.event, Stat::Fe | Stat::Fte)).count() as u32;
+        assert_eq!(fielding, game.home.e as u32);
+        assert!(lines[0].trim_start().starts_with("1  2  3"));
+        assert!(lines[0].ends_with("R   H   E LOB"));
+        assert!(lines[1].starts_with(teams[&2].abbr()));
+        assert!(lines[1].ends_with(&format!("{:>4}{:>4}{:>4}{:>4}", game.away.r, game.away.h, game.away.e, game.away.lob)));
+        assert!(lines[2].starts_with(teams[&1].abbr()));
+        assert!(lines[3].starts_with("W: "));
+
+        // nine batters per side plus a header line
+        assert_eq!(lines.iter().filter(|o| o.contains(" Batters")).count(), 2);
+        assert_eq!(lines.iter().filter(|o| o.starts_with(|c: char| c.is_ascii_digit()) && o.contains(". ")).count(), 18);
+
+        let pitchers = game.away.pitcher_record.len() + game.home.pitcher_record.len();
+        assert_eq!(lines.iter().filter(|o| o.contains(" Pitchers")).count(), 2);
+        assert_eq!(lines.len(), 5 + game.walkoff as usize + 2 * 11 + 2 * 2 + pitchers);
+
+        let recap = game.recap(&teams, &players);
+        let (w_r, l_r) = (game.home.r.max(game.away.r), game.home.r.min(game.away.r));
+        assert!(recap.starts_with("The "));
+        assert!(recap.contains(&format!(", {}-{}.", w_r, l_r)));
+        assert!(recap.contains(" got the win and "));
+    }
+
+    #[test]
+    fn test_custom_lineup() {
+        let mut rng = StdRng::seed_from_u64(2049);
+        let year = 2049;
+        let (mut teams, mut players) = setup_teams(2, year, &Data::new(), &mut rng);
+
+        let team = teams.get_mut(&1).unwrap();
+        let mut lineup = team.auto_lineup(&players);
+        lineup.reverse();
+        assert!(team.is_valid_lineup(&lineup, &players));
+        team.lineup = Some(lineup);
+
+        let mut scoreboard = Scoreboard::new(1);
+        Game::setup_bo(&mut players, &mut teams, &mut scoreboard, &mut EventSink::new(false), year, &mut StdRng::seed_from_u64(rng.gen()));
+        assert!(scoreboard.bo.iter().zip(lineup.iter()).all(|(o, id)| o.pos == players[id].pos));
+
+        // a player no longer on the team falls back to the automatic order
+        let team = teams.get_mut(&1).unwrap();
+        team.players.retain(|o| *o != lineup[0]);
+        assert!(!team.is_valid_lineup(&lineup, &players));
+    }
+
+    #[test]
+    fn test_lefty_specialist() {
+        let mut rng = StdRng::seed_from_u64(2049);
+        let year = 2049;
+        let (mut teams, mut players) = setup_teams(2, year, &Data::new(), &mut rng);
+        let mut game = Game::new(1, 2);
+        let mut boxscore = EventSink::new(false);
+        game.setup_game(&mut players, &mut teams, &mut boxscore, year, &mut StdRng::seed_from_u64(rng.gen()));
+        game.home.pitcher_bf = 20;
+
+        // a lefty slugger is due up in a tie game, and the starter can't get lefties out
+        let batter = players.get_mut(&game.away.bo[game.away.ab].player).unwrap();
+        batter.bats = Handedness::Left;
+        for expect in [&mut batter.bat_expect.0, &mut batter.bat_expect.1].iter_mut() {
+            expect.insert(Expect::HomeRun, 0.1);
+        }
+        players.get_mut(&game.home.pitcher).unwrap().pit_expect.0.insert(Expect::Walk, 0.2);
+
+        let specialist = *teams[&1].players.iter().find(|o| players[o].pos == Position::ShortRelief).unwrap();
+        for (_, value) in players.get_mut(&specialist).unwrap().pit_expect.0.iter_mut().filter(|(k, _)| !matches!(k, Expect::Strikeout | Expect::Out)) {
+            *value = 0.0;
+        }
+
+        // too early for a matchup move
+        let inning = Inning { number: 6, half: InningHalf::Top };
+        let starter = game.home.pitcher;
+        game.sub_pitcher(&inning, &mut teams, &mut players, &mut boxscore, &mut StdRng::seed_from_u64(rng.gen()));
+        assert_eq!(game.home.pitcher, starter);
+
+        let inning = Inning { number: 7, half: InningHalf::Top };
+        game.sub_pitcher(&inning, &mut teams, &mut players, &mut boxscore, &mut StdRng::seed_from_u64(rng.gen()));
+        assert_eq!(game.home.pitcher, specialist);
+    }
+
+    #[test]
+    fn test_pinch_hit() {
+        let mut rng = StdRng::seed_from_u64(2049);
+        let year = 2049;
+        let (mut teams, mut players) = setup_teams(2, year, &Data::new(), &mut rng);
+        let mut game = Game::new(1, 2);
+        let mut boxscore = EventSink::new(false);
+        game.setup_game(&mut players, &mut teams, &mut boxscore, year, &mut StdRng::seed_from_u64(rng.gen()));
+
+        // the designated hitter can't reach base, anyone on the bench can take his spot
+        game.away.ab = game.away.bo.iter().position(|o| o.pos == Position::DesignatedHitter).unwrap();
+        let weak = game.away.bo[game.away.ab].player;
+        let batter = players.get_mut(&weak).unwrap();
+        for expect in [&mut batter.bat_expect.0, &mut batter.bat_expect.1] {
+            for (key, value) in expect.iter_mut() {
+                *value = if *key == Expect::Out { 1.0 } else { 0.0 };
+            }
+        }
+        assert!(teams[&2].iter_players(&players).any(|o| !o.1.pos.is_pitcher() && game.away.bo.iter().all(|b| b.player != o.0)));
+
+        let inning = Inning { number: 6, half: InningHalf::Top };
+        game.pinch_hit(&inning, &teams, &players, &mut boxscore);
+        assert_eq!(game.away.bo[game.away.ab].player, weak);
+
+        let inning = Inning { number: 8, half: InningHalf::Top };
+        game.pinch_hit(&inning, &teams, &players, &mut boxscore);
+        let sub = game.away.bo[game.away.ab].player;
+        assert!(sub != weak);
+        assert_eq!(game.replaced(sub), Some(weak));
+        let event = boxscore.log().last().unwrap();
+        assert!(event.player == sub && event.event == Stat::G && event.target == Some(Position::DesignatedHitter));
+    }
+
+    #[test]
+    fn test_defensive_sub() {
+        let mut rng = StdRng::seed_from_u64(2049);
+        let year = 2049;
+        let (mut teams, mut players) = setup_teams(2, year, &Data::new(), &mut rng);
+        let mut game = Game::new(1, 2);
+        let mut boxscore = EventSink::new(false);
+        game.setup_game(&mut players, &mut teams, &mut boxscore, year, &mut StdRng::seed_from_u64(rng.gen()));
+
+        // a butcher in the field with a sure-handed backup on the bench
+        let idx = game.home.bo.iter().position(|o| o.pos != Position::DesignatedHitter).unwrap();
+        let starter = game.home.bo[idx].clone();
+        let glove = teams[&1].iter_players(&players).find(|o| !o.1.pos.is_pitcher() && !game.home.used.contains(&o.0)).unwrap().0;
+        players.get_mut(&starter.player).unwrap().error_rate = 0.5;
+        let backup = players.get_mut(&glove).unwrap();
+        backup.pos = starter.pos;
+        backup.error_rate = 0.0;
+
+        let inning = Inning { number: 8, half: InningHalf::Top };
+        game.defensive_sub(&inning, &teams, &players, &mut boxscore);
+        assert_eq!(game.home.bo[idx].player, starter.player);
+
+        game.home.r = 3;
+        let seventh = Inning { number: 7, half: InningHalf::Top };
+        game.defensive_sub(&seventh, &teams, &players, &mut boxscore);
+        assert_eq!(game.home.bo[idx].player, starter.player);
+
+        // anyone else on the bench who never errs is just as good
+        game.defensive_sub(&inning, &teams, &players, &mut boxscore);
+        let sub = game.home.bo[idx].player;
+        assert!(sub == glove || players[&sub].error_rate == 0.0);
+        assert_eq!(game.replaced(sub), Some(starter.player));
+        assert!(game.home.used.contains(&sub));
+    }
+
+    #[test]
+    fn test_no_reentry() {
+        let mut rng = StdRng::seed_from_u64(2049);
+        let year = 2049;
+        let (mut teams, mut players) = setup_teams(2, year, &Data::new(), &mut rng);
+        let mut game = Game::new(1, 2);
+        let mut boxscore = EventSink::new(false);
+        game.setup_game(&mut players, &mut teams, &mut boxscore, year, &mut StdRng::seed_from_u64(rng.gen()));
+        game.away.ab = game.away.bo.iter().position(|o| o.pos == Position::DesignatedHitter).unwrap();
+        let starter = game.away.bo[game.away.ab].player;
+
+        // whoever is in the slot can't reach base, while everyone already taken out is the best bat around
+        let inning = Inning { number: 8, half: InningHalf::Top };
+        let mut removed = HashSet::new();
+        for _ in 0..5 {
+            let current = game.away.bo[game.away.ab].player;
+            for (id, player) in players.iter_mut() {
+                let out = if *id == current { 1.0 } else if removed.contains(id) { 0.0 } else { 0.7 };
+                for expect in [&mut player.bat_expect.0, &mut player.bat_expect.1] {
+                    for (key, value) in expect.iter_mut() {
+                        *value = if *key == Expect::Out { out } else { (1.0 - out) / 7.0 };
+                    }
+                }
+            }
+            game.pinch_hit(&inning, &teams, &players, &mut boxscore);
+            let batter = game.away.bo[game.away.ab].player;
+            if batter == current {
+                break;
+            }
+            removed.insert(current);
+            assert!(!removed.contains(&batter));
+        }
+        assert!(removed.contains(&starter));
+        assert_eq!(game.away.subs.len(), removed.len());
+    }
+
+    #[test]
+    fn test_rotation_ace() {
+        let mut rng = StdRng::seed_from_u64(2049);
+        let (mut teams, mut players) = setup_teams(2, 2049, &Data::new(), &mut rng);
+
+        let team = teams.get_mut(&1).unwrap();
+        let ace = team.rotation[3];
+        team.rotation.swap(0, 3);
+
+        let mut scoreboard = Scoreboard::new(1);
+        Game::setup_pitcher(&mut players, &mut teams, &mut scoreboard, &mut EventSink::new(false));
+        assert_eq!(scoreboard.pitcher, ace);
+        assert_eq!(teams[&1].rotation[4], ace);
+    }
+
+    #[test]
+    fn test_slot_stats() {
+        let mut rng = StdRng::seed_from_u64(2049);
+        let year = 2049;
+        let (mut teams, mut players) = setup_teams(2, year, &Data::new(), &mut rng);
+
+        let mut game = Game::new(1, 2);
+        game.sim(&mut teams, &mut players, year, 1.0, None, rng.gen());
+
+        for (slot, def) in game.away.bo.iter().enumerate() {
+            let slot_stats = players[&def.player].get_slot_stats();
+            assert_eq!(slot_stats.len(), 1);
+            assert_eq!(slot_stats[0].0, slot);
+            assert_eq!(slot_stats[0].1.b_pa, players[&def.player].get_stats().b_pa);
+            assert!(players[&def.player].get_risp_stats().b_pa <= slot_stats[0].1.b_pa);
+        }
+
+        let risp = game.playbyplay.iter().filter(|o| o.risp).count() as u32;
+        let batters = game.away.bo.iter().chain(game.home.bo.iter()).map(|o| o.player)
+            .chain(game.away.subs.iter().chain(game.home.subs.iter()).map(|o| o.0));
+        assert_eq!(risp, batters.map(|o| players[&o].get_risp_stats().b_pa).sum::<u32>());
+    }
+
+    #[test]
+    fn test_single_advance() {
+        let runner = |id| Some(RunnerInfo { id, ..RunnerInfo::default() });
+        let on_base = |scoreboard: &Scoreboard| scoreboard.onbase[1..].iter().map(|o| o.map(|o| o.id)).collect::<Vec<_>>();
+
+        // a runner from second scores on a single to right but holds at third on one to left
+        let mut right = Scoreboard::new(1);
+        right.onbase[2] = runner(2);
+        right.advance_on_single(Position::RightField, 9, 1, true, |_| None);
+        assert_eq!(right.runs_in.len(), 1);
+        assert_eq!(on_base(&right), vec![Some(9), None, None]);
+
+        let mut left = Scoreboard::new(1);
+        left.onbase[2] = runner(2);
+        left.advance_on_single(Position::LeftField, 9, 1, true, |_| None);
+        assert!(left.runs_in.is_empty());
+        assert_eq!(on_base(&left), vec![Some(9), None, Some(2)]);
+
+        // first to third on a single to right
+        let mut right = Scoreboard::new(1);
+        right.onbase[1] = runner(1);
+        right.advance_on_single(Position::RightField, 9, 1, true, |_| None);
+        assert_eq!(on_base(&right), vec![Some(9), None, Some(1)]);
+
+        // runners on first and second each move up one on a single to left
+        let mut left = Scoreboard::new(1);
+        left.onbase[1] = runner(1);
+        left.onbase[2] = runner(2);
+        left.advance_on_single(Position::LeftField, 9, 1, true, |_| None);
+        assert!(left.runs_in.is_empty());
+        assert_eq!(on_base(&left), vec![Some(9), Some(1), Some(2)]);
+
+        // an infield single only moves the runners who are forced
+        let mut infield = Scoreboard::new(1);
+        infield.onbase[1] = runner(1);
+        infield.onbase[3] = runner(3);
+        assert!(infield.advance_on_single(Position::ShortStop, 9, 1, true, |_| Some(true)).is_none());
+        assert!(infield.runs_in.is_empty());
+        assert_eq!(on_base(&infield), vec![Some(9), Some(1), Some(3)]);
+        // an aggressive runner from first takes third on a single to left, or is thrown out there
+        let mut safe = Scoreboard::new(1);
+        safe.onbase[1] = runner(1);
+        assert!(safe.advance_on_single(Position::LeftField, 9, 1, true, |_| Some(true)).is_none());
+        assert_eq!(on_base(&safe), vec![Some(9), None, Some(1)]);
+
+        let mut out = Scoreboard::new(1);
+        out.onbase[1] = runner(1);
+        out.onbase[3] = runner(3);
+        assert_eq!(out.advance_on_single(Position::LeftField, 9, 1, true, |_| Some(false)), Some((1, 3)));
+        assert_eq!(out.runs_in.len(), 1);
+        assert_eq!(on_base(&out), vec![Some(9), None, None]);
+    }
+
+    #[test]
+    fn test_leverage() {
+        assert!(Game::leverage(9, 0, 0) == Leverage::High);
+        assert!(Game::leverage(8, -2, 1) == Leverage::High);
+        assert!(Game::leverage(10, 1, 0) == Leverage::High);
+        assert!(Game::leverage(8, -3, 1) == Leverage::Medium);
+        assert!(Game::leverage(2, 0, 0) == Leverage::Medium);
+        assert!(Game::leverage(2, 3, 2) == Leverage::Low);
+        assert!(Game::leverage(9, -5, 3) == Leverage::Low);
+
+        let mut rng = StdRng::seed_from_u64(2049);
+        let year = 2049;
+        let (mut teams, mut players) = setup_teams(2, year, &Data::new(), &mut rng);
+        let mut game = Game::new(1, 2);
+        game.sim(&mut teams, &mut players, year, 1.0, None, rng.gen());
+
+        let high = game.playbyplay.iter().filter(|o| o.leverage == Leverage::High).count() as u32;
+        let batters = game.away.bo.iter().chain(game.home.bo.iter()).map(|o| o.player)
+            .chain(game.away.subs.iter().chain(game.home.subs.iter()).map(|o| o.0));
+        assert_eq!(high, batters.map(|o| players[&o].get_clutch_stats().b_pa).sum::<u32>());
+    }
+
+    #[test]
+    fn test_putouts() {
+        let mut rng = StdRng::seed_from_u64(2049);
+        let year = 2049;
+        let (mut teams, mut players) = setup_teams(2, year, &Data::new(), &mut rng);
+
+        let mut game = Game::new(1, 2);
+        game.sim(&mut teams, &mut players, year, 1.0, None, rng.gen());
+
+        // every out is credited to exactly one fielder
+        let mut total = Stats::default();
+        for stream in game.boxscore().stat_map.values() {
+            total.compile(&Stats::compile_stats(stream));
+        }
+        assert_eq!(total.f_po, total.p_o);
+        assert!(total.f_fpct <= 1000);
+
+        // and every run goes to the run support of whoever was pitching for the scoring side
+        assert_eq!(total.p_rs, (game.away.r + game.home.r) as u32);
+    }
 }
@@ -1,14 +1,17 @@
-use std::collections::HashMap;
+use std::collections::{HashMap, HashSet};
 
-use rand::rngs::ThreadRng;
+use rand::Rng;
+use rand::seq::IteratorRandom;
 
 use crate::data::Data;
-use crate::player::{collect_all_active, generate_players, PlayerId, PlayerMap};
+use crate::game::{Game, RunExpectancy};
+use crate::player::{collect_all_active, generate_players, AgeProfile, PlayerId, PlayerMap};
+use crate::playoffs::{PlayoffConfig, Playoffs};
 use crate::schedule::Schedule;
 use crate::stat::{Stat, Stats};
-use crate::team::{TeamId, TeamMap};
+use crate::team::{Team, TeamId, TeamMap, DEFAULT_ROSTER_SIZE};
 
-#[derive(Default)]
+#[derive(Clone, Copy, Default)]
 pub(crate) struct LeagueRecord {
     pub(crate) player_id: PlayerId,
     pub(crate) team_id: TeamId,
@@ -16,18 +19,167 @@ pub(crate) struct LeagueRecord {
     pub(crate) year: u32,
 }
 
+// most by anyone over a whole career, retired players included
+#[derive(Clone, Copy, Default)]
+pub(crate) struct CareerRecord {
+    pub(crate) player_id: PlayerId,
+    pub(crate) record: u32,
+}
+
+// best line by a single player in one regular season game
+#[derive(Clone, Copy, Default)]
+pub(crate) struct GameRecord {
+    pub(crate) player_id: PlayerId,
+    pub(crate) team_id: TeamId,
+    pub(crate) record: u32,
+    pub(crate) year: u32,
+    pub(crate) day: usize,
+}
+
+#[derive(Clone)]
+pub(crate) struct Milestone {
+    pub(crate) player_id: PlayerId,
+    pub(crate) stat: Stat,
+    pub(crate) total: u32,
+    pub(crate) year: u32,
+    pub(crate) league: u32,
+    pub(crate) day: usize,
+}
+
+#[derive(Clone)]
+pub(crate) enum LeagueEvent {
+    Milestone(Milestone),
+    Record(u32, Stat, LeagueRecord),
+    Champion(u32, TeamId, u32),
+    // new league, team, old league, year
+    Promoted(u32, TeamId, u32, u32),
+    Relegated(u32, TeamId, u32, u32),
+    Relocated(u32, TeamId, String, u32),
+    Expansion(u32, TeamId, u32),
+    // winner, loser
+    Tiebreaker(u32, TeamId, TeamId, u32),
+    // the league and team of the player's last season
+    Retired(u32, TeamId, PlayerId, u32),
+}
+
+impl LeagueEvent {
+    pub(crate) fn involves(&self, team_id: TeamId, roster: &[PlayerId]) -> bool {
+        match self {
+            LeagueEvent::Milestone(milestone) => roster.contains(&milestone.player_id),
+            LeagueEvent::Record(_, _, record) => record.team_id == team_id,
+            LeagueEvent::Champion(_, id, _) |
+            LeagueEvent::Promoted(_, id, _, _) |
+            LeagueEvent::Relegated(_, id, _, _) |
+            LeagueEvent::Relocated(_, id, _, _) |
+            LeagueEvent::Expansion(_, id, _) |
+            LeagueEvent::Retired(_, id, _, _) => *id == team_id,
+            LeagueEvent::Tiebreaker(_, winner, loser, _) => *winner == team_id || *loser == team_id,
+        }
+    }
+
+    pub(crate) fn year(&self) -> u32 {
+        match self {
+            LeagueEvent::Milestone(milestone) => milestone.year,
+            LeagueEvent::Record(_, _, record) => record.year,
+            LeagueEvent::Champion(_, _, year) |
+            LeagueEvent::Promoted(_, _, _, year) |
+            LeagueEvent::Relegated(_, _, _, year) |
+            LeagueEvent::Relocated(_, _, _, year) |
+            LeagueEvent::Expansion(_, _, year) |
+            LeagueEvent::Tiebreaker(_, _, _, year) |
+            LeagueEvent::Retired(_, _, _, year) => *year,
+        }
+    }
+}
+
+// a retirement only makes the news after a career reaching one of these
+const NOTABLE_CAREER: [(Stat, u32); 4] = [
+    (Stat::Bhr, 300),
+    (Stat::Bh, 2000),
+    (Stat::Pw, 150),
+    (Stat::Pso, 2000),
+];
+
+const MILESTONES: [(Stat, u32); 4] = [
+    (Stat::Bhr, 500),
+    (Stat::Bh, 3000),
+    (Stat::Pw, 300),
+    (Stat::Pso, 3000),
+];
+
+// the round number reached when a total goes from before to after, if any
+fn milestone_crossed(before: u32, after: u32, step: u32) -> Option<u32> {
+    if before / step < after / step { Some(after / step * step) } else { None }
+}
+
 #[derive(Default)]
+pub(crate) struct ScoringReport {
+    pub(crate) games: u32,
+    // runs scored in each inning, extra innings included
+    pub(crate) by_inning: Vec<u32>,
+    // number of games with each run total
+    pub(crate) per_game: Vec<u32>,
+}
+
+// one line of the standings table, pct stored the same way as in Stats
+#[derive(Clone, Copy)]
+pub(crate) struct StandingRow {
+    pub(crate) rank: usize,
+    pub(crate) team_id: TeamId,
+    pub(crate) wins: u32,
+    pub(crate) losses: u32,
+    pub(crate) pct: u32,
+    // behind the first place team
+    pub(crate) games_behind: f64,
+    pub(crate) run_diff: i32,
+}
+
+// the league-wide offensive environment for one season, rates stored the same way as in Stats
+#[derive(Clone)]
+pub(crate) struct LeagueSeasonStats {
+    pub(crate) year: u32,
+    pub(crate) games: u32,
+    pub(crate) avg: u32,
+    pub(crate) era: u32,
+    pub(crate) runs: u32,
+    pub(crate) hr: u32,
+    // the baseline for neutralizing the season's player lines
+    pub(crate) total: Stats,
+}
+
+impl LeagueSeasonStats {
+    // both teams combined
+    pub(crate) fn per_game(&self, total: u32) -> f64 {
+        total as f64 / self.games.max(1) as f64
+    }
+}
+
+#[derive(Clone, Default)]
 pub(crate) struct League {
     id: u32,
     pub(crate) teams: Vec<TeamId>,
     pub(crate) schedule: Schedule,
     pub(crate) cur_idx: usize,
     pub(crate) records: HashMap<Stat, Option<LeagueRecord>>,
-
+    pub(crate) single_game_records: HashMap<Stat, Option<GameRecord>>,
+    pub(crate) playoff_config: PlayoffConfig,
+    // multiplier on the chance of reaching base, 1.0 is the normal league average
+    pub(crate) run_env: f64,
+    // regular season only, see Game::mercy_rule
+    pub(crate) mercy_rule: Option<(u8, u8)>,
+    // the same for every league, replacement and expansion players are drawn from it
+    pub(crate) ages: AgeProfile,
+    // designated hitter rule; games always bat a DH for now, so this only decides whether pitchers count as batters on the leaderboards
+    pub(crate) dh: bool,
+    // the most recent postseason, kept through the following season
+    pub(crate) playoffs: Option<Playoffs>,
+    pub(crate) history: Vec<LeagueSeasonStats>,
+    // the season so far, regular season games only
+    pub(crate) run_expectancy: RunExpectancy,
 }
 
 impl League {
-    pub(crate) fn new(id: u32, team_count: usize, remaining_teams: &mut Vec<TeamId>, rng: &mut ThreadRng) -> League {
+    pub(crate) fn new(id: u32, team_count: usize, remaining_teams: &mut Vec<TeamId>, rng: &mut impl Rng) -> League {
         let mut teams = Vec::new();
         for _ in 0..team_count {
             if let Some(team) = remaining_teams.pop() {
@@ -41,37 +193,255 @@ impl League {
             id,
             teams,
             schedule,
+            run_env: 1.0,
+            dh: true,
             ..Self::default()
         }
     }
 
-    pub(crate) fn reset_schedule(&mut self, teams: &mut TeamMap, rng: &mut ThreadRng) {
+    pub(crate) fn reset_schedule(&mut self, teams: &mut TeamMap, rng: &mut impl Rng) {
         for team_id in &self.teams {
             let team = teams.get_mut(team_id).unwrap();
             team.results.reset();
         }
         self.schedule = Schedule::new(&self.teams, rng);
         self.cur_idx = 0;
+        self.run_expectancy = RunExpectancy::default();
     }
 
-    pub(crate) fn sim(&mut self, team_data: &mut TeamMap, players: &mut PlayerMap, year: u32, mut rng: &mut ThreadRng) -> bool {
+    pub(crate) fn sim(&mut self, team_data: &mut TeamMap, players: &mut PlayerMap, events: &mut Vec<LeagueEvent>, year: u32, fast: bool, rng: &mut impl Rng) -> bool {
         if self.cur_idx < self.schedule.games.len() {
             let teams = self.teams.len();
             for idx in self.cur_idx..(self.cur_idx + (teams / 2)) {
                 if let Some(game) = self.schedule.games.get_mut(idx) {
-                    game.sim(team_data, players, year, &mut rng);
+                    game.fast = fast;
+                    game.sim(team_data, players, year, self.run_env, self.mercy_rule, rng.gen());
+                    self.run_expectancy.add(&game.run_expectancy);
+
+                    let boxscore = game.boxscore();
+                    let day = idx / (teams / 2) + 1;
+                    for (player_id, stream) in boxscore.stat_map {
+                        let game_stats = Stats::compile_stats(&stream);
+                        let team_id = if team_data.get(&game.home.id).unwrap().players.contains(&player_id) { game.home.id } else { game.away.id };
+                        check_game_record(&mut self.single_game_records, &game_stats, player_id, team_id, year, day);
+
+                        // the game is already part of the career totals, so back it out to find the crossing
+                        let career = players.get(&player_id).unwrap().career_stats();
+                        for (stat, step) in MILESTONES.iter() {
+                            let after = career.get_stat(*stat);
+                            let before = after - game_stats.get_stat(*stat);
+                            if let Some(total) = milestone_crossed(before, after, *step) {
+                                events.push(LeagueEvent::Milestone(Milestone { player_id, stat: *stat, total, year, league: self.id, day }));
+                            }
+                        }
+                    }
                 }
             }
             self.cur_idx += teams / 2;
             return true;
         }
 
-        self.teams.sort_by_key(|o| team_data.get(o).unwrap().get_losses());
+        self.teams = self.final_standings(team_data);
 
         false
     }
+
+    // one game playoffs between the teams either side of each cutoff that finished level, the winner takes the higher spot
+    pub(crate) fn tiebreakers(&mut self, cutoffs: &[usize], teams: &mut TeamMap, players: &mut PlayerMap, year: u32, rng: &mut impl Rng) -> Vec<(TeamId, TeamId)> {
+        let mut results = Vec::new();
+        for &cutoff in cutoffs {
+            if cutoff == 0 || cutoff >= self.teams.len() {
+                continue;
+            }
+            let (above, below) = (self.teams[cutoff - 1], self.teams[cutoff]);
+            if teams.get(&above).unwrap().win_pct() != teams.get(&below).unwrap().win_pct() {
+                continue;
+            }
+
+            let mut game = Game::new(above, below);
+            game.postseason = true;
+            game.sim(teams, players, year, self.run_env, None, rng.gen());
+            if game.away.r > game.home.r {
+                self.teams.swap(cutoff - 1, cutoff);
+                results.push((below, above));
+            } else {
+                results.push((above, below));
+            }
+        }
+        results
+    }
+
+    pub(crate) fn games_remaining(&self, team_id: TeamId) -> u32 {
+        let start = self.cur_idx.min(self.schedule.games.len());
+        self.schedule.games[start..].iter().filter(|o| o.home.id == team_id || o.away.id == team_id).count() as u32
+    }
+
+    // each game log is ~300 events at 16 bytes; dropping them keeps the line score and decisions but loses the game log and batting lines
+    pub(crate) fn trim_playbyplay(&mut self, keep_days: usize) {
+        let keep = keep_days * (self.teams.len() / 2);
+        let end = self.cur_idx.min(self.schedule.games.len()).saturating_sub(keep);
+        for game in &mut self.schedule.games[..end] {
+            if game.has_playbyplay() {
+                game.discard_playbyplay();
+            }
+        }
+    }
+
+    // games won against any of `opponents` so far this season
+    pub(crate) fn head_to_head(&self, team_id: TeamId, opponents: &[TeamId]) -> u32 {
+        let end = self.cur_idx.min(self.schedule.games.len());
+        self.schedule.games[..end].iter().filter(|o| {
+            (o.home.id == team_id && opponents.contains(&o.away.id) && o.home.r > o.away.r) ||
+                (o.away.id == team_id && opponents.contains(&o.home.id) && o.away.r > o.home.r)
+        }).count() as u32
+    }
+
+    // the one canonical order, shown on the standings screen and used for playoffs and promotion
+    // best record first, then run differential, then wins against the other teams still level
+    pub(crate) fn final_standings(&self, teams: &TeamMap) -> Vec<TeamId> {
+        let key = |o: &TeamId| {
+            let team = teams.get(o).unwrap();
+            (team.win_pct(), team.results.run_diff())
+        };
+        let mut standings = self.teams.clone();
+        standings.sort_by_key(|o| std::cmp::Reverse(key(o)));
+
+        let mut start = 0;
+        while start < standings.len() {
+            let end = start + standings[start..].iter().take_while(|o| key(o) == key(&standings[start])).count();
+            let level = standings[start..end].to_vec();
+            standings[start..end].sort_by_key(|o| std::cmp::Reverse(self.head_to_head(*o, &level)));
+            start = end;
+        }
+        standings
+    }
+
+    pub(crate) fn standings(&self, teams: &TeamMap) -> Vec<StandingRow> {
+        let order = self.final_standings(teams);
+        let leader = order.first().map(|o| teams.get(o).unwrap());
+        order.iter().enumerate().map(|(idx, team_id)| {
+            let team = teams.get(team_id).unwrap();
+            let (wins, losses) = (team.get_wins(), team.get_losses());
+            let games_behind = leader.map_or(0.0, |o| ((o.get_wins() as f64 - wins as f64) + (losses as f64 - o.get_losses() as f64)) / 2.0);
+            StandingRow {
+                rank: idx + 1,
+                team_id: *team_id,
+                wins,
+                losses,
+                pct: (wins * 1000).checked_div(wins + losses).unwrap_or(0),
+                games_behind,
+                run_diff: team.results.run_diff(),
+            }
+        }).collect()
+    }
+
+    pub(crate) fn next_game_day(&self, team_id: TeamId) -> Option<usize> {
+        let start = self.cur_idx.min(self.schedule.games.len());
+        let idx = self.schedule.games[start..].iter().position(|o| o.home.id == team_id || o.away.id == team_id)?;
+        Some((start + idx) / (self.teams.len() / 2))
+    }
+
+    // wins plus opponent losses still needed to guarantee a top `spots` finish; None if already out of reach
+    pub(crate) fn magic_number(&self, team_id: TeamId, spots: usize, teams: &TeamMap) -> Option<u32> {
+        let team = teams.get(&team_id).unwrap();
+        let wins = team.get_wins();
+
+        let others = self.teams.iter().filter(|o| **o != team_id).map(|o| (teams.get(o).unwrap().get_wins(), self.games_remaining(*o)));
+        let mut current = others.clone().map(|o| o.0).collect::<Vec<_>>();
+        let mut possible = others.map(|o| o.0 + o.1).collect::<Vec<_>>();
+        current.sort_unstable_by(|a, b| b.cmp(a));
+        possible.sort_unstable_by(|a, b| b.cmp(a));
+
+        if spots == 0 || current.len() < spots {
+            return Some(0);
+        }
+        if wins + self.games_remaining(team_id) < current[spots - 1] {
+            return None;
+        }
+        Some((possible[spots - 1] + 1).saturating_sub(wins))
+    }
+
+    pub(crate) fn scoring_report(&self, team_id: TeamId) -> ScoringReport {
+        let mut report = ScoringReport::default();
+        let played = &self.schedule.games[..self.cur_idx.min(self.schedule.games.len())];
+        for game in played.iter().filter(|o| o.home.id == team_id || o.away.id == team_id) {
+            let boxscore = game.boxscore();
+            let (runs, total) = if game.home.id == team_id { (boxscore.home_runs, game.home.r) } else { (boxscore.away_runs, game.away.r) };
+
+            if report.by_inning.len() < runs.len() {
+                report.by_inning.resize(runs.len(), 0);
+            }
+            for (inning, r) in runs.iter().enumerate() {
+                report.by_inning[inning] += *r as u32;
+            }
+
+            if report.per_game.len() <= total as usize {
+                report.per_game.resize(total as usize + 1, 0);
+            }
+            report.per_game[total as usize] += 1;
+            report.games += 1;
+        }
+        report
+    }
+
+    pub(crate) fn total_stats(&self, teams: &TeamMap, players: &PlayerMap) -> Stats {
+        let mut total = Stats::default();
+        for team_id in &self.teams {
+            let team = teams.get(team_id).unwrap();
+            for player_id in &team.players {
+                total.compile(&players.get(player_id).unwrap().get_stats());
+            }
+        }
+        total
+    }
+
+    // best first, lowest for stats like ERA, ties going to the lower player id
+    pub(crate) fn leaders(&self, stat: Stat, is_batter: bool, qualified_only: bool, teams: &TeamMap, players: &PlayerMap) -> Vec<(PlayerId, u32)> {
+        let league_total = self.total_stats(teams, players);
+        let mut leaders = Vec::new();
+        for team_id in &self.teams {
+            let team = teams.get(team_id).unwrap();
+            let games = team.results.games();
+            // without a DH pitchers take their turn at bat, so they're batters too
+            for (player_id, player) in team.iter_players(players).filter(|o| if is_batter { !self.dh || !o.1.pos.is_pitcher() } else { o.1.pos.is_pitcher() }) {
+                let mut stats = player.get_stats();
+                stats.calc_war(&league_total);
+                if !qualified_only || stat.is_qualified(&stats, games) {
+                    leaders.push((player_id, stats.get_stat(stat)));
+                }
+            }
+        }
+        if stat.is_reverse_sort() {
+            leaders.sort_by_key(|o| (o.1, o.0));
+        } else {
+            leaders.sort_by_key(|o| (std::cmp::Reverse(o.1), o.0));
+        }
+        leaders
+    }
+
+    pub(crate) fn season_stats(&self, teams: &TeamMap, players: &PlayerMap, year: u32) -> LeagueSeasonStats {
+        let total = self.total_stats(teams, players);
+        LeagueSeasonStats {
+            year,
+            games: self.cur_idx.min(self.schedule.games.len()) as u32,
+            avg: total.b_avg,
+            era: total.p_era,
+            runs: total.b_r,
+            hr: total.b_hr,
+            total,
+        }
+    }
+
+    pub(crate) fn team_average(&self, stat: Stat, total: &Stats) -> u32 {
+        let value = total.get_stat(stat);
+        if stat.is_rate() { value } else { value / self.teams.len().max(1) as u32 }
+    }
 }
 
+// new players generated per expansion team to top up the free agent pool
+const EXPANSION_POOL: usize = 30;
+pub(crate) const PROMOTION_COUNT: usize = 4;
+
 pub(crate) const RECORD_STATS: [Stat; 17] = [
     Stat::Bhr,
     Stat::Br,
@@ -92,21 +462,71 @@ pub(crate) const RECORD_STATS: [Stat; 17] = [
     Stat::Pera,
 ];
 
+pub(crate) const CAREER_STATS: [Stat; 8] = [
+    Stat::Bhr,
+    Stat::Bh,
+    Stat::Brbi,
+    Stat::Br,
+    Stat::Bsb,
+    Stat::Pw,
+    Stat::Psv,
+    Stat::Pso,
+];
+
+// ties go to the lower player id, so the holder doesn't depend on map order
+pub(crate) fn career_records(players: &PlayerMap) -> HashMap<Stat, CareerRecord> {
+    let mut records = HashMap::new();
+    for (player_id, player) in players {
+        let career = player.career_stats();
+        for stat in &CAREER_STATS {
+            let value = career.get_stat(*stat);
+            let record = records.entry(*stat).or_insert(CareerRecord::default());
+            if value > record.record || (value == record.record && value > 0 && *player_id < record.player_id) {
+                *record = CareerRecord { player_id: *player_id, record: value };
+            }
+        }
+    }
+    records.retain(|_, o| o.record > 0);
+    records
+}
+
+pub(crate) const SINGLE_GAME_STATS: [Stat; 6] = [
+    Stat::Bhr,
+    Stat::Bh,
+    Stat::Brbi,
+    Stat::Br,
+    Stat::Bsb,
+    Stat::Pso,
+];
+
+// ties go to whoever got there first
+fn check_game_record(records: &mut HashMap<Stat, Option<GameRecord>>, game_stats: &Stats, player_id: PlayerId, team_id: TeamId, year: u32, day: usize) {
+    for stat in &SINGLE_GAME_STATS {
+        let value = game_stats.get_stat(*stat);
+        let record = records.entry(*stat).or_insert(None);
+        if value == 0 || record.is_some_and(|o| o.record >= value) {
+            continue;
+        }
+        *record = Some(GameRecord { player_id, team_id, record: value, year, day });
+    }
+}
+
 fn check_record(records: &mut HashMap<Stat, Option<LeagueRecord>>, player_stats: &Stats, player_id: PlayerId, team_id: TeamId, year: u32, games: u32) {
     for stat in &RECORD_STATS {
         let record = records.entry(*stat).or_insert(None);
         let pval = player_stats.get_stat(*stat);
 
+        // the first holder has to qualify too, the bar scales with the games the team played
+        if !stat.is_qualified(player_stats, games) {
+            continue;
+        }
+
         if let Some(rec) = record {
             let reverse = stat.is_reverse_sort();
 
             if (reverse && rec.record <= pval) || (!reverse && rec.record >= pval) {
                 continue;
             }
-
-            if !stat.is_qualified(player_stats, games) {
-                continue;
-            }
         }
         *record = Some(LeagueRecord {
             record: pval,
@@ -117,17 +537,73 @@ fn check_record(records: &mut HashMap<Stat, Option<LeagueRecord>>, player_stats:
     }
 }
 
-pub(crate) fn end_of_season(leagues: &mut Vec<League>, teams: &mut TeamMap, players: &mut PlayerMap, count: usize, year: u32, data: &Data, rng: &mut ThreadRng) {
+pub(crate) fn end_of_season(leagues: &mut [League], teams: &mut TeamMap, players: &mut PlayerMap, count: usize, year: u32, data: &Data, rng: &mut impl Rng) -> Vec<LeagueEvent> {
+    let mut events = Vec::new();
+
+    // settle ties for playoff spots and across the promotion and relegation lines
+    let league_count = leagues.len();
+    for (league_idx, league) in leagues.iter_mut().enumerate() {
+        let mut cutoffs = vec![league.playoff_config.teams];
+        if league_idx > 0 {
+            cutoffs.push(count);
+        }
+        if league_idx + 1 < league_count {
+            cutoffs.push(league.teams.len().saturating_sub(count));
+        }
+        cutoffs.sort_unstable();
+        cutoffs.dedup();
+
+        for (winner, loser) in league.tiebreakers(&cutoffs, teams, players, year, rng) {
+            events.push(LeagueEvent::Tiebreaker(league.id, winner, loser, year));
+        }
+    }
+
     // record history
     for (league_idx, league) in leagues.iter_mut().enumerate() {
+        let seeds = league.teams.clone();
+        let playoffs = Playoffs::sim(&league.playoff_config, &seeds, teams, players, year, league.run_env, rng);
+        let champion = playoffs.champion;
+        league.playoffs = Some(playoffs);
+
+        let season = league.season_stats(teams, players, year);
+        league.history.push(season);
+
+        let prior = league.records.iter().filter(|(_, o)| o.is_some()).map(|(k, _)| *k).collect::<Vec<_>>();
+
         for (rank, team_id) in league.teams.iter().enumerate() {
-            let team = teams.get_mut(&team_id).unwrap();
+            let team = teams.get_mut(team_id).unwrap();
             for player_id in &team.players {
-                let player = players.get_mut(&player_id).unwrap();
+                let player = players.get_mut(player_id).unwrap();
                 check_record(&mut league.records, &player.get_stats(), *player_id, *team_id, year, team.results.games());
                 player.record_stat_history(year, league.id, *team_id);
             }
-            team.record_results(year, league_idx, rank, team.results);
+            team.update_popularity(league_idx + 1, rank + 1, league.teams.len());
+            team.record_results(year, league_idx, rank, *team_id == champion);
+        }
+
+        events.push(LeagueEvent::Champion(league.id, champion, year));
+        for stat in &RECORD_STATS {
+            if let Some(Some(record)) = league.records.get(stat) {
+                if record.year == year && prior.contains(stat) {
+                    events.push(LeagueEvent::Record(league.id, *stat, *record));
+                }
+            }
+        }
+    }
+
+    // relocate the franchise stuck at the bottom
+    if let Some(lowest) = leagues.last() {
+        let league_num = leagues.len();
+        if let Some(team_id) = lowest.teams.iter().find(|o| teams.get(o).unwrap().should_relocate(league_num, lowest.teams.len())) {
+            let used_locs = teams.values().map(|o| o.loc.clone()).collect::<HashSet<_>>();
+            let used_nicks = teams.values().map(|o| o.nick.clone()).collect::<HashSet<_>>();
+            if let Some(loc) = data.get_unused_loc(&used_locs, rng) {
+                let nick = if rng.gen_bool(0.5) { data.get_unused_nick(&used_nicks, rng) } else { None };
+                let team = teams.get_mut(team_id).unwrap();
+                let old_name = team.name();
+                team.relocate(loc, nick, year + 1);
+                events.push(LeagueEvent::Relocated(lowest.id, *team_id, old_name, year));
+            }
         }
     }
 
@@ -144,8 +620,12 @@ pub(crate) fn end_of_season(leagues: &mut Vec<League>, teams: &mut TeamMap, play
             promoted.push(leagues[lower].teams.remove(0));
         }
 
+        for team_id in &promoted {
+            events.push(LeagueEvent::Promoted(leagues[upper].id, *team_id, leagues[lower].id, year));
+        }
         leagues[upper].teams.append(&mut promoted);
         for rel in relegated {
+            events.push(LeagueEvent::Relegated(leagues[lower].id, rel, leagues[upper].id, year));
             leagues[lower].teams.insert(0, rel);
         }
     }
@@ -162,18 +642,26 @@ pub(crate) fn end_of_season(leagues: &mut Vec<League>, teams: &mut TeamMap, play
 
     // retire players
     let mut retired = 0;
-    for player in players.values_mut().filter(|o| o.active && o.should_retire(year, rng)) {
+    for (player_id, player) in players.iter_mut().filter(|o| o.1.active && o.1.should_retire(year, rng)) {
         player.active = false;
         //println!("[Retired] {} Age: {}", player.fullname(), player.age(year));
         retired += 1;
+
+        let career = player.career_stats();
+        if let Some(last) = player.historical.last() {
+            if NOTABLE_CAREER.iter().any(|(stat, total)| career.get_stat(*stat) >= *total) {
+                events.push(LeagueEvent::Retired(last.league, last.team, *player_id, year));
+            }
+        }
     }
 
     // age players
     for player in players.values_mut().filter(|o| o.active ) {
-        player.apply_age(year, &data, rng);
+        player.apply_age(year, data, rng);
     }
 
-    generate_players(players, retired, year, &data, rng);
+    let ages = leagues.first().map(|o| o.ages).unwrap_or_default();
+    generate_players(players, retired, year, data, &ages, rng);
 
     // collect available players
     let mut available = collect_all_active(players);
@@ -186,4 +674,585 @@ pub(crate) fn end_of_season(leagues: &mut Vec<League>, teams: &mut TeamMap, play
     for team in teams.values_mut() {
         team.populate(&mut available, players);
     }
+
+    events
+}
+
+// new teams join the lowest league, stocked from free agents plus one exposed player per existing team
+pub(crate) fn expand(leagues: &mut [League], teams: &mut TeamMap, players: &mut PlayerMap, count: usize, year: u32, data: &Data, rng: &mut impl Rng) -> Vec<LeagueEvent> {
+    let mut events = Vec::new();
+    let lowest = match leagues.last_mut() {
+        Some(league) => league,
+        None => return events,
+    };
+
+    // the scheduler needs an even number of teams
+    let count = if (lowest.teams.len() + count) % 2 == 1 { count.saturating_sub(1) } else { count };
+
+    // every new team is named before anything changes, so running out of names can't leave the league odd
+    let mut used_locs = teams.values().map(|o| o.loc.clone()).collect::<HashSet<_>>();
+    let mut used_nicks = teams.values().map(|o| o.nick.clone()).collect::<HashSet<_>>();
+    let mut names = Vec::new();
+    while names.len() < count {
+        let (loc, nick) = match (data.get_unused_loc(&used_locs, rng), data.get_unused_nick(&used_nicks, rng)) {
+            (Some(loc), Some(nick)) => (loc, nick),
+            _ => return events,
+        };
+        used_locs.insert(loc.clone());
+        used_nicks.insert(nick.clone());
+        names.push((loc, nick));
+    }
+    if names.is_empty() {
+        return events;
+    }
+
+    generate_players(players, count * EXPANSION_POOL, year, data, &lowest.ages, rng);
+
+    let mut exposed = Vec::new();
+    for team in teams.values() {
+        if let Some(player_id) = team.players.iter().filter(|o| !team.rotation.contains(o)).choose(rng) {
+            exposed.push(*player_id);
+        }
+    }
+
+    let rostered = teams.values().flat_map(|o| o.players.iter().copied()).collect::<HashSet<_>>();
+    let mut available = collect_all_active(players);
+    available.retain(|k, _| !rostered.contains(k) || exposed.contains(k));
+
+    let first_id = teams.keys().max().copied().unwrap_or(0) + 1;
+    let roster_size = teams.values().map(|o| o.roster_size).max().unwrap_or(DEFAULT_ROSTER_SIZE);
+    let mut drafted = HashSet::new();
+    for (team_id, (loc, nick)) in (first_id..).zip(names) {
+        let mut team = Team::new(loc, nick, year);
+        team.roster_size = roster_size;
+        team.populate(&mut available, players);
+        drafted.extend(team.players.iter().copied());
+
+        teams.insert(team_id, team);
+        lowest.teams.push(team_id);
+        events.push(LeagueEvent::Expansion(lowest.id, team_id, year));
+    }
+
+    // teams that lost a player refill from the remaining free agents
+    available.retain(|k, _| !rostered.contains(k));
+    for (_, team) in teams.iter_mut().filter(|(k, _)| **k < first_id) {
+        team.players.retain(|o| !drafted.contains(o));
+        team.populate(&mut available, players);
+    }
+
+    lowest.reset_schedule(teams, rng);
+
+    events
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::{HashMap, HashSet};
+
+    use rand::SeedableRng;
+    use rand::rngs::StdRng;
+
+    use crate::data::Data;
+    use crate::league::{career_records, check_record, end_of_season, expand, milestone_crossed, League, LeagueEvent, EXPANSION_POOL, PROMOTION_COUNT, SINGLE_GAME_STATS};
+    use crate::player::{generate_players, AgeProfile, PlayerMap};
+    use crate::playoffs::PlayoffConfig;
+    use crate::stat::{HistoricalStats, Stat, Stats};
+    use crate::team::tests::setup_teams;
+    use crate::team::{Team, TeamId};
+
+    #[test]
+    fn test_games_remaining() {
+        let mut rng = StdRng::seed_from_u64(2049);
+        let mut league = League::new(1, 4, &mut vec![1, 2, 3, 4], &mut rng);
+
+        for team_id in &league.teams {
+            assert_eq!(league.games_remaining(*team_id), 24);
+        }
+
+        league.cur_idx += 2;
+        for team_id in &league.teams {
+            assert_eq!(league.games_remaining(*team_id), 23);
+        }
+
+        assert!(league.teams.iter().all(|o| league.next_game_day(*o).is_some_and(|day| day >= 1)));
+
+        league.cur_idx = league.schedule.games.len();
+        for team_id in &league.teams {
+            assert_eq!(league.games_remaining(*team_id), 0);
+            assert_eq!(league.next_game_day(*team_id), None);
+        }
+    }
+
+    #[test]
+    fn test_milestone_crossed() {
+        assert_eq!(milestone_crossed(498, 499, 500), None);
+        assert_eq!(milestone_crossed(499, 500, 500), Some(500));
+        assert_eq!(milestone_crossed(499, 501, 500), Some(500));
+        assert_eq!(milestone_crossed(500, 501, 500), None);
+        assert_eq!(milestone_crossed(2998, 3001, 3000), Some(3000));
+        assert_eq!(milestone_crossed(999, 1000, 500), Some(1000));
+    }
+
+    #[test]
+    fn test_expand() {
+        let mut rng = StdRng::seed_from_u64(2049);
+        let data = Data::new();
+        let year = 2049;
+        let (mut teams, mut players) = setup_teams(8, year - 1, &data, &mut rng);
+        let mut remaining = teams.keys().copied().collect();
+        let mut leagues = vec![League::new(1, 4, &mut remaining, &mut rng), League::new(2, 4, &mut remaining, &mut rng)];
+
+        let events = expand(&mut leagues, &mut teams, &mut players, 2, year, &data, &mut rng);
+        assert_eq!(events.len(), 2);
+        assert_eq!(teams.len(), 10);
+        assert_eq!(leagues[0].teams.len(), 4);
+        assert_eq!(leagues[1].teams.len(), 6);
+        assert_eq!(leagues[1].games_remaining(9), 8 * 5);
+        assert_eq!(teams[&9].history.founded, year);
+
+        // nobody ends up on two rosters and everyone still has a full rotation
+        let mut rostered = HashSet::new();
+        for team in teams.values() {
+            assert!(team.players.iter().all(|o| rostered.insert(*o)));
+            assert!(team.rotation.iter().all(|o| team.players.contains(o)));
+        }
+
+        // an odd expansion is trimmed to keep the league even
+        assert_eq!(expand(&mut leagues, &mut teams, &mut players, 1, year, &data, &mut rng).len(), 0);
+    }
+
+    #[test]
+    fn test_expand_out_of_names() {
+        let mut rng = StdRng::seed_from_u64(2049);
+        let data = Data::new();
+        let year = 2049;
+        let (mut teams, mut players) = setup_teams(8, year - 1, &data, &mut rng);
+        let mut remaining = teams.keys().copied().collect();
+        let mut leagues = vec![League::new(1, 4, &mut remaining, &mut rng), League::new(2, 4, &mut remaining, &mut rng)];
+
+        // defunct franchises hold every nickname but one, so only one of the two new teams could be named
+        let loc = teams[&1].loc.clone();
+        let mut used = teams.values().map(|o| o.nick.clone()).collect::<HashSet<_>>();
+        let taken = used.len();
+        for (offset, nick) in data.get_nicks(&mut used, &mut rng, data.nick_count() - 1).into_iter().skip(taken).enumerate() {
+            teams.insert(100 + offset as TeamId, Team::new(loc.clone(), nick, year - 1));
+        }
+        let (team_count, player_count) = (teams.len(), players.len());
+
+        assert!(expand(&mut leagues, &mut teams, &mut players, 2, year, &data, &mut rng).is_empty());
+        assert_eq!((teams.len(), players.len()), (team_count, player_count));
+        assert_eq!(leagues[1].teams.len(), 4);
+
+        // nothing to add to a league that's already odd
+        leagues[1].teams.pop();
+        assert!(expand(&mut leagues, &mut teams, &mut players, 0, year, &data, &mut rng).is_empty());
+    }
+
+    #[test]
+    fn test_scoring_report() {
+        let mut rng = StdRng::seed_from_u64(2049);
+        let data = Data::new();
+        let (mut teams, mut players) = setup_teams(4, 2049, &data, &mut rng);
+        let mut league = League::new(1, 4, &mut vec![1, 2, 3, 4], &mut rng);
+
+        for _ in 0..3 {
+            league.sim(&mut teams, &mut players, &mut Vec::new(), 2049, false, &mut rng);
+        }
+
+        let report = league.scoring_report(1);
+        assert_eq!(report.games, 24 - league.games_remaining(1));
+        assert_eq!(report.per_game.iter().sum::<u32>(), report.games);
+        // a home team that's ahead skips the bottom of the ninth
+        assert!(report.games == 0 || report.by_inning.len() >= 8);
+
+        let runs = report.per_game.iter().enumerate().map(|(r, o)| r as u32 * o).sum::<u32>();
+        assert_eq!(report.by_inning.iter().sum::<u32>(), runs);
+        assert_eq!(runs, teams[&1].results.runs_for);
+    }
+
+    #[test]
+    fn test_single_game_records() {
+        let mut rng = StdRng::seed_from_u64(2049);
+        let data = Data::new();
+        let (mut teams, mut players) = setup_teams(4, 2049, &data, &mut rng);
+        let mut league = League::new(1, 4, &mut vec![1, 2, 3, 4], &mut rng);
+
+        for _ in 0..5 {
+            league.sim(&mut teams, &mut players, &mut Vec::new(), 2049, false, &mut rng);
+        }
+
+        for stat in &SINGLE_GAME_STATS {
+            let best = league.schedule.games[..league.cur_idx].iter()
+                .flat_map(|o| o.boxscore().stat_map.into_values())
+                .map(|o| Stats::compile_stats(&o).get_stat(*stat))
+                .max()
+                .unwrap();
+            match league.single_game_records[stat] {
+                Some(record) => {
+                    assert_eq!(record.record, best);
+                    assert!(teams[&record.team_id].players.contains(&record.player_id));
+                    assert!((1..=5).contains(&record.day));
+                }
+                None => assert_eq!(best, 0),
+            }
+        }
+    }
+
+    #[test]
+    fn test_trim_playbyplay() {
+        let mut rng = StdRng::seed_from_u64(2049);
+        let data = Data::new();
+        let (mut teams, mut players) = setup_teams(4, 2049, &data, &mut rng);
+        let mut league = League::new(1, 4, &mut vec![1, 2, 3, 4], &mut rng);
+
+        for _ in 0..5 {
+            league.sim(&mut teams, &mut players, &mut Vec::new(), 2049, false, &mut rng);
+        }
+        let before = league.schedule.games.iter().map(|o| o.boxscore()).collect::<Vec<_>>();
+
+        league.trim_playbyplay(2);
+        let games = &league.schedule.games;
+        assert!(games[..6].iter().all(|o| !o.has_playbyplay()));
+        assert!(games[6..10].iter().all(|o| o.has_playbyplay()));
+
+        // the line score and decisions survive
+        for (game, old) in games.iter().zip(before.iter()) {
+            let new = game.boxscore();
+            assert_eq!(new.away_runs, old.away_runs);
+            assert_eq!(new.home_runs, old.home_runs);
+            assert!(new.winner == old.winner && new.loser == old.loser && new.save == old.save);
+        }
+        assert_eq!(league.scoring_report(1).games, 24 - league.games_remaining(1));
+    }
+
+    #[test]
+    fn test_final_standings() {
+        let mut rng = StdRng::seed_from_u64(2049);
+        let data = Data::new();
+        let (mut teams, mut players) = setup_teams(4, 2049, &data, &mut rng);
+        let mut league = League::new(1, 4, &mut vec![4, 3, 2, 1], &mut rng);
+
+        // same losses, but team 4 won more of its games
+        for (team_id, (wins, losses)) in [(1, (1, 2)), (2, (0, 3)), (3, (0, 4)), (4, (3, 2))].iter() {
+            let team = teams.get_mut(team_id).unwrap();
+            for _ in 0..*wins {
+                team.results(5, 1);
+            }
+            for _ in 0..*losses {
+                team.results(1, 5);
+            }
+        }
+
+        league.cur_idx = league.schedule.games.len();
+        assert!(!league.sim(&mut teams, &mut players, &mut Vec::new(), 2049, false, &mut rng));
+        assert_eq!(league.teams, vec![4, 1, 2, 3]);
+    }
+
+    #[test]
+    fn test_standing_rows() {
+        let mut rng = StdRng::seed_from_u64(2049);
+        let data = Data::new();
+        let (mut teams, _) = setup_teams(4, 2049, &data, &mut rng);
+        let league = League::new(1, 4, &mut vec![1, 2, 3, 4], &mut rng);
+
+        for (team_id, (wins, losses)) in [(1, (2, 3)), (2, (5, 0)), (3, (1, 3)), (4, (3, 2))].iter() {
+            let team = teams.get_mut(team_id).unwrap();
+            for _ in 0..*wins {
+                team.results(4, 1);
+            }
+            for _ in 0..*losses {
+                team.results(1, 2);
+            }
+        }
+
+        let rows = league.standings(&teams);
+        assert_eq!(rows.iter().map(|o| o.team_id).collect::<Vec<_>>(), vec![2, 4, 1, 3]);
+        assert_eq!(rows.iter().map(|o| o.rank).collect::<Vec<_>>(), vec![1, 2, 3, 4]);
+        assert_eq!((rows[0].wins, rows[0].losses, rows[0].games_behind, rows[0].run_diff), (5, 0, 0.0, 15));
+        assert_eq!((rows[0].pct, rows[1].pct), (1000, 600));
+        assert_eq!((rows[1].games_behind, rows[2].games_behind, rows[3].games_behind), (2.0, 3.0, 3.5));
+        assert_eq!(rows[3].run_diff, 0);
+        assert!(rows.windows(2).all(|o| o[0].pct >= o[1].pct));
+    }
+
+    #[test]
+    fn test_head_to_head_standings() {
+        let mut rng = StdRng::seed_from_u64(2049);
+        let data = Data::new();
+        let (mut teams, mut players) = setup_teams(4, 2049, &data, &mut rng);
+        let mut league = League::new(1, 4, &mut vec![1, 2, 3, 4], &mut rng);
+        league.sim(&mut teams, &mut players, &mut Vec::new(), 2049, false, &mut rng);
+
+        // level on record and run differential, so the day's result between them decides it
+        for team in teams.values_mut() {
+            team.results = Default::default();
+            team.results(3, 3);
+        }
+        let game = &league.schedule.games[0];
+        let (winner, loser) = if game.home.r > game.away.r { (game.home.id, game.away.id) } else { (game.away.id, game.home.id) };
+        let standings = league.final_standings(&teams);
+        assert!(standings.iter().position(|o| *o == winner) < standings.iter().position(|o| *o == loser));
+        assert_eq!(league.head_to_head(winner, &[loser]), 1);
+        assert_eq!(league.head_to_head(loser, &[winner]), 0);
+    }
+
+    #[test]
+    fn test_leaders() {
+        let mut rng = StdRng::seed_from_u64(2049);
+        let data = Data::new();
+        let (mut teams, mut players) = setup_teams(4, 2049, &data, &mut rng);
+        let mut league = League::new(1, 4, &mut vec![1, 2, 3, 4], &mut rng);
+        for _ in 0..10 {
+            league.sim(&mut teams, &mut players, &mut Vec::new(), 2049, false, &mut rng);
+        }
+
+        let hr = league.leaders(Stat::Bhr, true, false, &teams, &players);
+        let most = teams.values().flat_map(|o| &o.players).filter(|o| !players[o].pos.is_pitcher()).map(|o| players[o].get_stats().b_hr).max().unwrap();
+        assert_eq!(hr[0].1, most);
+        assert!(hr.windows(2).all(|o| o[0].1 >= o[1].1));
+        assert!(hr.iter().all(|o| !players[&o.0].pos.is_pitcher()));
+        let team = &teams[&1];
+        assert_eq!(team.total_stats(&players, true).b_hr, team.iter_players(&players).filter(|o| !o.1.pos.is_pitcher()).map(|o| o.1.get_stats().b_hr).sum::<u32>());
+
+        // pitchers only show up among the batters without a DH
+        league.dh = false;
+        let hr = league.leaders(Stat::Bhr, true, false, &teams, &players);
+        assert!(hr.iter().any(|o| players[&o.0].pos.is_pitcher()));
+        assert_eq!(hr.len(), teams.values().map(|o| o.players.len()).sum::<usize>());
+        league.dh = true;
+
+        // lowest first, and only the pitchers with enough innings
+        let era = league.leaders(Stat::Pera, false, true, &teams, &players);
+        assert!(era.windows(2).all(|o| o[0].1 <= o[1].1));
+        assert!(era.len() < league.leaders(Stat::Pera, false, false, &teams, &players).len());
+        for (player_id, _) in &era {
+            let team = teams.values().find(|o| o.players.contains(player_id)).unwrap();
+            assert!(players[player_id].pos.is_pitcher() && Stat::Pera.is_qualified(&players[player_id].get_stats(), team.results.games()));
+        }
+    }
+
+    #[test]
+    fn test_tiebreakers() {
+        let mut rng = StdRng::seed_from_u64(2049);
+        let data = Data::new();
+        let (mut teams, mut players) = setup_teams(4, 2049, &data, &mut rng);
+        let mut league = League::new(1, 4, &mut vec![4, 3, 2, 1], &mut rng);
+
+        for (team_id, (wins, losses)) in [(1, (3, 1)), (2, (2, 2)), (3, (2, 2)), (4, (1, 3))].iter() {
+            let team = teams.get_mut(team_id).unwrap();
+            for _ in 0..*wins {
+                team.results(5, 1);
+            }
+            for _ in 0..*losses {
+                team.results(1, 5);
+            }
+        }
+        assert_eq!(league.teams, vec![1, 2, 3, 4]);
+
+        let results = league.tiebreakers(&[1, 2, 4], &mut teams, &mut players, 2049, &mut rng);
+        assert_eq!(results.len(), 1);
+        let (winner, loser) = results[0];
+        assert!((winner == 2 && loser == 3) || (winner == 3 && loser == 2));
+        assert_eq!(league.teams, vec![1, winner, loser, 4]);
+
+        // the tiebreaker doesn't count toward the season record
+        assert_eq!(teams[&winner].get_losses(), teams[&loser].get_losses());
+    }
+
+    #[test]
+    fn test_run_env() {
+        let mut rng = StdRng::seed_from_u64(2049);
+        let data = Data::new();
+        let (mut teams, mut players) = setup_teams(4, 2049, &data, &mut rng);
+        let mut league = League::new(1, 4, &mut vec![4, 3, 2, 1], &mut rng);
+
+        let mut totals = Vec::new();
+        for run_env in [0.7, 1.5].iter() {
+            league.reset_schedule(&mut teams, &mut rng);
+            league.run_env = *run_env;
+            while league.sim(&mut teams, &mut players, &mut Vec::new(), 2049, false, &mut rng) {}
+            totals.push(teams.values().map(|o| o.results.runs_for).sum::<u32>());
+        }
+        assert!(totals[0] < totals[1]);
+    }
+
+    #[test]
+    fn test_run_expectancy() {
+        let mut rng = StdRng::seed_from_u64(2049);
+        let data = Data::new();
+        let (mut teams, mut players) = setup_teams(4, 2049, &data, &mut rng);
+        let mut league = League::new(1, 4, &mut vec![4, 3, 2, 1], &mut rng);
+        while league.sim(&mut teams, &mut players, &mut Vec::new(), 2049, false, &mut rng) {}
+
+        // every half inning starts empty with nobody out, and a runner on with nobody out beats two down
+        let run_expectancy = &league.run_expectancy;
+        assert!(run_expectancy.chances(0, 0) >= league.schedule.games.len() as u32 * 17);
+        assert!(run_expectancy.expected(0, 0).is_some_and(|o| o > 0.0 && o < 2.0));
+        assert!(run_expectancy.expected(1, 0).unwrap() > run_expectancy.expected(0, 2).unwrap());
+
+        league.reset_schedule(&mut teams, &mut rng);
+        assert_eq!(league.run_expectancy.chances(0, 0), 0);
+    }
+
+    #[test]
+    fn test_season_history() {
+        let mut rng = StdRng::seed_from_u64(2049);
+        let data = Data::new();
+        let (mut teams, mut players) = setup_teams(4, 2049, &data, &mut rng);
+        let mut leagues = vec![League::new(1, 4, &mut vec![4, 3, 2, 1], &mut rng)];
+        leagues[0].playoff_config = PlayoffConfig { teams: 2, series_len: 1 };
+
+        for year in 2049..2051 {
+            while leagues[0].sim(&mut teams, &mut players, &mut Vec::new(), year, false, &mut rng) {}
+            let runs = teams.values().map(|o| o.results.runs_for).sum::<u32>();
+            let games = leagues[0].schedule.games.len() as u32;
+            end_of_season(&mut leagues, &mut teams, &mut players, PROMOTION_COUNT, year, &data, &mut rng);
+
+            let season = leagues[0].history.last().unwrap();
+            assert_eq!(season.year, year);
+            assert_eq!(season.games, games);
+            assert_eq!(season.runs, runs);
+            assert!(season.avg > 0 && season.era > 0);
+            assert!(season.per_game(season.hr) < season.per_game(season.runs));
+        }
+        assert_eq!(leagues[0].history.len(), 2);
+    }
+
+    #[test]
+    fn test_promotion_relegation() {
+        let mut rng = StdRng::seed_from_u64(2049);
+        let data = Data::new();
+        let year = 2049;
+        let (mut teams, mut players) = setup_teams(8, year, &data, &mut rng);
+        let mut leagues = vec![League::new(1, 4, &mut vec![1, 2, 3, 4], &mut rng), League::new(2, 4, &mut vec![5, 6, 7, 8], &mut rng)];
+        for league in leagues.iter_mut() {
+            league.playoff_config = PlayoffConfig { teams: 2, series_len: 1 };
+            while league.sim(&mut teams, &mut players, &mut Vec::new(), year, false, &mut rng) {}
+        }
+        let before = leagues.iter().map(|o| o.teams.clone()).collect::<Vec<_>>();
+
+        let events = end_of_season(&mut leagues, &mut teams, &mut players, 1, year, &data, &mut rng);
+        let moves = events.iter().filter_map(|o| match o {
+            LeagueEvent::Promoted(to, team_id, from, _) | LeagueEvent::Relegated(to, team_id, from, _) => Some((*team_id, *from, *to)),
+            _ => None,
+        }).collect::<Vec<_>>();
+
+        // the bottom of the top league swaps with the top of the lower one
+        assert_eq!(moves.len(), 2);
+        for (team_id, from, to) in moves {
+            assert!(before[from as usize - 1].contains(&team_id));
+            assert!(leagues[to as usize - 1].teams.contains(&team_id));
+            assert_eq!((from as i32 - to as i32).abs(), 1);
+        }
+    }
+
+    #[test]
+    fn test_ages_after_first_season() {
+        let mut rng = StdRng::seed_from_u64(2049);
+        let data = Data::new();
+        let year = 2049;
+        let (mut teams, mut players) = setup_teams(8, year, &data, &mut rng);
+        let mut leagues = vec![League::new(1, 4, &mut vec![1, 2, 3, 4], &mut rng), League::new(2, 4, &mut vec![5, 6, 7, 8], &mut rng)];
+        for league in leagues.iter_mut() {
+            league.playoff_config = PlayoffConfig { teams: 2, series_len: 1 };
+            league.ages = AgeProfile::with_mean(AgeProfile::MAX_MEAN);
+            while league.sim(&mut teams, &mut players, &mut Vec::new(), year, false, &mut rng) {}
+        }
+        let first_new = players.keys().max().unwrap() + 1;
+
+        // retirement replacements and the expansion pool both keep the veteran profile
+        end_of_season(&mut leagues, &mut teams, &mut players, 1, year, &data, &mut rng);
+        expand(&mut leagues, &mut teams, &mut players, 2, year + 1, &data, &mut rng);
+        let ages = players.range(first_new..).map(|o| o.1.age(year + 1) as f64).collect::<Vec<_>>();
+        assert!(ages.len() >= 2 * EXPANSION_POOL);
+        assert!(ages.iter().sum::<f64>() / ages.len() as f64 > AgeProfile::default().mean() + 5.0);
+    }
+
+    #[test]
+    fn test_record_qualification() {
+        let line = |counts: &[(Stat, usize)]| Stats::compile_stats(&counts.iter().flat_map(|o| std::iter::repeat_n(o.0, o.1)).collect::<Vec<_>>());
+        // a reliever who went 2 for 2 and got one out, against an everyday player and a starter
+        let cameo = line(&[(Stat::B1b, 2), (Stat::Po, 1)]);
+        let regular = line(&[(Stat::B1b, 3), (Stat::Bbb, 1), (Stat::Bo, 6), (Stat::Po, 9), (Stat::Per, 1)]);
+        assert_eq!((cameo.b_pa, cameo.b_avg), (2, 1000));
+        let stats = [Stat::Bavg, Stat::Bobp, Stat::Pera];
+
+        // a three game season asks for 9 plate appearances and 9 outs
+        let mut records = HashMap::new();
+        check_record(&mut records, &cameo, 1, 1, 2049, 3);
+        check_record(&mut records, &regular, 2, 1, 2049, 3);
+        check_record(&mut records, &cameo, 1, 1, 2049, 3);
+        for stat in &stats {
+            let record = records[stat].expect("the regular qualifies");
+            assert_eq!(record.player_id, 2);
+            assert!(stat.is_qualified(&regular, 3) && !stat.is_qualified(&cameo, 3));
+        }
+
+        // the same line falls short once the season is longer
+        let mut records = HashMap::new();
+        check_record(&mut records, &regular, 2, 1, 2049, 10);
+        check_record(&mut records, &cameo, 1, 1, 2049, 10);
+        assert!(stats.iter().all(|o| records[o].is_none()));
+    }
+
+    #[test]
+    fn test_career_records() {
+        let mut rng = StdRng::seed_from_u64(2049);
+        let data = Data::new();
+        let mut players = PlayerMap::new();
+        generate_players(&mut players, 3, 2049, &data, &AgeProfile::default(), &mut rng);
+        assert!(career_records(&players).is_empty());
+
+        // a retired slugger still holds the record, and a tie stays with the earlier player
+        for (id, hr) in [(1, 300), (2, 500), (3, 500)] {
+            let player = players.get_mut(&id).unwrap();
+            player.historical.push(HistoricalStats { year: 2048, league: 1, team: 1, stats: Stats { b_hr: hr, ..Stats::default() } });
+        }
+        players.get_mut(&2).unwrap().active = false;
+        let records = career_records(&players);
+        assert_eq!(records[&Stat::Bhr].player_id, 2);
+        assert_eq!(records[&Stat::Bhr].record, 500);
+        assert!(!records.contains_key(&Stat::Psv));
+
+        players.get_mut(&3).unwrap().record_stat(Stat::Bhr);
+        assert_eq!(career_records(&players)[&Stat::Bhr].player_id, 3);
+    }
+
+    #[test]
+    fn test_first_record_qualified() {
+        let mut records = HashMap::new();
+
+        // a cameo at 1.000 can't open the books, a full-time .300 hitter can
+        let cameo = Stats { b_pa: 1, b_avg: 1000, ..Stats::default() };
+        check_record(&mut records, &cameo, 1, 1, 2049, 162);
+        assert!(records[&Stat::Bavg].is_none());
+
+        let regular = Stats { b_pa: 600, b_avg: 300, ..Stats::default() };
+        check_record(&mut records, &regular, 2, 1, 2049, 162);
+        check_record(&mut records, &cameo, 1, 1, 2050, 162);
+        let record = records[&Stat::Bavg].unwrap();
+        assert_eq!((record.player_id, record.record), (2, 300));
+    }
+
+    #[test]
+    fn test_notable_retirement() {
+        let mut rng = StdRng::seed_from_u64(2049);
+        let data = Data::new();
+        let year = 2049;
+        let (mut teams, mut players) = setup_teams(4, year, &data, &mut rng);
+        let mut leagues = vec![League::new(1, 4, &mut vec![1, 2, 3, 4], &mut rng)];
+        leagues[0].playoff_config = PlayoffConfig { teams: 2, series_len: 1 };
+        while leagues[0].sim(&mut teams, &mut players, &mut Vec::new(), year, false, &mut rng) {}
+
+        // a slugger too old to keep playing, with the numbers to be remembered
+        let slugger = *teams[&2].players.iter().find(|o| !players[o].pos.is_pitcher()).unwrap();
+        let player = players.get_mut(&slugger).unwrap();
+        player.born = year - 50;
+        player.historical.push(HistoricalStats { year: year - 1, league: 1, team: 2, stats: Stats { b_hr: 400, ..Stats::default() } });
+
+        let events = end_of_season(&mut leagues, &mut teams, &mut players, PROMOTION_COUNT, year, &data, &mut rng);
+        assert!(!players[&slugger].active);
+        assert!(events.iter().any(|o| matches!(o, LeagueEvent::Retired(1, 2, id, y) if *id == slugger && *y == year)));
+        assert!(events.iter().all(|o| o.year() == year));
+    }
 }
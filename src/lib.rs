@@ -6,13 +6,18 @@
 use eframe::wasm_bindgen::{self, prelude::*};
 
 pub use app::Imp019App;
+pub use engine::Engine;
+pub use stat::{Stat, Stats};
 
 mod app;
 mod data;
+mod engine;
 mod game;
 mod league;
 mod player;
+mod playoffs;
 mod schedule;
+mod sim;
 mod stat;
 mod team;
 mod util;
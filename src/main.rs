@@ -1,16 +1,6 @@
 use eframe::NativeOptions;
 
-pub use app::Imp019App;
-
-mod app;
-mod data;
-mod game;
-mod league;
-mod player;
-mod schedule;
-mod stat;
-mod team;
-mod util;
+use imp019::Imp019App;
 
 #[cfg(not(target_arch = "wasm32"))]
 fn main() -> eframe::Result<()> {
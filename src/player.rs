@@ -1,10 +1,9 @@
-use std::collections::HashMap;
+use std::collections::BTreeMap;
 use std::fmt::{Display, Formatter};
 use std::fmt;
 
 use enum_iterator::{all, Sequence};
 use rand::Rng;
-use rand::rngs::ThreadRng;
 use rand::seq::SliceRandom;
 
 use crate::data::{AgeData, Data};
@@ -13,11 +12,14 @@ use crate::team::TeamId;
 use crate::util::{gen_gamma, gen_normal};
 
 pub(crate) type PlayerId = u64;
-pub(crate) type PlayerMap = HashMap<PlayerId, Player>;
-pub(crate) type PlayerRefMap<'a> = HashMap<PlayerId, &'a Player>;
+// ordered maps, so a seeded league iterates and draws the same way every run
+pub(crate) type PlayerMap = BTreeMap<PlayerId, Player>;
+pub(crate) type PlayerRefMap<'a> = BTreeMap<PlayerId, &'a Player>;
 
-#[derive(Copy, Clone, PartialEq, Eq, Hash, Sequence)]
+#[derive(Copy, Clone, PartialEq, Eq, PartialOrd, Ord, Hash, Sequence, Default)]
+#[cfg_attr(feature = "persistence", derive(serde::Deserialize, serde::Serialize))]
 pub(crate) enum Position {
+    #[default]
     StartingPitcher,
     Catcher,
     FirstBase,
@@ -34,12 +36,6 @@ pub(crate) enum Position {
     Closer,
 }
 
-impl Default for Position {
-    fn default() -> Self {
-        Self::StartingPitcher
-    }
-}
-
 impl Position {
     pub(crate) fn is_pitcher(&self) -> bool {
         matches!(self,
@@ -109,9 +105,77 @@ impl Display for Handedness {
     }
 }
 
-pub(crate) type ExpectMap = HashMap<Expect, f64>;
-type SprayChart = HashMap<Expect, HashMap<Position, u32>>;
+#[derive(Copy, Clone, PartialEq, Eq, Debug)]
+pub(crate) enum ErrorType {
+    Fielding,
+    // the batter takes an extra base on the overthrow
+    Throwing,
+}
+
+const THROWING_ERROR_PCT: f64 = 0.5;
+// chance of being able to cover each neighbouring position
+const SECONDARY_POSITION_PCT: f64 = 0.3;
+// an extra base on a hit is tried this often by the most aggressive runner, and made this often by anyone
+const EXTRA_BASE_ATTEMPT_PCT: f64 = 0.4;
+const EXTRA_BASE_SAFE_PCT: f64 = 0.7;
+// high leverage plate appearances needed before a clutch rating means anything
+const CLUTCH_MIN_PA: u32 = 20;
+
+// years past the youngest age are drawn from gamma(shape, scale), skewing the pool young
+#[derive(Copy, Clone, PartialEq, Debug)]
+pub(crate) struct AgeProfile {
+    pub(crate) shape: f64,
+    pub(crate) scale: f64,
+}
+
+impl Default for AgeProfile {
+    fn default() -> Self {
+        Self {
+            shape: 2.0,
+            scale: 3.0,
+        }
+    }
+}
+
+impl AgeProfile {
+    pub(crate) const MIN_AGE: u32 = 18;
+    // everyone retires by then, so nobody is generated older
+    pub(crate) const MAX_AGE: u32 = 45;
+    pub(crate) const MIN_MEAN: f64 = 20.0;
+    pub(crate) const MAX_MEAN: f64 = 35.0;
+
+    // the default spread, stretched or squeezed to land on the mean
+    pub(crate) fn with_mean(mean: f64) -> Self {
+        let shape = Self::default().shape;
+        Self {
+            shape,
+            scale: (mean - Self::MIN_AGE as f64) / shape,
+        }
+    }
+
+    pub(crate) fn mean(&self) -> f64 {
+        Self::MIN_AGE as f64 + self.shape * self.scale
+    }
 
+    pub(crate) fn validate(&self) -> Result<(), String> {
+        if self.shape <= 0.0 || self.scale <= 0.0 {
+            return Err("Age shape and scale must be positive.".to_string());
+        }
+        if !(Self::MIN_MEAN..=Self::MAX_MEAN).contains(&self.mean()) {
+            return Err(format!("Mean age must be between {} and {}.", Self::MIN_MEAN, Self::MAX_MEAN));
+        }
+        Ok(())
+    }
+
+    fn generate(&self, rng: &mut impl Rng) -> u32 {
+        (Self::MIN_AGE + gen_gamma(rng, self.shape, self.scale).round() as u32).min(Self::MAX_AGE)
+    }
+}
+
+pub(crate) type ExpectMap = BTreeMap<Expect, f64>;
+type SprayChart = BTreeMap<Expect, BTreeMap<Position, u32>>;
+
+#[derive(Clone)]
 pub(crate) struct Player {
     pub(crate) active: bool,
     name_first: &'static str,
@@ -119,6 +183,8 @@ pub(crate) struct Player {
     pub(crate) birthplace: String,
     pub(crate) born: u32,
     pub(crate) pos: Position,
+    // other positions they can cover when nobody at that position is available
+    pub(crate) secondary: Vec<Position>,
     pub(crate) bats: Handedness,
     pub(crate) throws: Handedness,
     pub(crate) bat_expect: (ExpectMap, ExpectMap),
@@ -126,14 +192,64 @@ pub(crate) struct Player {
     pub(crate) pit_expect: (ExpectMap, ExpectMap),
     pub(crate) pit_spray: SprayChart,
     pub(crate) error_rate: f64,
+    // chance a catcher throws out a runner who would otherwise steal the base
+    pub(crate) arm: f64,
     pub(crate) patience: f64,
     pub(crate) control: f64,
+    // 0 never takes a chance on the bases, 1 always looks for the extra base
+    pub(crate) baserunning: f64,
+    pub(crate) arsenal: Arsenal,
     stat_stream: Vec<Stat>,
+    slot_stream: [Vec<Stat>; 9],
+    risp_stream: Vec<Stat>,
+    clutch_stream: Vec<Stat>,
     pub(crate) historical: Vec<HistoricalStats>,
     pub(crate) fatigue: u16,
 }
 
-#[derive(Copy, Clone, PartialEq, Eq, Hash, Sequence)]
+// a pitcher's stuff, as multipliers on his strikeout rate and on how well he keeps the ball in the park
+#[derive(Copy, Clone)]
+pub(crate) struct Arsenal {
+    pub(crate) velocity: f64,
+    pub(crate) movement: f64,
+}
+
+impl Default for Arsenal {
+    fn default() -> Self {
+        Self {
+            velocity: 1.0,
+            movement: 1.0,
+        }
+    }
+}
+
+impl Arsenal {
+    fn generate(rng: &mut impl Rng) -> Self {
+        Self {
+            velocity: gen_normal(rng, 1.0, 0.06).clamp(0.85, 1.15),
+            movement: gen_normal(rng, 1.0, 0.06).clamp(0.85, 1.15),
+        }
+    }
+
+    // the 20-80 scouting scale, 50 being neutral
+    pub(crate) fn grade(value: f64) -> u32 {
+        (50.0 + (value - 1.0) * 200.0).round().clamp(20.0, 80.0) as u32
+    }
+
+    // outs absorb the difference so the rates still sum to one
+    pub(crate) fn apply(&self, expect: &ExpectMap) -> ExpectMap {
+        let mut expect = expect.clone();
+        let before = expect[&Expect::Strikeout] + expect[&Expect::HomeRun];
+        *expect.get_mut(&Expect::Strikeout).unwrap() *= self.velocity;
+        *expect.get_mut(&Expect::HomeRun).unwrap() /= self.movement;
+        let after = expect[&Expect::Strikeout] + expect[&Expect::HomeRun];
+        let out = expect.get_mut(&Expect::Out).unwrap();
+        *out = (*out + before - after).max(0.0);
+        expect
+    }
+}
+
+#[derive(Copy, Clone, PartialEq, Eq, PartialOrd, Ord, Hash, Sequence)]
 pub(crate) enum Expect {
     Single,
     Double,
@@ -169,7 +285,7 @@ impl Player {
         let so = expect_pct.so;
         let o = 1.0 - expect_pct.target_obp - so;
 
-        let mut expect = HashMap::new();
+        let mut expect = BTreeMap::new();
         expect.insert(Expect::Single, h1b);
         expect.insert(Expect::Double, h2b);
         expect.insert(Expect::Triple, h3b);
@@ -182,7 +298,7 @@ impl Player {
         expect
     }
 
-    fn generate_bat_expect(rng: &mut ThreadRng) -> ExpectMap {
+    fn generate_bat_expect(rng: &mut impl Rng) -> ExpectMap {
         let target_obp = gen_normal(rng, 0.320, 0.036);
 
         let h1b = gen_gamma(rng, 4.4746090247171, 22.0123537722845);
@@ -207,7 +323,7 @@ impl Player {
         Self::generate_expect(expect)
     }
 
-    fn generate_pit_expect(rng: &mut ThreadRng) -> ExpectMap {
+    fn generate_pit_expect(rng: &mut impl Rng) -> ExpectMap {
         let target_obp = gen_normal(rng, 0.321, 0.039);
         let h = gen_gamma(rng, 3.58229424925063, 43.691697161455);
         let h2b = gen_normal(rng, 0.342, 0.137) * h;
@@ -233,18 +349,18 @@ impl Player {
     }
 
 
-    fn normalize(hashmap: &mut HashMap<Position, u32>) {
-        let sum = hashmap.iter().map(|(_, v)| v).sum::<u32>();
-        for (_, val) in hashmap.iter_mut() {
+    fn normalize(hashmap: &mut BTreeMap<Position, u32>) {
+        let sum = hashmap.values().sum::<u32>();
+        for val in hashmap.values_mut() {
             *val = (*val * 1000) / sum;
         }
     }
 
-    fn generate_bat_spray(rng: &mut ThreadRng, pos: &Position) -> SprayChart {
+    fn generate_bat_spray(rng: &mut impl Rng, pos: &Position) -> SprayChart {
         let mut spray = SprayChart::new();
 
         if !pos.is_pitcher() {
-            let mut single = HashMap::new();
+            let mut single = BTreeMap::new();
             single.insert(Position::StartingPitcher, rng.gen_range(0..3));
             single.insert(Position::Catcher, rng.gen_range(0..3));
             single.insert(Position::FirstBase, rng.gen_range(0..3));
@@ -256,25 +372,25 @@ impl Player {
             single.insert(Position::RightField, rng.gen_range(100..200));
             Self::normalize(&mut single);
 
-            let mut double = HashMap::new();
+            let mut double = BTreeMap::new();
             double.insert(Position::LeftField, rng.gen_range(100..200));
             double.insert(Position::CenterField, rng.gen_range(100..200));
             double.insert(Position::RightField, rng.gen_range(100..200));
             Self::normalize(&mut double);
 
-            let mut triple = HashMap::new();
+            let mut triple = BTreeMap::new();
             triple.insert(Position::LeftField, rng.gen_range(100..200));
             triple.insert(Position::CenterField, rng.gen_range(100..200));
             triple.insert(Position::RightField, rng.gen_range(100..200));
             Self::normalize(&mut triple);
 
-            let mut homerun = HashMap::new();
+            let mut homerun = BTreeMap::new();
             homerun.insert(Position::LeftField, rng.gen_range(100..200));
             homerun.insert(Position::CenterField, rng.gen_range(100..200));
             homerun.insert(Position::RightField, rng.gen_range(100..200));
             Self::normalize(&mut homerun);
 
-            let mut out = HashMap::new();
+            let mut out = BTreeMap::new();
             out.insert(Position::StartingPitcher, 5);
             out.insert(Position::Catcher, 5);
             out.insert(Position::FirstBase, 10);
@@ -291,16 +407,16 @@ impl Player {
             spray.insert(Expect::Triple, triple);
             spray.insert(Expect::HomeRun, homerun);
             spray.insert(Expect::Out, out);
-        } else {}
+        }
 
         spray
     }
 
-    fn generate_pit_spray(rng: &mut ThreadRng, pos: &Position) -> SprayChart {
+    fn generate_pit_spray(rng: &mut impl Rng, pos: &Position) -> SprayChart {
         let mut spray = SprayChart::new();
 
         if pos.is_pitcher() {
-            let mut single = HashMap::new();
+            let mut single = BTreeMap::new();
             single.insert(Position::StartingPitcher, rng.gen_range(0..3));
             single.insert(Position::Catcher, rng.gen_range(0..3));
             single.insert(Position::FirstBase, rng.gen_range(0..3));
@@ -312,25 +428,25 @@ impl Player {
             single.insert(Position::RightField, rng.gen_range(100..200));
             Self::normalize(&mut single);
 
-            let mut double = HashMap::new();
+            let mut double = BTreeMap::new();
             double.insert(Position::LeftField, rng.gen_range(100..200));
             double.insert(Position::CenterField, rng.gen_range(100..200));
             double.insert(Position::RightField, rng.gen_range(100..200));
             Self::normalize(&mut double);
 
-            let mut triple = HashMap::new();
+            let mut triple = BTreeMap::new();
             triple.insert(Position::LeftField, rng.gen_range(100..200));
             triple.insert(Position::CenterField, rng.gen_range(100..200));
             triple.insert(Position::RightField, rng.gen_range(100..200));
             Self::normalize(&mut triple);
 
-            let mut homerun = HashMap::new();
+            let mut homerun = BTreeMap::new();
             homerun.insert(Position::LeftField, rng.gen_range(100..200));
             homerun.insert(Position::CenterField, rng.gen_range(100..200));
             homerun.insert(Position::RightField, rng.gen_range(100..200));
             Self::normalize(&mut homerun);
 
-            let mut out = HashMap::new();
+            let mut out = BTreeMap::new();
             out.insert(Position::StartingPitcher, 5);
             out.insert(Position::Catcher, 5);
             out.insert(Position::FirstBase, 10);
@@ -352,9 +468,43 @@ impl Player {
         spray
     }
 
-    pub(crate) fn determine_spray(bat: &SprayChart, pit: &SprayChart, expect: &Expect, rng: &mut ThreadRng) -> Position {
-        let merged = bat.iter().chain(pit).collect::<HashMap<_, _>>();
-        if let Some(expect_spray) = merged.get(expect) {
+    // the pitcher's chart wins where both have one, same as merging them
+    fn generate_secondary(rng: &mut impl Rng, pos: &Position) -> Vec<Position> {
+        let neighbours: &[Position] = match pos {
+            Position::Catcher => &[Position::FirstBase],
+            Position::FirstBase => &[Position::ThirdBase, Position::LeftField],
+            Position::SecondBase => &[Position::ShortStop, Position::ThirdBase],
+            Position::ThirdBase => &[Position::FirstBase, Position::ShortStop],
+            Position::ShortStop => &[Position::SecondBase, Position::ThirdBase],
+            Position::LeftField => &[Position::RightField, Position::FirstBase],
+            Position::CenterField => &[Position::LeftField, Position::RightField],
+            Position::RightField => &[Position::LeftField, Position::FirstBase],
+            Position::DesignatedHitter => &[Position::FirstBase],
+            _ => &[],
+        };
+        neighbours.iter().copied().filter(|_| rng.gen_bool(SECONDARY_POSITION_PCT)).collect()
+    }
+
+    // share of the batter's hits landing at each position, each kind of hit weighted by how often it comes
+    pub(crate) fn hit_spray(&self) -> BTreeMap<Position, f64> {
+        let mut spray = BTreeMap::new();
+        for expect in [Expect::Single, Expect::Double, Expect::Triple, Expect::HomeRun] {
+            let rate = (self.bat_expect.0[&expect] + self.bat_expect.1[&expect]) / 2.0;
+            for (pos, share) in self.bat_spray.get(&expect).into_iter().flatten() {
+                *spray.entry(*pos).or_insert(0.0) += rate * *share as f64;
+            }
+        }
+        let total = spray.values().sum::<f64>();
+        if total > 0.0 {
+            for share in spray.values_mut() {
+                *share /= total;
+            }
+        }
+        spray
+    }
+
+    pub(crate) fn determine_spray(bat: &SprayChart, pit: &SprayChart, expect: &Expect, rng: &mut impl Rng) -> Position {
+        if let Some(expect_spray) = pit.get(expect).or_else(|| bat.get(expect)) {
             *expect_spray.iter()
                 .collect::<Vec<(_, _)>>()
                 .choose_weighted(rng, |o| o.1)
@@ -364,31 +514,53 @@ impl Player {
         }
     }
 
-    pub(crate) fn check_for_e(&self, rng: &mut ThreadRng) -> bool {
-        rng.gen_bool(self.error_rate)
+    // only infielders have a long enough throw to sail one
+    pub(crate) fn check_for_e(&self, pos: Position, rng: &mut impl Rng) -> Option<ErrorType> {
+        if !rng.gen_bool(self.error_rate) {
+            return None;
+        }
+        let infield = !matches!(pos, Position::FirstBase | Position::LeftField | Position::CenterField | Position::RightField);
+        if infield && rng.gen_bool(THROWING_ERROR_PCT) { Some(ErrorType::Throwing) } else { Some(ErrorType::Fielding) }
     }
 
-    pub(crate) fn check_for_sb(&self, rng: &mut ThreadRng) -> bool {
+    pub(crate) fn check_for_sb(&self, rng: &mut impl Rng) -> bool {
         let triple = (*self.bat_expect.0.get(&Expect::Triple).unwrap() * 10.0) - 0.25;
         let sb_pct = (0.7 + (triple * 0.20) + (triple * 0.20) + (triple * 0.20)).clamp(0.0, 1.0);
         rng.gen_bool(sb_pct)
     }
 
-    pub(crate) fn new(data: &Data, pos: &Position, year: u32, rng: &mut ThreadRng) -> Self {
+    // None when the runner holds, otherwise whether the runner makes it
+    pub(crate) fn try_extra_base(&self, rng: &mut impl Rng) -> Option<bool> {
+        if !rng.gen_bool(self.baserunning * EXTRA_BASE_ATTEMPT_PCT) {
+            return None;
+        }
+        Some(rng.gen_bool(EXTRA_BASE_SAFE_PCT))
+    }
+
+    pub(crate) fn check_for_throw_out(&self, rng: &mut impl Rng) -> bool {
+        rng.gen_bool(self.arm)
+    }
+
+    // an average arm comes out around two seconds, the strongest closer to 1.8
+    pub(crate) fn pop_time(&self) -> f64 {
+        2.0 - (self.arm - 0.1)
+    }
+
+    pub(crate) fn new(data: &Data, pos: &Position, year: u32, ages: &AgeProfile, rng: &mut impl Rng) -> Self {
         let loc_data = data.choose_location(rng);
         let name_first = data.choose_name_first(loc_data.country, rng);
         let name_last = data.choose_name_last(loc_data.country, rng);
 
-        let age = 18 + gen_gamma(rng, 2.0, 3.0).round() as u32;
+        let age = ages.generate(rng);
 
-        let batting_hand = vec![
+        let batting_hand = [
             (Handedness::Right, 54),
             (Handedness::Left, 33),
             (Handedness::Switch, 13),
         ];
         let bat_hand = &batting_hand.choose_weighted(rng, |o| o.1).unwrap().0;
 
-        let pitching_hand = vec![
+        let pitching_hand = [
             (Handedness::Right, 67),
             (Handedness::Left, 33),
         ];
@@ -402,9 +574,14 @@ impl Player {
         let bat_spray = Self::generate_bat_spray(rng, pos);
         let pit_spray = Self::generate_pit_spray(rng, pos);
 
+        let secondary = Self::generate_secondary(rng, pos);
+
         let error_rate = 1.0 - gen_normal(rng, 0.9765828221, 0.03).clamp(0.0, 1.0);
+        let arm = gen_normal(rng, 0.1, 0.05).min(0.5);
         let patience = gen_gamma(rng, 4.5, 1.0).round().max(1.0);
         let control = gen_gamma(rng, 18.0, 0.2195).round().max(1.0);
+        let baserunning = gen_normal(rng, 0.5, 0.15).clamp(0.0, 1.0);
+        let arsenal = Arsenal::generate(rng);
 
         Self {
             active: true,
@@ -413,6 +590,7 @@ impl Player {
             birthplace: format!("{}, {}, {}", loc_data.city, loc_data.state, loc_data.country),
             born: year - age,
             pos: *pos,
+            secondary,
             bats: *bat_hand,
             throws: *pitch_hand,
             bat_expect,
@@ -420,14 +598,24 @@ impl Player {
             pit_expect,
             pit_spray,
             error_rate,
+            arm,
             patience,
             control,
+            baserunning,
+            arsenal,
             stat_stream: vec![],
+            slot_stream: Default::default(),
+            risp_stream: vec![],
+            clutch_stream: vec![],
             historical: vec![],
             fatigue: 0,
         }
     }
 
+    pub(crate) fn can_play(&self, pos: Position) -> bool {
+        self.pos == pos || self.secondary.contains(&pos)
+    }
+
     pub(crate) fn fullname(&self) -> String {
         format!("{} {}", self.name_first, self.name_last)
     }
@@ -438,12 +626,53 @@ impl Player {
 
     fn reset_stats(&mut self) {
         self.stat_stream.clear();
+        for stream in self.slot_stream.iter_mut() {
+            stream.clear();
+        }
+        self.risp_stream.clear();
+        self.clutch_stream.clear();
     }
 
     pub(crate) fn record_stat(&mut self, stat: Stat) {
         self.stat_stream.push(stat);
     }
 
+    pub(crate) fn record_slot_stat(&mut self, slot: usize, stat: Stat) {
+        self.slot_stream[slot].push(stat);
+    }
+
+    pub(crate) fn record_risp_stat(&mut self, stat: Stat) {
+        self.risp_stream.push(stat);
+    }
+
+    // this season's plate appearances with a runner in scoring position
+    pub(crate) fn get_risp_stats(&self) -> Stats {
+        Stats::compile_stats(&self.risp_stream)
+    }
+
+    pub(crate) fn record_clutch_stat(&mut self, stat: Stat) {
+        self.clutch_stream.push(stat);
+    }
+
+    // this season's high leverage plate appearances
+    pub(crate) fn get_clutch_stats(&self) -> Stats {
+        Stats::compile_stats(&self.clutch_stream)
+    }
+
+    // high leverage wOBA less overall wOBA, once there are enough chances to say anything
+    pub(crate) fn clutch(&self) -> Option<i32> {
+        let clutch = self.get_clutch_stats();
+        if clutch.b_pa < CLUTCH_MIN_PA {
+            return None;
+        }
+        Some(clutch.b_woba as i32 - self.get_stats().b_woba as i32)
+    }
+
+    // this season's stats by batting order slot, skipping slots never batted in
+    pub(crate) fn get_slot_stats(&self) -> Vec<(usize, Stats)> {
+        self.slot_stream.iter().enumerate().filter(|(_, o)| !o.is_empty()).map(|(slot, o)| (slot, Stats::compile_stats(o))).collect()
+    }
+
     pub(crate) fn record_stat_history(&mut self, year: u32, league: u32, team_id: TeamId) {
         let mut historical = HistoricalStats {
             year,
@@ -466,10 +695,28 @@ impl Player {
         if bats == Handedness::Left { &self.pit_expect.0 } else { &self.pit_expect.1 }
     }
 
+    // chance a plate appearance by this batter against a pitcher throwing with that hand ends with him on base
+    pub(crate) fn obp_vs(&self, throws: Handedness) -> f64 {
+        self.bat_expect_vs(throws).iter().filter(|(k, _)| !matches!(k, Expect::Strikeout | Expect::Out)).map(|(_, v)| v).sum()
+    }
+
+    // chance a plate appearance against this pitcher ends with the batter on base
+    pub(crate) fn obp_allowed_vs(&self, bats: Handedness) -> f64 {
+        self.pit_expect_vs(bats).iter().filter(|(k, _)| !matches!(k, Expect::Strikeout | Expect::Out)).map(|(_, v)| v).sum()
+    }
+
     pub(crate) fn get_stats(&self) -> Stats {
         Stats::compile_stats(&self.stat_stream)
     }
 
+    pub(crate) fn career_stats(&self) -> Stats {
+        let mut total = self.get_stats();
+        for history in &self.historical {
+            total.compile(&history.stats);
+        }
+        total
+    }
+
     pub(crate) fn age(&self, year: u32) -> u32 {
         year - self.born
     }
@@ -480,7 +727,7 @@ impl Player {
         age_factor as f64
     }
 
-    fn apply_age_to_value(cur: f64, other: f64, age_data: &AgeData, rng: &mut ThreadRng) -> f64 {
+    fn apply_age_to_value(cur: f64, other: f64, age_data: &AgeData, rng: &mut impl Rng) -> f64 {
         match age_data.skew.iter().zip(0..2).collect::<Vec<(_,_)>>().choose_weighted(rng, |o| o.1).unwrap().1 {
             0 => f64::min(cur,other),
             1 => cur,
@@ -489,15 +736,15 @@ impl Player {
         }
     }
 
-    fn apply_age_to_expect(expect_self: &mut ExpectMap, expect_other: &ExpectMap, age_data: &AgeData, rng: &mut ThreadRng) {
+    fn apply_age_to_expect(expect_self: &mut ExpectMap, expect_other: &ExpectMap, age_data: &AgeData, rng: &mut impl Rng) {
         for expect in all::<Expect>() {
             expect_self.insert(expect, Self::apply_age_to_value(expect_self[&expect], expect_other[&expect], age_data, rng ));
         }
     }
 
-    pub(crate) fn apply_age(&mut self, year: u32, data: &Data, rng: &mut ThreadRng ) {
-        let age_data = data.age.iter().find(|o| o.age == self.age(year) ).expect(&*format!("age was {}", self.age(year)));
-        let target = Player::new(data, &self.pos, year, rng);
+    pub(crate) fn apply_age(&mut self, year: u32, data: &Data, rng: &mut impl Rng) {
+        let age_data = data.age.iter().find(|o| o.age == self.age(year) ).unwrap_or_else(|| panic!("age was {}", self.age(year)));
+        let target = Player::new(data, &self.pos, year, &AgeProfile::default(), rng);
 
         Self::apply_age_to_expect( &mut self.bat_expect.0, &target.bat_expect.0, age_data, rng );
         Self::apply_age_to_expect( &mut self.bat_expect.1, &target.bat_expect.1, age_data, rng );
@@ -506,7 +753,7 @@ impl Player {
 
     }
 
-    pub(crate) fn should_retire(&self, year: u32, rng: &mut ThreadRng) -> bool {
+    pub(crate) fn should_retire(&self, year: u32, rng: &mut impl Rng) -> bool {
         const MIN_AGE: u32 = 30;
         const MAX_AGE: u32 = 45;
         let age = self.age(year);
@@ -517,7 +764,7 @@ impl Player {
     }
 }
 
-pub(crate) fn generate_players(players: &mut PlayerMap, count: usize, year: u32, data: &Data, rng: &mut ThreadRng) {
+pub(crate) fn generate_players(players: &mut PlayerMap, count: usize, year: u32, data: &Data, ages: &AgeProfile, rng: &mut impl Rng) {
     let pos_gen = vec![
         Position::StartingPitcher,
         Position::StartingPitcher,
@@ -539,14 +786,10 @@ pub(crate) fn generate_players(players: &mut PlayerMap, count: usize, year: u32,
         Position::DesignatedHitter,
     ];
 
-    let mut player_id = players.keys().max().unwrap_or(&0) + 1;
-    if players.len() < count {
-        players.reserve(count - players.len());
-    }
+    let player_id = players.keys().max().unwrap_or(&0) + 1;
 
-    for _ in 0..count {
-        players.insert(player_id, Player::new(data, pos_gen.choose(rng).unwrap(), year, rng));
-        player_id += 1;
+    for offset in 0..count as PlayerId {
+        players.insert(player_id + offset, Player::new(data, pos_gen.choose(rng).unwrap(), year, ages, rng));
     }
 }
 
@@ -556,3 +799,55 @@ pub(crate) fn collect_all_active(players: &PlayerMap) -> PlayerRefMap<'_> {
         .map(|(k, v)| (*k, v))
         .collect()
 }
+
+#[cfg(test)]
+mod tests {
+    use rand::SeedableRng;
+    use rand::rngs::StdRng;
+
+    use crate::data::Data;
+    use crate::player::{generate_players, AgeProfile, PlayerMap, Position};
+
+    #[test]
+    fn test_age_profile() {
+        let mut rng = StdRng::seed_from_u64(2049);
+        let data = Data::new();
+        let year = 2049;
+        let mean_age = |ages: &AgeProfile, rng: &mut StdRng| {
+            let mut players = PlayerMap::new();
+            generate_players(&mut players, 500, year, &data, ages, rng);
+            assert!(players.values().all(|o| (AgeProfile::MIN_AGE..=AgeProfile::MAX_AGE).contains(&o.age(year))));
+            players.values().map(|o| o.age(year) as f64).sum::<f64>() / players.len() as f64
+        };
+
+        let default = AgeProfile::default();
+        assert_eq!(default.mean(), 24.0);
+        let young = mean_age(&default, &mut rng);
+        assert!((young - 24.0).abs() < 1.0);
+
+        // a veteran-heavy world
+        let veteran = AgeProfile::with_mean(30.0);
+        assert!(veteran.validate().is_ok());
+        assert!(mean_age(&veteran, &mut rng) > young + 4.0);
+
+        assert!(AgeProfile::with_mean(40.0).validate().is_err());
+        assert!(AgeProfile { shape: 0.0, scale: 3.0 }.validate().is_err());
+    }
+
+    #[test]
+    fn test_hit_spray() {
+        let mut players = PlayerMap::new();
+        generate_players(&mut players, 100, 2049, &Data::new(), &AgeProfile::default(), &mut StdRng::seed_from_u64(2049));
+        let batter = players.values().find(|o| !o.pos.is_pitcher()).unwrap();
+        let spray = batter.hit_spray();
+
+        // every kind of hit reaches the outfield, only singles stay in
+        assert!((spray.values().sum::<f64>() - 1.0).abs() < 1e-9);
+        let outfield = [Position::LeftField, Position::CenterField, Position::RightField];
+        let infield = spray.iter().filter(|o| !outfield.contains(o.0)).map(|o| o.1).sum::<f64>();
+        assert!(infield > 0.0 && infield < 0.2);
+
+        let pitcher = players.values().find(|o| o.pos.is_pitcher()).unwrap();
+        assert!(pitcher.hit_spray().is_empty());
+    }
+}
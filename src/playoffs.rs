@@ -0,0 +1,153 @@
+use rand::Rng;
+
+use crate::game::Game;
+use crate::player::PlayerMap;
+use crate::team::{TeamId, TeamMap};
+
+#[derive(Copy, Clone, PartialEq, Eq)]
+pub(crate) struct PlayoffConfig {
+    // qualifiers taken from the top of the standings, 1 leaves the title to the regular season winner
+    pub(crate) teams: usize,
+    // best-of-N
+    pub(crate) series_len: usize,
+}
+
+impl Default for PlayoffConfig {
+    fn default() -> Self {
+        Self {
+            teams: 4,
+            series_len: 5,
+        }
+    }
+}
+
+impl PlayoffConfig {
+    pub(crate) fn validate(&self, league_size: usize) -> Result<(), String> {
+        if !self.teams.is_power_of_two() {
+            return Err("Playoff teams must be a power of two.".to_string());
+        }
+        if self.teams > league_size {
+            return Err(format!("Only {} teams can make the playoffs.", league_size));
+        }
+        if self.series_len == 0 || self.series_len.is_multiple_of(2) {
+            return Err("Series length must be an odd number.".to_string());
+        }
+        Ok(())
+    }
+
+    fn wins_needed(&self) -> u32 {
+        (self.series_len / 2 + 1) as u32
+    }
+}
+
+#[derive(Clone)]
+pub(crate) struct Series {
+    // the better seed, with home field in the odd-numbered games
+    pub(crate) high: TeamId,
+    pub(crate) low: TeamId,
+    pub(crate) games: Vec<Game>,
+}
+
+impl Series {
+    fn new(high: TeamId, low: TeamId) -> Self {
+        Self {
+            high,
+            low,
+            games: Vec::new(),
+        }
+    }
+
+    pub(crate) fn wins(&self, team_id: TeamId) -> u32 {
+        self.games.iter().filter(|o| (o.home.id == team_id && o.home.r > o.away.r) || (o.away.id == team_id && o.away.r > o.home.r)).count() as u32
+    }
+
+    fn winner(&self, needed: u32) -> Option<TeamId> {
+        [self.high, self.low].iter().copied().find(|o| self.wins(*o) >= needed)
+    }
+
+    fn sim(&mut self, needed: u32, teams: &mut TeamMap, players: &mut PlayerMap, year: u32, run_env: f64, rng: &mut impl Rng) -> TeamId {
+        loop {
+            if let Some(winner) = self.winner(needed) {
+                return winner;
+            }
+            let mut game = if self.games.len().is_multiple_of(2) { Game::new(self.high, self.low) } else { Game::new(self.low, self.high) };
+            game.postseason = true;
+            game.sim(teams, players, year, run_env, None, rng.gen());
+            self.games.push(game);
+        }
+    }
+}
+
+#[derive(Clone)]
+pub(crate) struct Playoffs {
+    pub(crate) year: u32,
+    pub(crate) rounds: Vec<Vec<Series>>,
+    pub(crate) champion: TeamId,
+}
+
+impl Playoffs {
+    // seeds are in standings order; every round is reseeded so the best remaining seed meets the worst
+    pub(crate) fn sim(config: &PlayoffConfig, seeds: &[TeamId], teams: &mut TeamMap, players: &mut PlayerMap, year: u32, run_env: f64, rng: &mut impl Rng) -> Self {
+        let mut remaining = seeds[..config.teams.min(seeds.len())].to_vec();
+        let mut rounds = Vec::new();
+
+        while remaining.len() > 1 {
+            let half = remaining.len() / 2;
+            let mut round = (0..half).map(|o| Series::new(remaining[o], remaining[remaining.len() - 1 - o])).collect::<Vec<_>>();
+            let winners = round.iter_mut().map(|o| o.sim(config.wins_needed(), teams, players, year, run_env, rng)).collect::<Vec<_>>();
+            remaining.retain(|o| winners.contains(o));
+            rounds.push(round);
+        }
+
+        Self {
+            year,
+            rounds,
+            champion: remaining[0],
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use rand::SeedableRng;
+    use rand::rngs::StdRng;
+
+    use crate::data::Data;
+    use crate::team::tests::setup_teams;
+
+    use super::*;
+
+    #[test]
+    fn test_playoffs() {
+        let mut rng = StdRng::seed_from_u64(2049);
+        let data = Data::new();
+        let year = 2049;
+
+        let (mut teams, mut players) = setup_teams(6, year, &data, &mut rng);
+
+        let config = PlayoffConfig { teams: 4, series_len: 3 };
+        assert!(config.validate(6).is_ok());
+        assert!(PlayoffConfig { teams: 3, series_len: 3 }.validate(6).is_err());
+        assert!(PlayoffConfig { teams: 8, series_len: 3 }.validate(6).is_err());
+        assert!(PlayoffConfig { teams: 4, series_len: 4 }.validate(6).is_err());
+
+        let seeds = [3, 1, 6, 2, 5, 4];
+        let playoffs = Playoffs::sim(&config, &seeds, &mut teams, &mut players, year, 1.0, &mut rng);
+
+        assert_eq!(playoffs.rounds.len(), 2);
+        assert_eq!(playoffs.rounds[0].len(), 2);
+        assert_eq!((playoffs.rounds[0][0].high, playoffs.rounds[0][0].low), (3, 2));
+        assert_eq!((playoffs.rounds[0][1].high, playoffs.rounds[0][1].low), (1, 6));
+        for series in playoffs.rounds.iter().flatten() {
+            assert!((2..=3).contains(&series.games.len()));
+            assert_eq!(series.wins(series.high).max(series.wins(series.low)), 2);
+        }
+
+        let last = &playoffs.rounds[1][0];
+        assert_eq!(last.wins(playoffs.champion), 2);
+        assert!(seeds[..4].contains(&playoffs.champion));
+
+        // postseason games stay out of the regular season record
+        assert!(teams.values().all(|o| o.results.games() == 0));
+    }
+}
@@ -1,16 +1,16 @@
-use rand::rngs::ThreadRng;
+use rand::Rng;
 use rand::seq::SliceRandom;
 
 use crate::game::Game;
 use crate::team::TeamId;
 
-#[derive(Default)]
+#[derive(Clone, Default)]
 pub(crate) struct Schedule {
     pub(crate) games: Vec<Game>,
 }
 
 impl Schedule {
-    pub(crate) fn new(teams: &[TeamId], rng: &mut ThreadRng) -> Self {
+    pub(crate) fn new(teams: &[TeamId], rng: &mut impl Rng) -> Self {
         let mut raw_matchups = Vec::new();
         let team_count = teams.len();
         raw_matchups.reserve(team_count * (team_count - 1));
@@ -0,0 +1,74 @@
+use std::collections::BTreeMap;
+
+use lazy_static::lazy_static;
+
+use crate::player::{Expect, ExpectMap, Handedness};
+
+lazy_static! {
+    pub(crate) static ref LEAGUE_AVG: ExpectMap = {
+        let mut expect = BTreeMap::new();
+        expect.insert(Expect::Single, 0.1379988963);
+        expect.insert(Expect::Double, 0.045119492);
+        expect.insert(Expect::Triple, 0.004006693438);
+        expect.insert(Expect::HomeRun, 0.03522694576);
+        expect.insert(Expect::Walk, 0.08492014357);
+        expect.insert(Expect::HitByPitch, 0.01096355115);
+        expect.insert(Expect::Strikeout, 0.19);
+        expect.insert(Expect::Out, 0.4909664694);
+        expect
+    };
+
+    // the league against left- and right-handed pitching, each side's own splits are read against these
+    pub(crate) static ref LEAGUE_AVG_VS: (ExpectMap, ExpectMap) = (
+        league_split(&[(Expect::Single, 1.02), (Expect::HomeRun, 0.97), (Expect::Walk, 1.01), (Expect::Strikeout, 0.98)]),
+        league_split(&[(Expect::Single, 0.99), (Expect::HomeRun, 1.01), (Expect::Strikeout, 1.01)]),
+    );
+}
+
+// scales the overall rates, outs absorb the difference so the split still sums to one
+fn league_split(scale: &[(Expect, f64)]) -> ExpectMap {
+    let mut expect = LEAGUE_AVG.clone();
+    for (key, factor) in scale {
+        *expect.get_mut(key).unwrap() *= factor;
+    }
+    let rest = expect.iter().filter(|(k, _)| **k != Expect::Out).map(|(_, v)| v).sum::<f64>();
+    expect.insert(Expect::Out, 1.0 - rest);
+    expect
+}
+
+pub(crate) fn league_avg_vs(throws: Handedness) -> &'static ExpectMap {
+    if throws == Handedness::Left { &LEAGUE_AVG_VS.0 } else { &LEAGUE_AVG_VS.1 }
+}
+
+// combines batter and pitcher rates relative to the league rate
+pub(crate) fn matchup_morey_z(batter: f64, pitcher: f64, league: f64) -> f64 {
+    let sqrt_league = (league * (1.0 - league)).sqrt();
+    let top_left = (batter - league) / sqrt_league;
+    let top_right = (pitcher - league) / sqrt_league;
+    let left = (top_left + top_right) / 2.0f64.sqrt();
+    (left * sqrt_league) + league
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::player::{Expect, Handedness};
+    use crate::sim::{league_avg_vs, matchup_morey_z, LEAGUE_AVG};
+
+    #[test]
+    fn test_matchup_morey_z() {
+        let close = |a: f64, b: f64| (a - b).abs() < 1e-9;
+
+        assert!(close(matchup_morey_z(0.25, 0.25, 0.25), 0.25));
+        assert!(close(matchup_morey_z(0.3, 0.3, 0.25), 0.25 + 0.1 / 2.0f64.sqrt()));
+        assert!(close(matchup_morey_z(0.3, 0.2, 0.25), 0.25));
+        assert!(close(matchup_morey_z(0.2, 0.2, 0.25), 0.25 - 0.1 / 2.0f64.sqrt()));
+
+        let hr = *LEAGUE_AVG.get(&Expect::HomeRun).unwrap();
+        assert!(close(matchup_morey_z(hr, hr, hr), hr));
+        assert!((LEAGUE_AVG.values().sum::<f64>() - 1.0).abs() < 0.001);
+        for throws in [Handedness::Left, Handedness::Right] {
+            assert!((league_avg_vs(throws).values().sum::<f64>() - 1.0).abs() < 1e-9);
+        }
+        assert!(league_avg_vs(Handedness::Left)[&Expect::HomeRun] < league_avg_vs(Handedness::Right)[&Expect::HomeRun]);
+    }
+}
@@ -1,10 +1,13 @@
 use std::fmt::{Display, Formatter};
 use std::fmt;
 
+use crate::player::Expect;
+use crate::sim::LEAGUE_AVG;
 use crate::team::TeamId;
 
 #[derive(Copy, Clone, Debug, PartialEq, Eq, Hash)]
-pub(crate) enum Stat {
+#[cfg_attr(feature = "persistence", derive(serde::Deserialize, serde::Serialize))]
+pub enum Stat {
     // recorded
     G,
     Gs,
@@ -23,6 +26,8 @@ pub(crate) enum Stat {
     Bgidp,
     Bsb,
     Bcs,
+    // thrown out taking an extra base on a hit
+    Boob,
     // calculated
     Bh,
     Bab,
@@ -30,6 +35,8 @@ pub(crate) enum Stat {
     Bavg,
     Bobp,
     Bslg,
+    Bwoba,
+    Bwar,
     // recorded
     P1b,
     P2b,
@@ -49,6 +56,11 @@ pub(crate) enum Stat {
     Phld,
     Pcg,
     Psho,
+    Pir,
+    Pirs,
+    Pqs,
+    // runs scored by the pitcher's team while they were in the game
+    Prs,
     // calculated
     Ph,
     Pbf,
@@ -57,27 +69,37 @@ pub(crate) enum Stat {
     Pslg,
     Pera,
     Pwhip,
+    Psvpct,
+    Prs9,
+    Pwar,
     // recorded
     Fpo,
+    Fa,
     Fe,
+    // also counts as an error
+    Fte,
+    Ffpct,
+    // catchers
+    Fsb,
+    Fcs,
 }
 
 impl Stat {
     pub(crate) fn is_batting(&self) -> bool {
-        matches!(self, Stat::B1b | Stat::B2b | Stat::B3b | Stat::Bhr | Stat::Bbb | Stat::Bibb | Stat::Bhbp | Stat::Bso | Stat::Bo | Stat::Bgidp | Stat::Bsb | Stat::Bcs | Stat::Br | Stat::Brbi | Stat::Bh | Stat::Bab | Stat::Bpa | Stat::Bavg | Stat::Bobp | Stat::Bslg)
+        matches!(self, Stat::B1b | Stat::B2b | Stat::B3b | Stat::Bhr | Stat::Bbb | Stat::Bibb | Stat::Bhbp | Stat::Bso | Stat::Bo | Stat::Bgidp | Stat::Bsb | Stat::Bcs | Stat::Boob | Stat::Br | Stat::Brbi | Stat::Bh | Stat::Bab | Stat::Bpa | Stat::Bavg | Stat::Bobp | Stat::Bslg | Stat::Bwoba | Stat::Bwar)
     }
 
-    pub(crate) fn value(&self, val: u32) -> String {
+    pub(crate) fn is_rate(&self) -> bool {
+        matches!(self, Stat::Bavg | Stat::Bobp | Stat::Bslg | Stat::Bwoba | Stat::Pavg | Stat::Pobp | Stat::Pslg | Stat::Pera | Stat::Pwhip | Stat::Psvpct | Stat::Prs9 | Stat::Ffpct)
+    }
+
+    /// Formats a value as returned by `Stats::get_stat`, rates are stored in thousandths.
+    pub fn value(&self, val: u32) -> String {
         match self {
-            Stat::Bavg |
-            Stat::Bobp |
-            Stat::Bslg |
-            Stat::Pavg |
-            Stat::Pobp |
-            Stat::Pslg |
-            Stat::Pera |
-            Stat::Pwhip => format!("{}.{:03}", val / 1000, val % 1000),
+            Stat::Psvpct => format!("{}.{}%", val / 10, val % 10),
+            _ if self.is_rate() => format!("{}.{:03}", val / 1000, val % 1000),
             Stat::Po => format!("{}.{}", val / 3, val % 3),
+            Stat::Bwar | Stat::Pwar => format!("{}.{}", val / 10, val % 10),
             _ => format!("{}", val),
         }
     }
@@ -90,7 +112,8 @@ impl Stat {
         let qual = match self {
             Stat::Bavg |
             Stat::Bobp |
-            Stat::Bslg => Some((Stat::Bpa, 31)),
+            Stat::Bslg |
+            Stat::Bwoba => Some((Stat::Bpa, 31)),
             Stat::Pobp |
             Stat::Pslg |
             Stat::Pera |
@@ -106,6 +129,62 @@ impl Stat {
         }
         true
     }
+
+    pub(crate) fn explanation(&self) -> &'static str {
+        match self {
+            Stat::G => "Games played",
+            Stat::Gs => "Games started",
+            Stat::B1b | Stat::P1b => "Singles",
+            Stat::B2b | Stat::P2b => "Doubles",
+            Stat::B3b | Stat::P3b => "Triples",
+            Stat::Bhr | Stat::Phr => "Home runs",
+            Stat::Bbb | Stat::Pbb => "Walks",
+            Stat::Bibb | Stat::Pibb => "Intentional walks",
+            Stat::Bhbp | Stat::Phbp => "Hit by pitch",
+            Stat::Bso | Stat::Pso => "Strikeouts",
+            Stat::Bo => "Outs made",
+            Stat::Br | Stat::Pr => "Runs",
+            Stat::Brbi => "Runs batted in",
+            Stat::Bgidp => "Grounded into double plays",
+            Stat::Bsb => "Stolen bases",
+            Stat::Bcs => "Caught stealing",
+            Stat::Boob => "Thrown out trying for an extra base on a hit",
+            Stat::Bh | Stat::Ph => "Hits",
+            Stat::Bab => "At bats: plate appearances minus walks, hit by pitch and sacrifices",
+            Stat::Bpa => "Plate appearances",
+            Stat::Bavg => "Batting average: hits per at bat",
+            Stat::Bobp | Stat::Pobp => "On-base percentage: how often a batter reaches base",
+            Stat::Bslg | Stat::Pslg => "Slugging percentage: total bases per at bat",
+            Stat::Bwoba => "Weighted on-base average: each way of reaching base weighted by its run value, per plate appearance",
+            Stat::Po => "Innings pitched, with thirds of an inning after the point",
+            Stat::Per => "Earned runs: runs that scored without the help of an error",
+            Stat::Pw => "Wins",
+            Stat::Pl => "Losses",
+            Stat::Psv => "Saves: finishing a close win in relief",
+            Stat::Pbs => "Blown saves: giving up the lead in a save situation",
+            Stat::Phld => "Holds: keeping a save situation intact before leaving",
+            Stat::Pcg => "Complete games",
+            Stat::Psho => "Shutouts: complete games allowing no runs",
+            Stat::Pir => "Inherited runners: runners on base when entering the game",
+            Stat::Pirs => "Inherited runners who scored",
+            Stat::Pqs => "Quality starts: at least six innings with three or fewer earned runs",
+            Stat::Pbf => "Batters faced",
+            Stat::Pavg => "Batting average against",
+            Stat::Pera => "Earned run average: earned runs per nine innings",
+            Stat::Pwhip => "Walks plus hits per inning pitched",
+            Stat::Psvpct => "Save percentage: saves converted out of save opportunities",
+            Stat::Prs => "Run support: runs scored by the team while in the game",
+            Stat::Prs9 => "Run support per nine innings pitched",
+            Stat::Bwar | Stat::Pwar => "Wins above replacement: a rough estimate of the wins added over a replacement level player",
+            Stat::Fpo => "Putouts",
+            Stat::Fa => "Assists: throws that led to an out",
+            Stat::Fe => "Errors",
+            Stat::Fte => "Throwing errors",
+            Stat::Ffpct => "Fielding percentage: chances handled without an error",
+            Stat::Fsb => "Stolen bases allowed as the catcher",
+            Stat::Fcs => "Runners thrown out stealing as the catcher",
+        }
+    }
 }
 
 impl Display for Stat {
@@ -125,6 +204,7 @@ impl Display for Stat {
             Stat::Bgidp => "GIDP",
             Stat::Bsb => "SB",
             Stat::Bcs => "CS",
+            Stat::Boob => "OOB",
             Stat::Br => "R",
             Stat::Brbi => "RBI",
             Stat::Bh => "H",
@@ -133,6 +213,8 @@ impl Display for Stat {
             Stat::Bavg => "AVG",
             Stat::Bobp => "OBP",
             Stat::Bslg => "SLG",
+            Stat::Bwoba => "wOBA",
+            Stat::Bwar => "WAR",
             Stat::P1b => "1B",
             Stat::P2b => "2B",
             Stat::P3b => "3B",
@@ -151,6 +233,9 @@ impl Display for Stat {
             Stat::Phld => "HLD",
             Stat::Pcg => "CG",
             Stat::Psho => "SHO",
+            Stat::Pir => "IR",
+            Stat::Pirs => "IRS",
+            Stat::Pqs => "QS",
             Stat::Ph => "H",
             Stat::Pbf => "BF",
             Stat::Pavg => "BAA",
@@ -158,15 +243,33 @@ impl Display for Stat {
             Stat::Pslg => "SLG",
             Stat::Pera => "ERA",
             Stat::Pwhip => "WHIP",
+            Stat::Psvpct => "SV%",
+            Stat::Prs => "RS",
+            Stat::Prs9 => "RS/9",
+            Stat::Pwar => "WAR",
             Stat::Fpo => "PO",
+            Stat::Fa => "A",
             Stat::Fe => "E",
+            Stat::Fte => "TE",
+            Stat::Ffpct => "FPCT",
+            Stat::Fsb => "SBA",
+            Stat::Fcs => "CS",
         };
         write!(f, "{}", str)
     }
 }
 
-#[derive(Default)]
-pub(crate) struct Stats {
+// unintentional walk, hit by pitch, single, double, triple, home run
+const WOBA_WEIGHTS: [u32; 6] = [69, 72, 89, 127, 162, 210];
+
+const RUNS_PER_WIN: f64 = 10.0;
+const WOBA_SCALE: f64 = 1.2;
+// how many runs a replacement level player gives up against average
+const BATTER_REPLACEMENT_RUNS_PER_PA: f64 = 20.0 / 600.0;
+const PITCHER_REPLACEMENT_RUNS_PER_9: f64 = 1.0;
+
+#[derive(Clone, Default)]
+pub struct Stats {
     pub(crate) g: u32,
     pub(crate) gs: u32,
     pub(crate) b_1b: u32,
@@ -183,12 +286,16 @@ pub(crate) struct Stats {
     pub(crate) b_gidp: u32,
     pub(crate) b_sb: u32,
     pub(crate) b_cs: u32,
+    pub(crate) b_oob: u32,
     pub(crate) b_h: u32,
     pub(crate) b_ab: u32,
     pub(crate) b_pa: u32,
     pub(crate) b_avg: u32,
     pub(crate) b_obp: u32,
     pub(crate) b_slg: u32,
+    pub(crate) b_woba: u32,
+    // tenths of a win, only filled in by calc_war
+    pub(crate) b_war: u32,
 
     pub(crate) p_1b: u32,
     pub(crate) p_2b: u32,
@@ -206,6 +313,9 @@ pub(crate) struct Stats {
     pub(crate) p_hld: u32,
     pub(crate) p_cg: u32,
     pub(crate) p_sho: u32,
+    pub(crate) p_ir: u32,
+    pub(crate) p_irs: u32,
+    pub(crate) p_qs: u32,
     pub(crate) p_so: u32,
     pub(crate) p_o: u32,
     pub(crate) p_h: u32,
@@ -215,13 +325,22 @@ pub(crate) struct Stats {
     pub(crate) p_slg: u32,
     pub(crate) p_era: u32,
     pub(crate) p_whip: u32,
+    pub(crate) p_svpct: u32,
+    pub(crate) p_rs: u32,
+    pub(crate) p_rs9: u32,
+    pub(crate) p_war: u32,
 
     pub(crate) f_po: u32,
+    pub(crate) f_a: u32,
     pub(crate) f_e: u32,
+    pub(crate) f_te: u32,
+    pub(crate) f_fpct: u32,
+    pub(crate) f_sb: u32,
+    pub(crate) f_cs: u32,
 }
 
 impl Stats {
-    pub(crate) fn get_stat(&self, stat: Stat) -> u32 {
+    pub fn get_stat(&self, stat: Stat) -> u32 {
         match stat {
             Stat::G => self.g,
             Stat::Gs => self.gs,
@@ -237,6 +356,7 @@ impl Stats {
             Stat::Bgidp => self.b_gidp,
             Stat::Bsb => self.b_sb,
             Stat::Bcs => self.b_cs,
+            Stat::Boob => self.b_oob,
             Stat::Br => self.b_r,
             Stat::Brbi => self.b_rbi,
             Stat::Bh => self.b_h,
@@ -245,6 +365,8 @@ impl Stats {
             Stat::Bavg => self.b_avg,
             Stat::Bobp => self.b_obp,
             Stat::Bslg => self.b_slg,
+            Stat::Bwoba => self.b_woba,
+            Stat::Bwar => self.b_war,
             Stat::P1b => self.p_1b,
             Stat::P2b => self.p_2b,
             Stat::P3b => self.p_3b,
@@ -263,6 +385,9 @@ impl Stats {
             Stat::Phld => self.p_hld,
             Stat::Pcg => self.p_cg,
             Stat::Psho => self.p_sho,
+            Stat::Pir => self.p_ir,
+            Stat::Pirs => self.p_irs,
+            Stat::Pqs => self.p_qs,
             Stat::Ph => self.p_h,
             Stat::Pbf => self.p_bf,
             Stat::Pavg => self.p_avg,
@@ -270,8 +395,17 @@ impl Stats {
             Stat::Pslg => self.p_slg,
             Stat::Pera => self.p_era,
             Stat::Pwhip => self.p_whip,
+            Stat::Psvpct => self.p_svpct,
+            Stat::Prs => self.p_rs,
+            Stat::Prs9 => self.p_rs9,
+            Stat::Pwar => self.p_war,
             Stat::Fpo => self.f_po,
+            Stat::Fa => self.f_a,
             Stat::Fe => self.f_e,
+            Stat::Fte => self.f_te,
+            Stat::Ffpct => self.f_fpct,
+            Stat::Fsb => self.f_sb,
+            Stat::Fcs => self.f_cs,
         }
     }
     pub(crate) fn compile(&mut self, rhs: &Self) {
@@ -291,12 +425,15 @@ impl Stats {
         self.b_gidp += rhs.b_gidp;
         self.b_sb += rhs.b_sb;
         self.b_cs += rhs.b_cs;
+        self.b_oob += rhs.b_oob;
         self.b_h += rhs.b_h;
         self.b_ab += rhs.b_ab;
         self.b_pa += rhs.b_pa;
         self.b_avg += rhs.b_avg;
         self.b_obp += rhs.b_obp;
         self.b_slg += rhs.b_slg;
+        self.b_woba += rhs.b_woba;
+        self.b_war += rhs.b_war;
         self.p_1b += rhs.p_1b;
         self.p_2b += rhs.p_2b;
         self.p_3b += rhs.p_3b;
@@ -313,6 +450,9 @@ impl Stats {
         self.p_hld += rhs.p_hld;
         self.p_cg += rhs.p_cg;
         self.p_sho += rhs.p_sho;
+        self.p_ir += rhs.p_ir;
+        self.p_irs += rhs.p_irs;
+        self.p_qs += rhs.p_qs;
         self.p_so += rhs.p_so;
         self.p_o += rhs.p_o;
         self.p_h += rhs.p_h;
@@ -322,13 +462,23 @@ impl Stats {
         self.p_slg += rhs.p_slg;
         self.p_era += rhs.p_era;
         self.p_whip += rhs.p_whip;
+        self.p_svpct += rhs.p_svpct;
+        self.p_rs += rhs.p_rs;
+        self.p_rs9 += rhs.p_rs9;
+        self.p_war += rhs.p_war;
         self.f_po += rhs.f_po;
+        self.f_a += rhs.f_a;
         self.f_e += rhs.f_e;
+        self.f_te += rhs.f_te;
+        self.f_fpct += rhs.f_fpct;
+        self.f_sb += rhs.f_sb;
+        self.f_cs += rhs.f_cs;
         self.calculate();
     }
 
-    fn div1000_or_0(n: u32, d: u32) -> u32 {
-        if d > 0 { (n * 1000) / d } else { d }
+    // widened so league-wide totals can't overflow once scaled up
+    fn div1000_or_0(n: impl Into<u64>, d: impl Into<u64>) -> u32 {
+        (n.into() * 1000).checked_div(d.into()).unwrap_or(0) as u32
     }
 
     fn calc_avg1000(ab: u32, h: u32) -> u32 {
@@ -343,6 +493,12 @@ impl Stats {
         Self::div1000_or_0(h1b + (2 * h2b) + (3 * h3b) + (4 * hr), ab)
     }
 
+    // linear weights are in hundredths of a run
+    fn calc_woba1000(pa: u32, ubb: u32, hbp: u32, h1b: u32, h2b: u32, h3b: u32, hr: u32) -> u32 {
+        let weighted = [ubb, hbp, h1b, h2b, h3b, hr].iter().zip(WOBA_WEIGHTS.iter()).map(|(count, weight)| *count as u64 * *weight as u64).sum::<u64>();
+        Self::div1000_or_0(weighted, pa) / 100
+    }
+
     fn calc_era1000(er: u32, o: u32) -> u32 {
         Self::div1000_or_0(27 * er, o)
     }
@@ -359,6 +515,7 @@ impl Stats {
         self.b_avg = Self::calc_avg1000(self.b_ab, self.b_h);
         self.b_obp = Self::calc_obp1000(self.b_pa, self.b_h, self.b_bb, self.b_hbp);
         self.b_slg = Self::calc_slg1000(self.b_ab, self.b_1b, self.b_2b, self.b_3b, self.b_hr);
+        self.b_woba = Self::calc_woba1000(self.b_pa, self.b_bb - self.b_ibb, self.b_hbp, self.b_1b, self.b_2b, self.b_3b, self.b_hr);
 
 
         self.p_h = self.p_1b + self.p_2b + self.p_3b + self.p_hr;
@@ -370,6 +527,106 @@ impl Stats {
         self.p_slg = Self::calc_slg1000(p_ab, self.p_1b, self.p_2b, self.p_3b, self.p_hr);
         self.p_era = Self::calc_era1000(self.p_er, self.p_o);
         self.p_whip = Self::calc_whip1000(self.p_h, self.p_bb, self.p_o);
+        self.p_svpct = Self::div1000_or_0(self.p_sv, self.p_sv + self.p_bs);
+        self.p_rs9 = Self::calc_era1000(self.p_rs, self.p_o);
+
+        self.f_fpct = Self::div1000_or_0(self.f_po + self.f_a, self.f_po + self.f_a + self.f_e);
+    }
+
+    // fielding independent pitching per nine innings, without the constant that puts it on the ERA scale
+    fn raw_fip(&self) -> f64 {
+        if self.p_o == 0 {
+            return 0.0;
+        }
+        let weighted = 13.0 * self.p_hr as f64 + 3.0 * (self.p_bb + self.p_hbp) as f64 - 2.0 * self.p_so as f64;
+        weighted * 27.0 / self.p_o as f64
+    }
+
+    fn war10(runs: f64) -> u32 {
+        (runs / RUNS_PER_WIN * 10.0).round().max(0.0) as u32
+    }
+
+    // batters from wOBA and pitchers from FIP, both against the league totals; below replacement shows as zero
+    pub(crate) fn calc_war(&mut self, league: &Stats) {
+        let pa = self.b_pa as f64;
+        let woba_diff = (self.b_woba as f64 - league.b_woba as f64) / 1000.0;
+        self.b_war = Self::war10(woba_diff / WOBA_SCALE * pa + BATTER_REPLACEMENT_RUNS_PER_PA * pa);
+
+        let nines = self.p_o as f64 / 27.0;
+        self.p_war = Self::war10((league.raw_fip() + PITCHER_REPLACEMENT_RUNS_PER_9 - self.raw_fip()) * nines);
+    }
+
+    // the sim's own league average rates over a fixed number of plate appearances, for both sides of the ball
+    fn neutral_league() -> Stats {
+        const PA: f64 = 10000.0;
+        let per = |expect: Expect| (LEAGUE_AVG[&expect] * PA).round() as u32;
+        let mut stats = Stats {
+            b_1b: per(Expect::Single),
+            b_2b: per(Expect::Double),
+            b_3b: per(Expect::Triple),
+            b_hr: per(Expect::HomeRun),
+            b_bb: per(Expect::Walk),
+            b_hbp: per(Expect::HitByPitch),
+            b_so: per(Expect::Strikeout),
+            b_o: per(Expect::Strikeout) + per(Expect::Out),
+            ..Stats::default()
+        };
+        stats.p_1b = stats.b_1b;
+        stats.p_2b = stats.b_2b;
+        stats.p_3b = stats.b_3b;
+        stats.p_hr = stats.b_hr;
+        stats.p_bb = stats.b_bb;
+        stats.p_hbp = stats.b_hbp;
+        stats.p_so = stats.b_so;
+        stats.p_o = stats.b_o;
+        stats.calculate();
+        stats
+    }
+
+    // the line as it would look in a neutral league: each event is scaled by how far its own league's rate
+    // was from the neutral one, plate appearances and innings stay put and runs follow the change in wOBA
+    pub(crate) fn neutralized(&self, league: &Stats) -> Stats {
+        let neutral = Self::neutral_league();
+        let rate = |count: u32, total: u32| count as f64 / total.max(1) as f64;
+        let scale = |own: u32, league_count: u32, league_total: u32, neutral_count: u32, neutral_total: u32| {
+            if league_count == 0 {
+                own
+            } else {
+                (own as f64 * rate(neutral_count, neutral_total) / rate(league_count, league_total)).round() as u32
+            }
+        };
+        let runs = neutral.b_woba as f64 / league.b_woba.max(1) as f64;
+        let scale_runs = |own: u32| (own as f64 * runs).round() as u32;
+
+        let mut stats = self.clone();
+        let bat = |own: u32, league_count: u32, neutral_count: u32| scale(own, league_count, league.b_pa, neutral_count, neutral.b_pa);
+        stats.b_1b = bat(self.b_1b, league.b_1b, neutral.b_1b);
+        stats.b_2b = bat(self.b_2b, league.b_2b, neutral.b_2b);
+        stats.b_3b = bat(self.b_3b, league.b_3b, neutral.b_3b);
+        stats.b_hr = bat(self.b_hr, league.b_hr, neutral.b_hr);
+        stats.b_bb = bat(self.b_bb, league.b_bb, neutral.b_bb);
+        stats.b_hbp = bat(self.b_hbp, league.b_hbp, neutral.b_hbp);
+        stats.b_ibb = self.b_ibb.min(stats.b_bb);
+        let on_base = stats.b_1b + stats.b_2b + stats.b_3b + stats.b_hr + stats.b_bb + stats.b_hbp;
+        stats.b_o = self.b_pa.saturating_sub(on_base);
+        stats.b_so = bat(self.b_so, league.b_so, neutral.b_so).min(stats.b_o);
+        stats.b_r = scale_runs(self.b_r);
+        stats.b_rbi = scale_runs(self.b_rbi);
+
+        let pit = |own: u32, league_count: u32, neutral_count: u32| scale(own, league_count, league.p_bf, neutral_count, neutral.p_bf);
+        stats.p_1b = pit(self.p_1b, league.p_1b, neutral.p_1b);
+        stats.p_2b = pit(self.p_2b, league.p_2b, neutral.p_2b);
+        stats.p_3b = pit(self.p_3b, league.p_3b, neutral.p_3b);
+        stats.p_hr = pit(self.p_hr, league.p_hr, neutral.p_hr);
+        stats.p_bb = pit(self.p_bb, league.p_bb, neutral.p_bb);
+        stats.p_hbp = pit(self.p_hbp, league.p_hbp, neutral.p_hbp);
+        stats.p_ibb = self.p_ibb.min(stats.p_bb);
+        stats.p_so = pit(self.p_so, league.p_so, neutral.p_so).min(self.p_o);
+        stats.p_r = scale_runs(self.p_r);
+        stats.p_er = scale_runs(self.p_er).min(stats.p_r);
+
+        stats.calculate();
+        stats
     }
 
     pub(crate) fn compile_stats(stream: &[Stat]) -> Stats {
@@ -404,6 +661,7 @@ impl Stats {
                 },
                 Stat::Bsb => stats.b_sb += 1,
                 Stat::Bcs => stats.b_cs += 1,
+                Stat::Boob => stats.b_oob += 1,
                 Stat::Br => stats.b_r += 1,
                 Stat::Brbi => stats.b_rbi += 1,
                 Stat::P1b => stats.p_1b += 1,
@@ -433,8 +691,19 @@ impl Stats {
                 Stat::Phld => stats.p_hld += 1,
                 Stat::Pcg => stats.p_cg += 1,
                 Stat::Psho => stats.p_sho += 1,
+                Stat::Pir => stats.p_ir += 1,
+                Stat::Pirs => stats.p_irs += 1,
+                Stat::Pqs => stats.p_qs += 1,
+                Stat::Prs => stats.p_rs += 1,
                 Stat::Fpo => stats.f_po += 1,
+                Stat::Fa => stats.f_a += 1,
                 Stat::Fe => stats.f_e += 1,
+                Stat::Fte => {
+                    stats.f_te += 1;
+                    stats.f_e += 1
+                }
+                Stat::Fsb => stats.f_sb += 1,
+                Stat::Fcs => stats.f_cs += 1,
                 _ => {}
             }
         }
@@ -444,10 +713,117 @@ impl Stats {
     }
 }
 
-#[derive(Default)]
+#[derive(Clone, Default)]
 pub(crate) struct HistoricalStats {
     pub(crate) year: u32,
     pub(crate) league: u32,
     pub(crate) team: TeamId,
     pub(crate) stats: Stats,
 }
+
+#[cfg(test)]
+mod tests {
+    use crate::stat::{Stat, Stats};
+
+    #[test]
+    fn test_innings_pitched() {
+        for outs in 0..30 {
+            let ip = Stat::Po.value(outs);
+            let (whole, thirds) = ip.split_once('.').unwrap();
+            assert_eq!(whole.parse::<u32>().unwrap(), outs / 3);
+            assert!(thirds.parse::<u32>().unwrap() <= 2, "{} outs displayed as {}", outs, ip);
+        }
+
+        let stream = [Stat::Po; 20];
+        assert_eq!(Stat::Po.value(Stats::compile_stats(&stream).p_o), "6.2");
+
+        let mut total = Stats::compile_stats(&[Stat::Po, Stat::Pso]);
+        total.compile(&Stats::compile_stats(&[Stat::Po, Stat::Po]));
+        assert_eq!(Stat::Po.value(total.p_o), "1.1");
+    }
+
+    #[test]
+    fn test_woba() {
+        let mut stream = Vec::new();
+        for (stat, count) in [(Stat::Bbb, 9), (Stat::Bibb, 1), (Stat::Bhbp, 2), (Stat::B1b, 15), (Stat::B2b, 5), (Stat::B3b, 1), (Stat::Bhr, 3), (Stat::Bo, 64)].iter() {
+            stream.extend(std::iter::repeat_n(*stat, *count));
+        }
+
+        // (9 * .69 + 2 * .72 + 15 * .89 + 5 * 1.27 + 1.62 + 3 * 2.10) / 100
+        let stats = Stats::compile_stats(&stream);
+        assert_eq!(stats.b_pa, 100);
+        assert_eq!(Stat::Bwoba.value(stats.get_stat(Stat::Bwoba)), "0.352");
+        assert!(Stat::Bwoba.is_rate() && !Stat::Bwoba.is_reverse_sort());
+
+        // a 40-team league's worth of plate appearances keeps the same rates
+        let mut league = Stats::default();
+        for _ in 0..4000 {
+            league.compile(&stats);
+        }
+        assert_eq!(league.b_pa, 400_000);
+        assert_eq!(league.b_woba, stats.b_woba);
+        assert_eq!(league.b_avg, stats.b_avg);
+        assert_eq!(league.b_slg, stats.b_slg);
+    }
+
+    #[test]
+    fn test_war() {
+        let line = |bb: usize, hr: usize, o: usize, so: usize| {
+            let mut stream = Vec::new();
+            for (stat, count) in [(Stat::Bbb, bb), (Stat::Bhr, hr), (Stat::B1b, 100), (Stat::Bo, o), (Stat::Pbb, bb), (Stat::Phr, hr), (Stat::Pso, so), (Stat::Po, o)].iter() {
+                stream.extend(std::iter::repeat_n(*stat, *count));
+            }
+            Stats::compile_stats(&stream)
+        };
+        let league = line(50, 20, 400, 100);
+
+        let mut average = line(50, 20, 400, 100);
+        average.calc_war(&league);
+        let mut good_batter = line(60, 30, 400, 100);
+        good_batter.calc_war(&league);
+        let mut good_pitcher = line(40, 10, 400, 150);
+        good_pitcher.calc_war(&league);
+        let mut weak_batter = line(0, 0, 800, 0);
+        weak_batter.calc_war(&league);
+        let mut weak_pitcher = line(100, 60, 400, 0);
+        weak_pitcher.calc_war(&league);
+
+        // an average player is still worth more than replacement
+        assert!(average.b_war > 0 && average.p_war > 0);
+        assert!(good_batter.b_war > average.b_war && good_pitcher.p_war > average.p_war);
+        assert_eq!((weak_batter.b_war, weak_pitcher.p_war), (0, 0));
+        assert_eq!(Stat::Bwar.value(27), "2.7");
+    }
+
+    #[test]
+    fn test_neutralized() {
+        let line = |hr: usize, o: usize, r: usize| {
+            let mut stream = Vec::new();
+            for (stat, count) in [(Stat::B1b, 130), (Stat::Bhr, hr), (Stat::Bbb, 80), (Stat::Bso, 190), (Stat::Bo, o), (Stat::Br, r)].iter() {
+                stream.extend(std::iter::repeat_n(*stat, *count));
+            }
+            Stats::compile_stats(&stream)
+        };
+        let player = line(40, 560, 100);
+
+        // a home run happy league makes the same line look less special once moved to neutral
+        let neutralized = player.neutralized(&line(80, 520, 100));
+        assert_eq!(neutralized.b_pa, player.b_pa);
+        assert!(neutralized.b_hr < player.b_hr);
+        assert!(neutralized.b_r < player.b_r);
+        assert!(neutralized.b_woba < player.b_woba);
+
+        // and a pitcher's duel league makes it look better
+        let neutralized = player.neutralized(&line(10, 610, 100));
+        assert!(neutralized.b_hr > player.b_hr);
+        assert!(neutralized.b_woba > player.b_woba);
+    }
+
+    #[test]
+    fn test_save_pct() {
+        assert_eq!(Stats::compile_stats(&[Stat::Po]).p_svpct, 0);
+
+        let stats = Stats::compile_stats(&[Stat::Psv, Stat::Psv, Stat::Psv, Stat::Psv, Stat::Psv, Stat::Psv, Stat::Pbs]);
+        assert_eq!(Stat::Psvpct.value(stats.get_stat(Stat::Psvpct)), "85.7%");
+    }
+}
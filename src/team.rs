@@ -1,22 +1,50 @@
-use std::cmp::{max, min};
-use std::collections::HashMap;
+use std::cmp::{max, min, Ordering};
+use std::collections::BTreeMap;
+use std::fmt;
 use enum_iterator::all;
+use ordinal::Ordinal;
 
 use crate::player::{Player, PlayerId, PlayerMap, PlayerRefMap, Position};
 use crate::data::{LocData, NickData};
+use crate::stat::Stats;
 
 pub(crate) type TeamId = u64;
-pub(crate) type TeamMap = HashMap<TeamId, Team>;
+pub(crate) type TeamMap = BTreeMap<TeamId, Team>;
+
+// seasons in a row finishing last in the lowest league before a franchise moves
+const RELOCATION_SEASONS: usize = 3;
+
+// the starting depth plus a couple of infield and an outfield fill-in
+pub(crate) const DEFAULT_ROSTER_SIZE: usize = 26;
+pub(crate) const MAX_ROSTER_SIZE: usize = 40;
 
 #[derive(Default, Copy, Clone)]
 pub(crate) struct Results {
     win: u32,
     lose: u32,
+    // only exhibition games capped by Game::max_innings can end level
+    tie: u32,
+    pub(crate) runs_for: u32,
+    pub(crate) runs_against: u32,
 }
 
 impl Results {
     pub(crate) fn games(&self) -> u32 {
-        self.win + self.lose
+        self.win + self.lose + self.tie
+    }
+
+    pub(crate) fn run_diff(&self) -> i32 {
+        self.runs_for as i32 - self.runs_against as i32
+    }
+
+    pub(crate) fn expected_wins(&self) -> u32 {
+        let rs = (self.runs_for as f64).powi(2);
+        let ra = (self.runs_against as f64).powi(2);
+        if rs + ra > 0.0 {
+            ((self.win + self.lose) as f64 * rs / (rs + ra)).round() as u32
+        } else {
+            0
+        }
     }
 }
 
@@ -24,43 +52,70 @@ impl Results {
     pub(crate) fn reset(&mut self) {
         self.win = 0;
         self.lose = 0;
+        self.tie = 0;
+        self.runs_for = 0;
+        self.runs_against = 0;
     }
 }
 
+#[derive(Clone)]
 pub(crate) struct HistoricalResults {
     pub(crate) year: u32,
     pub(crate) league: usize,
     pub(crate) rank: usize,
     pub(crate) win: u32,
     pub(crate) lose: u32,
+    pub(crate) champion: bool,
+}
+
+// field order matters: the derived ordering sorts higher leagues, then better ranks, first
+#[derive(Copy, Clone, Debug, PartialEq, Eq, PartialOrd, Ord)]
+pub(crate) struct LeaguePosition {
+    pub(crate) league: usize,
+    pub(crate) rank: usize,
+}
+
+impl fmt::Display for LeaguePosition {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{} in League {}", Ordinal(self.rank), self.league)
+    }
 }
 
-#[derive(Default)]
+#[derive(Clone, Default)]
 pub(crate) struct History {
     pub(crate) founded: u32,
-    pub(crate) best: Option<u32>,
-    pub(crate) worst: Option<u32>,
+    pub(crate) best: Option<LeaguePosition>,
+    pub(crate) worst: Option<LeaguePosition>,
     pub(crate) wins: u32,
     pub(crate) losses: u32,
     pub(crate) results: Vec<HistoricalResults>,
+    pub(crate) former_names: Vec<(u32, String)>,
 }
 
+#[derive(Clone)]
 pub(crate) struct Team {
     pub(crate) loc: LocData,
     pub(crate) nick: NickData,
     pub(crate) players: Vec<PlayerId>,
     pub(crate) rotation: [PlayerId; 5],
+    pub(crate) lineup: Option<[PlayerId; 9]>,
     pub(crate) results: Results,
     pub(crate) history: History,
+    pub(crate) popularity: f64,
+    // populate never fills past this
+    pub(crate) roster_size: usize,
 }
 
 impl Team {
     pub(crate) fn new(loc: LocData, nick: NickData, year: u32) -> Self {
         Self {
+            popularity: loc.base_popularity(),
+            roster_size: DEFAULT_ROSTER_SIZE,
             loc,
             nick,
             players: Vec::new(),
             rotation: [0, 0, 0, 0, 0],
+            lineup: None,
             results: Results::default(),
             history: History {
                 founded: year,
@@ -81,10 +136,12 @@ impl Team {
     }
 
     pub(crate) fn results(&mut self, us: u8, them: u8) {
-        if us > them {
-            self.results.win += 1;
-        } else {
-            self.results.lose += 1;
+        self.results.runs_for += us as u32;
+        self.results.runs_against += them as u32;
+        match us.cmp(&them) {
+            Ordering::Greater => self.results.win += 1,
+            Ordering::Less => self.results.lose += 1,
+            Ordering::Equal => self.results.tie += 1,
         }
     }
 
@@ -96,24 +153,52 @@ impl Team {
         self.results.lose
     }
 
+    pub(crate) fn get_ties(&self) -> u32 {
+        self.results.tie
+    }
+
     pub(crate) fn win_pct(&self) -> u32 {
         let denom = self.results.win + self.results.lose;
-        if denom > 0 {
-            (self.results.win * 1000 / denom) + 1
-        } else {
-            0
+        (self.results.win * 1000).checked_div(denom).map_or(0, |o| o + 1)
+    }
+
+    // the franchise keeps its history, only the name changes
+    pub(crate) fn relocate(&mut self, loc: LocData, nick: Option<NickData>, year: u32) {
+        self.history.former_names.push((year, self.name()));
+        self.popularity = loc.base_popularity();
+        self.loc = loc;
+        if let Some(nick) = nick {
+            self.nick = nick;
         }
     }
 
-    pub(crate) fn record_results(&mut self, year: u32, league_idx: usize, rank_idx: usize, results: Results) {
-        self.history.wins += self.results.win;
-        self.history.losses += self.results.lose;
+    // drifts toward a target set by market size, winning and league standing
+    pub(crate) fn update_popularity(&mut self, league: usize, rank: usize, league_size: usize) {
+        let win_pct = self.results.win as f64 / self.results.games().max(1) as f64;
+        let standing = 1.0 - (rank - 1) as f64 / league_size as f64;
+        let target = self.loc.base_popularity() + (win_pct - 0.5) * 40.0 + standing * 10.0 / league as f64;
+        self.popularity = (self.popularity * 0.75 + target * 0.25).clamp(0.0, 100.0);
+    }
+
+    pub(crate) fn championships(&self) -> usize {
+        self.history.results.iter().filter(|o| o.champion).count()
+    }
+
+    pub(crate) fn should_relocate(&self, league: usize, rank: usize) -> bool {
+        let results = &self.history.results;
+        results.len() >= RELOCATION_SEASONS && results[results.len() - RELOCATION_SEASONS..].iter().all(|o| o.league == league && o.rank == rank)
+    }
+
+    pub(crate) fn record_results(&mut self, year: u32, league_idx: usize, rank_idx: usize, champion: bool) {
+        let results = self.results;
+        self.history.wins += results.win;
+        self.history.losses += results.lose;
 
         let league = league_idx + 1;
         let rank = rank_idx + 1;
-        let pos = Some((league * 100 + rank) as u32);
-        self.history.best = if self.history.best.is_none() { pos } else { min(pos, self.history.best) };
-        self.history.worst = if self.history.worst.is_none() { pos } else { max(pos, self.history.worst) };
+        let pos = LeaguePosition { league, rank };
+        self.history.best = Some(self.history.best.map_or(pos, |o| min(o, pos)));
+        self.history.worst = Some(self.history.worst.map_or(pos, |o| max(o, pos)));
 
         self.history.results.push(HistoricalResults {
             year,
@@ -121,9 +206,110 @@ impl Team {
             rank,
             win: results.win,
             lose: results.lose,
+            // only the top league's title counts
+            champion: league == 1 && champion,
         });
     }
 
+    pub(crate) fn iter_players<'a>(&'a self, players: &'a PlayerMap) -> impl Iterator<Item = (PlayerId, &'a Player)> + 'a {
+        self.players.iter().map(move |o| (*o, players.get(o).unwrap()))
+    }
+
+    // the batters' or the pitchers' season stats summed, as shown in the team page TOTAL row
+    pub(crate) fn total_stats(&self, players: &PlayerMap, is_batter: bool) -> Stats {
+        let mut total = Stats::default();
+        for (_, player) in self.iter_players(players).filter(|o| o.1.pos.is_pitcher() != is_batter) {
+            total.compile(&player.get_stats());
+        }
+        total
+    }
+
+    // whoever rates highest by metric among those playing pos full time, skipping anyone in exclude
+    pub(crate) fn best_at<K: Ord>(&self, pos: Position, players: &PlayerMap, exclude: &[PlayerId], metric: impl Fn(&Player) -> K) -> Option<PlayerId> {
+        self.iter_players(players).filter(|o| o.1.pos == pos && !exclude.contains(&o.0)).max_by_key(|o| metric(o.1)).map(|o| o.0)
+    }
+
+    // one batter per position, best on-base percentage first
+    pub(crate) fn auto_lineup(&self, players: &PlayerMap) -> [PlayerId; 9] {
+        let mut batters = self.iter_players(players).filter(|o| !o.1.pos.is_pitcher()).collect::<Vec<_>>();
+        batters.sort_by_cached_key(|o| o.1.get_stats().b_obp);
+        batters.reverse();
+
+        let mut lineup = Vec::new();
+        let mut positions = Vec::new();
+        for (id, player) in &batters {
+            if !positions.contains(&player.pos) {
+                lineup.push(*id);
+                positions.push(player.pos);
+            }
+        }
+
+        // positions nobody plays full time go to the best bat left who still leaves every position covered
+        for (id, _) in &batters {
+            if lineup.len() < 9 && !lineup.contains(id) {
+                lineup.push(*id);
+                if Self::lineup_positions(&lineup, players).is_none() {
+                    lineup.pop();
+                }
+            }
+        }
+
+        // short a position nobody can cover, someone will have to play out of position
+        for (id, _) in &batters {
+            if lineup.len() < 9 && !lineup.contains(id) {
+                lineup.push(*id);
+            }
+        }
+
+        let mut full = [0; 9];
+        full[..lineup.len()].copy_from_slice(&lineup);
+        full
+    }
+
+    // primary positions are tried first, a secondary only when the lineup can't be covered otherwise
+    pub(crate) fn lineup_positions(lineup: &[PlayerId], players: &PlayerMap) -> Option<Vec<Position>> {
+        let options = lineup.iter()
+            .map(|o| players.get(o).map(|p| std::iter::once(p.pos).chain(p.secondary.iter().copied()).collect::<Vec<_>>()))
+            .collect::<Option<Vec<_>>>()?;
+        let mut positions = Vec::new();
+        if Self::assign_positions(&options, &mut positions) { Some(positions) } else { None }
+    }
+
+    // same as lineup_positions, but anyone can be stuck anywhere as a last resort
+    pub(crate) fn emergency_positions(lineup: &[PlayerId], players: &PlayerMap) -> Vec<Position> {
+        let options = lineup.iter()
+            .map(|o| {
+                let p = players.get(o).unwrap();
+                std::iter::once(p.pos).chain(p.secondary.iter().copied()).chain(all::<Position>()).collect::<Vec<_>>()
+            })
+            .collect::<Vec<_>>();
+        let mut positions = Vec::new();
+        Self::assign_positions(&options, &mut positions);
+        positions
+    }
+
+    fn assign_positions(options: &[Vec<Position>], positions: &mut Vec<Position>) -> bool {
+        let idx = positions.len();
+        if idx == options.len() {
+            return true;
+        }
+        for pos in &options[idx] {
+            if !pos.is_pitcher() && !positions.contains(pos) {
+                positions.push(*pos);
+                if Self::assign_positions(options, positions) {
+                    return true;
+                }
+                positions.pop();
+            }
+        }
+        false
+    }
+
+    // a stored lineup goes stale when players leave or change position
+    pub(crate) fn is_valid_lineup(&self, lineup: &[PlayerId; 9], players: &PlayerMap) -> bool {
+        lineup.iter().all(|o| self.players.contains(o)) && Self::lineup_positions(lineup, players).is_some()
+    }
+
     fn players_per_position(pos: Position) -> usize {
         match pos {
             Position::StartingPitcher => 5,
@@ -134,6 +320,11 @@ impl Team {
         }
     }
 
+    // every position filled to its depth, with nobody on the bench
+    pub(crate) fn min_roster_size() -> usize {
+        all::<Position>().map(Self::players_per_position).sum()
+    }
+
     fn count_at(&self, players: &PlayerMap, pred: &dyn Fn(&&Player) -> bool) -> usize {
         self.players.iter().filter_map(|o| players.get(o)).filter(pred).count()
     }
@@ -151,6 +342,9 @@ impl Team {
     fn fill_in(&mut self, available: &mut PlayerRefMap<'_>, players: &PlayerMap, max: usize, pred: &dyn Fn(&&Player) -> bool) {
         let cur = self.count_at(players, pred);
         for _ in cur..max {
+            if self.players.len() >= self.roster_size {
+                break;
+            }
             if let Some(id) = Self::pick(available, pred) {
                 self.players.push(id);
             }
@@ -162,6 +356,20 @@ impl Team {
             let max = Self::players_per_position(pos);
             let exact_position = |o: &&Player| o.pos == pos;
             self.fill_in(available, players, max, &exact_position);
+
+            // nobody left at the position, so sign someone who can cover it
+            let cur = self.count_at(players, &exact_position);
+            if !pos.is_pitcher() && cur < max {
+                let covers = |o: &&Player| o.secondary.contains(&pos);
+                for _ in cur..max {
+                    if self.players.len() >= self.roster_size {
+                        break;
+                    }
+                    if let Some(id) = Self::pick(available, &covers) {
+                        self.players.push(id);
+                    }
+                }
+            }
         }
 
         let is_infield = |o: &&Player| o.pos.is_infield();
@@ -170,9 +378,270 @@ impl Team {
         let is_outfield = |o: &&Player| o.pos.is_outfield();
         self.fill_in(available, players, 4, &is_outfield);
 
-        let pitchers = self.players.iter().filter(|o| players.get(o).unwrap().pos == Position::StartingPitcher).collect::<Vec<_>>();
-        for (idx, p) in pitchers[0..5].iter().enumerate() {
-            self.rotation[idx] = **p;
+        // anything left over goes to bench bats and extra arms
+        let is_bench = |o: &&Player| o.pos != Position::StartingPitcher;
+        self.fill_in(available, players, self.roster_size, &is_bench);
+
+        // keep the existing order for starters who are still here, new ones go to the back
+        let is_starter = |o: &PlayerId| self.players.contains(o) && players.get(o).unwrap().pos == Position::StartingPitcher;
+        let mut pitchers = Vec::new();
+        for p in self.rotation.iter().chain(self.players.iter()).copied().filter(is_starter) {
+            if !pitchers.contains(&p) {
+                pitchers.push(p);
+            }
+        }
+        self.rotation.copy_from_slice(&pitchers[0..5]);
+    }
+}
+
+#[cfg(test)]
+pub(crate) mod tests {
+    use std::collections::HashSet;
+
+    use ordinal::Ordinal;
+    use rand::{Rng, SeedableRng};
+    use rand::rngs::StdRng;
+
+    use crate::data::Data;
+    use crate::player::{collect_all_active, generate_players, AgeProfile, Player, PlayerMap, PlayerRefMap, Position};
+    use crate::stat::Stat;
+    use crate::team::{LeaguePosition, Team, TeamId, TeamMap, DEFAULT_ROSTER_SIZE};
+
+    // a fresh team with no roster, named at random
+    pub(crate) fn test_team(data: &Data, rng: &mut impl Rng) -> Team {
+        let loc = data.get_locs(&mut HashSet::new(), rng, 1).remove(0);
+        let nick = data.get_nicks(&mut HashSet::new(), rng, 1).remove(0);
+        Team::new(loc, nick, 2049)
+    }
+
+    // count teams numbered from 1, each filled from a pool of PLAYERS_PER_TEAM generated players
+    pub(crate) fn setup_teams(count: usize, year: u32, data: &Data, rng: &mut impl Rng) -> (TeamMap, PlayerMap) {
+        const PLAYERS_PER_TEAM: usize = 75;
+        let mut players = PlayerMap::new();
+        generate_players(&mut players, count * PLAYERS_PER_TEAM, year, data, &AgeProfile::default(), rng);
+        let locs = data.get_locs(&mut HashSet::new(), rng, count);
+        let nicks = data.get_nicks(&mut HashSet::new(), rng, count);
+        let mut teams = TeamMap::new();
+        let mut available = collect_all_active(&players);
+        for idx in 0..count {
+            let mut team = Team::new(locs[idx].clone(), nicks[idx].clone(), year);
+            team.populate(&mut available, &players);
+            teams.insert(idx as TeamId + 1, team);
+        }
+        (teams, players)
+    }
+
+    #[test]
+    fn test_run_differential() {
+        let data = Data::new();
+        let mut rng = StdRng::seed_from_u64(2049);
+        let mut team = test_team(&data, &mut rng);
+
+        team.results(5, 3);
+        team.results(2, 1);
+        assert_eq!(team.results.runs_for, 7);
+        assert_eq!(team.results.runs_against, 4);
+        assert_eq!(team.results.run_diff(), 3);
+
+        team.results(4, 4);
+        assert_eq!((team.get_wins(), team.get_losses(), team.get_ties()), (2, 0, 1));
+        assert_eq!(team.results.games(), 3);
+        assert_eq!(team.win_pct(), 1001);
+
+        team.results.reset();
+        assert_eq!(team.results.runs_for, 0);
+        assert_eq!(team.results.runs_against, 0);
+        assert_eq!(team.results.games(), 0);
+    }
+
+    #[test]
+    fn test_franchise_totals() {
+        let data = Data::new();
+        let mut rng = StdRng::seed_from_u64(2049);
+        let mut team = test_team(&data, &mut rng);
+
+        for (season, (wins, losses)) in [(3, 1), (0, 4), (2, 2)].iter().enumerate() {
+            for _ in 0..*wins {
+                team.results(4, 2);
+            }
+            for _ in 0..*losses {
+                team.results(1, 5);
+            }
+            team.record_results(2049 + season as u32, 0, season, false);
+            team.results.reset();
+        }
+
+        assert_eq!(team.history.results.len(), 3);
+        assert_eq!(team.history.wins, team.history.results.iter().map(|o| o.win).sum::<u32>());
+        assert_eq!(team.history.losses, team.history.results.iter().map(|o| o.lose).sum::<u32>());
+        assert_eq!((team.history.wins, team.history.losses), (5, 7));
+    }
+
+    #[test]
+    fn test_league_position() {
+        let data = Data::new();
+        let mut rng = StdRng::seed_from_u64(2049);
+        let mut team = test_team(&data, &mut rng);
+        assert_eq!(team.history.best, None);
+
+        for league_idx in 0..3 {
+            for rank_idx in 0..20 {
+                team.record_results(2049, league_idx, rank_idx, rank_idx == 0);
+                let pos = team.history.results.last().map(|o| LeaguePosition { league: o.league, rank: o.rank }).unwrap();
+                assert_eq!(pos.to_string(), format!("{} in League {}", Ordinal(rank_idx + 1), league_idx + 1));
+            }
+        }
+
+        assert_eq!(team.history.best, Some(LeaguePosition { league: 1, rank: 1 }));
+        assert_eq!(team.championships(), 1);
+        assert_eq!(team.history.worst, Some(LeaguePosition { league: 3, rank: 20 }));
+        assert_eq!(team.history.worst.unwrap().to_string(), "20th in League 3");
+    }
+
+    #[test]
+    fn test_rotation_order() {
+        let data = Data::new();
+        let mut rng = StdRng::seed_from_u64(2049);
+        let mut players = PlayerMap::new();
+        generate_players(&mut players, 100, 2049, &data, &AgeProfile::default(), &mut rng);
+
+        let mut team = test_team(&data, &mut rng);
+        team.populate(&mut collect_all_active(&players), &players);
+
+        let mut rotation = team.rotation;
+        rotation.reverse();
+        team.rotation = rotation;
+
+        // repopulating with an unchanged roster keeps the user's order
+        team.populate(&mut PlayerRefMap::new(), &players);
+        assert_eq!(team.rotation, rotation);
+
+        // a departed starter is replaced at the back
+        let departed = rotation[1];
+        team.players.retain(|o| *o != departed);
+        let mut available = collect_all_active(&players);
+        available.retain(|k, _| !team.players.contains(k) && *k != departed);
+        team.populate(&mut available, &players);
+        assert_eq!(&team.rotation[..4], &[rotation[0], rotation[2], rotation[3], rotation[4]]);
+        assert!(!team.rotation.contains(&departed));
+    }
+
+    #[test]
+    fn test_roster_size() {
+        let data = Data::new();
+        let mut rng = StdRng::seed_from_u64(2049);
+        let mut players = PlayerMap::new();
+        generate_players(&mut players, 300, 2049, &data, &AgeProfile::default(), &mut rng);
+        let mut available = collect_all_active(&players);
+
+        for roster_size in [Team::min_roster_size(), DEFAULT_ROSTER_SIZE, 30].iter() {
+            let mut team = test_team(&data, &mut rng);
+            team.roster_size = *roster_size;
+            team.populate(&mut available, &players);
+            assert_eq!(team.players.len(), *roster_size);
+
+            // repopulating a full roster adds nobody
+            team.populate(&mut available, &players);
+            assert_eq!(team.players.len(), *roster_size);
+        }
+    }
+
+    #[test]
+    fn test_secondary_positions() {
+        let data = Data::new();
+        let mut rng = StdRng::seed_from_u64(2049);
+        let mut players = PlayerMap::new();
+        generate_players(&mut players, 200, 2049, &data, &AgeProfile::default(), &mut rng);
+
+        let mut team = test_team(&data, &mut rng);
+        team.populate(&mut collect_all_active(&players), &players);
+
+        // the shortstop leaves and the only one left on the market is a second baseman who can cover
+        team.players.retain(|o| players[o].pos != Position::ShortStop);
+        let cover = *players.iter().find(|(k, v)| v.pos == Position::SecondBase && !team.players.contains(k)).unwrap().0;
+        players.get_mut(&cover).unwrap().secondary = vec![Position::ShortStop];
+        let mut available = collect_all_active(&players);
+        available.retain(|k, v| !team.players.contains(k) && v.pos != Position::ShortStop);
+        available.retain(|k, v| *k == cover || !v.secondary.contains(&Position::ShortStop));
+        team.populate(&mut available, &players);
+        assert!(team.players.contains(&cover));
+
+        // whoever ends up there, the lineup still covers short
+        let lineup = team.auto_lineup(&players);
+        let positions = Team::lineup_positions(&lineup, &players).unwrap();
+        assert!(positions.contains(&Position::ShortStop));
+        assert!(team.is_valid_lineup(&lineup, &players));
+    }
+
+    #[test]
+    fn test_best_at() {
+        let data = Data::new();
+        let mut rng = StdRng::seed_from_u64(2049);
+        let mut players = PlayerMap::new();
+        generate_players(&mut players, 200, 2049, &data, &AgeProfile::default(), &mut rng);
+
+        let mut team = test_team(&data, &mut rng);
+        team.populate(&mut collect_all_active(&players), &players);
+
+        let catchers = team.players.iter().copied().filter(|o| players[o].pos == Position::Catcher).collect::<Vec<_>>();
+        assert!(catchers.len() > 1);
+        for id in &catchers {
+            let player = players.get_mut(id).unwrap();
+            player.record_stat(Stat::Bo);
+            player.record_stat(Stat::Bo);
+        }
+        let best = catchers[catchers.len() - 1];
+        players.get_mut(&best).unwrap().record_stat(Stat::B1b);
+
+        let obp = |o: &Player| o.get_stats().b_obp;
+        assert_eq!(team.best_at(Position::Catcher, &players, &[], obp), Some(best));
+        let next = team.best_at(Position::Catcher, &players, &[best], obp).unwrap();
+        assert!(next != best && catchers.contains(&next));
+        assert_eq!(team.best_at(Position::Catcher, &players, &catchers, obp), None);
+    }
+
+    #[test]
+    fn test_relocate() {
+        let data = Data::new();
+        let mut rng = StdRng::seed_from_u64(2049);
+        let mut team = test_team(&data, &mut rng);
+        let (used, nick) = ([team.loc.clone()].iter().cloned().collect(), team.nick.clone());
+
+        for year in 2049..2052 {
+            team.results(1, 2);
+            team.record_results(year, 2, 19, false);
+            team.results.reset();
+        }
+        assert!(team.should_relocate(3, 20));
+        assert!(!team.should_relocate(3, 19));
+
+        let old_name = team.name();
+        let new_loc = data.get_unused_loc(&used, &mut rng).unwrap();
+        team.relocate(new_loc.clone(), None, 2052);
+
+        assert!(team.loc == new_loc);
+        assert!(team.nick == nick);
+        assert_eq!(team.history.former_names, vec![(2052, old_name)]);
+        assert_eq!(team.history.results.len(), 3);
+        assert_eq!(team.history.losses, 3);
+    }
+
+    #[test]
+    fn test_popularity() {
+        let data = Data::new();
+        let mut rng = StdRng::seed_from_u64(2049);
+        let mut winner = test_team(&data, &mut rng);
+        let loc = winner.loc.clone();
+        let mut loser = Team::new(loc.clone(), winner.nick.clone(), 2049);
+        assert_eq!(winner.popularity, loc.base_popularity());
+
+        for _ in 0..10 {
+            winner.results(5, 1);
+            loser.results(1, 5);
         }
+        winner.update_popularity(1, 1, 20);
+        loser.update_popularity(1, 20, 20);
+        assert!(winner.popularity > loc.base_popularity());
+        assert!(loser.popularity < loc.base_popularity());
     }
 }
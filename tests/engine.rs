@@ -0,0 +1,26 @@
+use imp019::{Engine, Stat};
+
+// drives the engine the way an embedding crate would, through the public API only
+#[test]
+fn test_headless_season() {
+    let mut engine = Engine::new(Some(2049));
+    let year = engine.year();
+    assert_eq!(Engine::new(Some(engine.seed())).standings(0), engine.standings(0));
+
+    for _ in 0..10 {
+        assert!(!engine.sim_day());
+    }
+    let standings = engine.standings(0).unwrap();
+    assert!(!standings.is_empty());
+    assert!(engine.standings(engine.league_count()).is_none());
+
+    let leaders = engine.leaders(0, Stat::Bhr, true, false).unwrap();
+    let (player_id, hr) = leaders[0];
+    let stats = engine.player_stats(player_id).unwrap();
+    assert_eq!(stats.get_stat(Stat::Bhr), hr);
+    assert!(!Stat::Bavg.value(stats.get_stat(Stat::Bavg)).is_empty());
+    assert!(engine.leaders(engine.league_count(), Stat::Bhr, true, false).is_none());
+
+    engine.sim_season();
+    assert_eq!(engine.year(), year + 1);
+}